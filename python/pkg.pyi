@@ -1,6 +1,6 @@
 """Type stubs for packager - Rust package manager with Python bindings."""
 
-from typing import Optional, Dict, List, Any, Union
+from typing import Optional, Dict, List, Any, Union, Tuple, Set
 from enum import IntEnum
 
 class SolveStatus(IntEnum):
@@ -26,6 +26,8 @@ class Action:
     Set: "Action"
     Append: "Action"
     Insert: "Action"
+    Prepend: "Action"
+    Unset: "Action"
 
 class Evar:
     """Environment variable with action semantics."""
@@ -33,12 +35,20 @@ class Evar:
     name: str
     value: str
     action: Action
+    secret: bool  # mask value in repr/display; with secret=True a "file:<path>" value is read on solve
+    referencable: bool  # may be referenced as {NAME} in strict solve mode
+    position: Optional[int]  # list index for "insert" (None = front)
+    separator: Optional[str]  # list separator (None = OS path separator)
     
     def __init__(
         self,
         name: str,
         value: str,
-        action: str = "set"  # "set", "append", "insert"
+        action: str = "set",  # "set", "append", "insert", "prepend", "unset"
+        secret: bool = False,
+        referencable: bool = False,
+        position: Optional[int] = None,
+        separator: Optional[str] = None
     ) -> None: ...
     
     def solve(self, env: "Env", max_depth: int = 10) -> "Evar":
@@ -55,6 +65,15 @@ class Evar:
     @staticmethod
     def from_json(s: str) -> "Evar": ...
 
+class EnvGuard:
+    """Restore handle from Env.commit_scoped(); context manager."""
+    names: List[str]
+    def restore(self) -> None:
+        """Put back the values from before the commit (idempotent)."""
+        ...
+    def __enter__(self) -> "EnvGuard": ...
+    def __exit__(self, exc_type: Any = None, exc_value: Any = None, traceback: Any = None) -> bool: ...
+
 class Env:
     """Named collection of environment variables."""
     
@@ -75,17 +94,61 @@ class Env:
         """Merge with another env."""
         ...
     
-    def compress(self) -> "Env":
-        """Collapse same-name evars using action semantics."""
+    def compress(self, dedup: bool = False) -> "Env":
+        """Collapse same-name evars using action semantics (dedup: drop repeated list entries)."""
         ...
     
-    def solve(self, max_depth: int = 10, compress: bool = True) -> "Env":
-        """Expand all {TOKEN} references."""
+    def solve(self, max_depth: int = 10, compress: bool = True, strict: bool = False, dollar_syntax: bool = False) -> "Env":
+        """Expand all {TOKEN} references (strict: only referencable evars; dollar_syntax: also ${TOKEN})."""
         ...
     
     def commit(self) -> None:
         """Apply to current process environment."""
         ...
+
+    def commit_scoped(self) -> "EnvGuard":
+        """Apply to process; the returned guard restores prior values (use with `with`)."""
+        ...
+
+    def diff_os(self) -> List[Tuple[str, Optional[str], str]]:
+        """(name, old, new) for each variable commit() would change; old is None if unset."""
+        ...
+
+    def commit_filtered(self, blacklist: Optional[List[str]] = None) -> List[str]:
+        """Apply, skipping blacklisted names (case-insensitive); returns skipped names."""
+        ...
+
+    @staticmethod
+    def default_blacklist() -> List[str]:
+        """$PKG_ENV_BLACKLIST (comma-separated) or LD_PRELOAD, LD_AUDIT, DYLD_INSERT_LIBRARIES."""
+        ...
+    
+    def to_pairs(self) -> List[Tuple[str, str]]:
+        """Ordered (name, value) pairs."""
+        ...
+    
+    def duplicates(self) -> List[Tuple[str, int]]:
+        """Variables defined more than once, with counts (before compression)."""
+        ...
+    
+    @staticmethod
+    def from_prelude() -> Optional["Env"]:
+        """Load site prelude env ($PKG_PRELUDE or ~/.pkg-rs/prelude.json)."""
+        ...
+    
+    @staticmethod
+    def set_prelude(prelude: Optional["Env"]) -> None:
+        """Install the prelude merged under every Package env (None removes it)."""
+        ...
+    
+    @staticmethod
+    def prelude() -> Optional["Env"]:
+        """Currently installed prelude, if any."""
+        ...
+    
+    def compare(self, other: "Env") -> Dict[str, Any]:
+        """Compare with another env: {only_self, only_other, differing: {name: (self, other)}}."""
+        ...
     
     def to_dict(self) -> Dict[str, Any]: ...
     
@@ -108,6 +171,8 @@ class App:
     args: List[str]
     cwd: Optional[str]
     properties: Dict[str, str]
+    env_overrides: List[Evar]
+    detached: bool
     
     def __init__(
         self,
@@ -117,6 +182,8 @@ class App:
         args: Optional[List[str]] = None,
         cwd: Optional[str] = None,
         properties: Optional[Dict[str, str]] = None,
+        env_overrides: Optional[List[Evar]] = None,
+        detached: bool = False,
     ) -> None: ...
     
     def with_path(self, path: str) -> "App":
@@ -139,11 +206,19 @@ class App:
         """Builder: set property."""
         ...
     
+    def with_override(self, evar: Evar) -> "App":
+        """Builder: add evar layered on top of the app's env."""
+        ...
+    
     def get_prop(self, key: str) -> Optional[str]: ...
     def set_prop(self, key: str, value: str) -> None: ...
     def has_prop(self, key: str) -> bool: ...
     def remove_prop(self, key: str) -> Optional[str]: ...
     
+    def add_override(self, evar: Evar) -> None:
+        """Add an evar layered on top of the app's env (use instead of env_overrides.append)."""
+        ...
+    
     def effective_cwd(self) -> Optional[str]:
         """Get working directory (explicit or from path)."""
         ...
@@ -163,18 +238,20 @@ class App:
     def launch(
         self,
         env: Optional[Union["Env", Dict[str, str]]] = None,
-        extra_args: Optional[List[str]] = None,
+        args: Optional[List[str]] = None,
         wait: bool = False,
     ) -> int:
         """Launch the application.
         
+        Detaches fully from the caller when `detached` is set.
+        
         Args:
             env: Environment - Env object or dict {"VAR": "value"}
-            extra_args: Additional command-line arguments
+            args: Additional command-line arguments
             wait: Wait for process to complete
             
         Returns:
-            Exit code if wait=True, else 0
+            Exit code if wait=True, else the child's PID
         """
         ...
     
@@ -197,9 +274,19 @@ class Package:
     envs: List[Env]
     apps: List[App]
     reqs: List[str]
+    extras: Dict[str, List[str]]  # optional req groups, requested as name[group]
     deps: List[str]
+    provides: List[str]  # virtual "base@version" names, e.g. ["ocio@2.3.0"]
     tags: List[str]
     icon: Optional[str]
+    deprecated: bool  # assigning a str also sets deprecated_reason
+    deprecated_reason: Optional[str]
+    hidden: bool
+    source_only: bool  # needs a build until install_path exists
+    install_path: Optional[str]
+    tests: Dict[str, str]  # name -> shell command, run by `pkg test`
+    released: Optional[int]  # unix seconds; defaults to the package.py mtime
+    origin_layer: Optional[int]  # index of the storage location it came from
     solve_status: SolveStatus
     solve_error: Optional[str]
     
@@ -210,6 +297,10 @@ class Package:
     def add_req(self, req: str) -> None: ...
     def add_tag(self, tag: str) -> None: ...
     
+    def reqs_for(self, extras: List[str]) -> List[str]:
+        """Requirements plus those of the given extra groups."""
+        ...
+    
     def get_env(self, name: str) -> Optional[Env]: ...
     def get_app(self, name: str) -> Optional[App]: ...
     
@@ -223,7 +314,7 @@ class Package:
     def env_names(self) -> List[str]: ...
     
     def effective_env(self, app_name: Optional[str] = None) -> Optional[Env]:
-        """Get solved environment for an app."""
+        """Get solved environment for an app, with its env_overrides applied."""
         ...
     
     def semver(self) -> str:
@@ -260,17 +351,34 @@ class Package:
 class Loader:
     """Package.py file loader."""
     
+    sys_paths: List[str]  # prepended to sys.path ($PKG_SYS_PATH + with_sys_paths)
+    
     def __init__(self, use_cache: Optional[bool] = None) -> None: ...
     
-    def load(self, path: str, **kwargs: Any) -> Package:
+    def with_sys_paths(self, paths: List[str]) -> "Loader":
+        """Builder: add helper module dirs to prepend to sys.path."""
+        ...
+    
+    def load(self, path: str, *args: str, **kwargs: Any) -> Package:
         """Load package from package.py file.
         
         Args:
             path: Path to package.py file
+            *args: Positional arguments for get_package()
             **kwargs: Keyword arguments for get_package()
         """
         ...
     
+    def load_all(self, path: str, *args: str, **kwargs: Any) -> List[Package]:
+        """Load every package a file defines (get_packages() or get_package()).
+        
+        Args:
+            path: Path to package.py file
+            *args: Positional arguments for the get function
+            **kwargs: Keyword arguments for the get function
+        """
+        ...
+    
     def clear_cache(self) -> None:
         """Clear the package cache."""
         ...
@@ -288,19 +396,74 @@ class Solver:
     
     def __init__(self, packages: List[Package]) -> None: ...
     
-    def solve(self, root: str) -> List[str]:
+    def solve(self, root: str, prefer: str = "newest", timeout_steps: Optional[int] = None) -> List[str]:
         """Resolve dependencies for a package."""
         ...
+
+    def solve_ordered(self, root: str) -> List[str]:
+        """Resolve dependencies, each package after its dependencies (install order)."""
+        ...
     
     def solve_reqs(self, reqs: List[str]) -> List[str]:
         """Resolve a list of requirements."""
         ...
 
+    def solve_reqs_bounded(self, reqs: List[str], max_packages: int) -> List[str]:
+        """Resolve requirements, failing if more than max_packages are resolved."""
+        ...
+
+    def solve_reqs_capped(self, reqs: List[str], caps: List[str]) -> List[str]:
+        """Resolve with constraint-only caps like 'ocio@<3' (bound if pulled in, never added)."""
+        ...
+
+    def solve_reqs_capped_detailed(
+        self, reqs: List[str], caps: List[str]
+    ) -> Tuple[List[str], List[str], List[str]]:
+        """Capped resolve returning (packages, applied weak '~' reqs, skipped weak reqs whose base is absent)."""
+        ...
+
+    def solve_reqs_restricted(self, reqs: List[str], allowed_bases: Set[str]) -> List[str]:
+        """Resolve using only allowed bases; raises naming a forbidden dependency."""
+        ...
+
+    def solve_best_effort(self, reqs: List[str]) -> Tuple[List[str], List[str]]:
+        """Greedy partial resolve: (resolved names, unmet requirements). Approximate."""
+        ...
+
+    def check_world(self, bases: List[str]) -> List[str]:
+        """Resolve the latest version of each base together; raises on conflict."""
+        ...
+
+    def build_plan(self, target: str) -> List[str]:
+        """Source-only packages of the resolution without an install, dependencies first."""
+        ...
+
+    def freeze(self, reqs: List[str]) -> List[str]:
+        """Resolve to sorted exact 'base-version' names (re-usable as requirements)."""
+        ...
+
+    def verify_resolution(self, exact: List[str]) -> None:
+        """Check that exact 'base-version' pins satisfy all their requirements."""
+        ...
+
+    def compatible_versions(self, reqs: List[str], base: str) -> List[str]:
+        """Get versions of base allowed by reqs (shallow check, newest first)."""
+        ...
+
+    def available_for(self, req: str) -> List[str]:
+        """Full names of all versions matching req, newest first."""
+        ...
+
+    def selection_rationale(self, requirements: List[str]) -> List[Tuple[str, str]]:
+        """Explain why each resolved version was chosen: [(name, reason), ...]."""
+        ...
+
 class Storage:
     """Package scanner and registry."""
     
     packages: List[Package]
     locations: List[str]
+    readonly_locations: List[str]
     warnings: List[str]
     
     def __init__(self) -> None: ...
@@ -315,6 +478,11 @@ class Storage:
         """Scan specific paths."""
         ...
     
+    @staticmethod
+    def scan_layered(layers: List[str]) -> "Storage":
+        """Scan layers, highest priority first; earlier layers override later ones."""
+        ...
+    
     def get(self, name: str) -> Optional[Package]:
         """Get package by full name."""
         ...
@@ -322,6 +490,10 @@ class Storage:
     def versions(self, base: str) -> List[str]:
         """Get all versions of a package (newest first)."""
         ...
+
+    def version_origins(self, base: str) -> List[Tuple[str, str]]:
+        """(full name, source location) for each scanned version, newest first."""
+        ...
     
     def bases(self) -> List[str]:
         """Get all base package names."""
@@ -330,8 +502,16 @@ class Storage:
     def has(self, name: str) -> bool: ...
     def has_base(self, base: str) -> bool: ...
     
-    def list(self, tags: Optional[List[str]] = None) -> List[Package]:
-        """List packages, optionally filtered by tags."""
+    def list(self, tags: Optional[List[str]] = None, include_hidden: bool = False) -> List[Package]:
+        """List packages, optionally filtered by tags (hidden skipped by default)."""
+        ...
+
+    def libraries(self) -> List[str]:
+        """Get names of packages without apps (sorted)."""
+        ...
+
+    def applications(self) -> List[str]:
+        """Get names of packages providing apps (sorted)."""
         ...
     
     def find(self, pattern: str) -> List[str]:
@@ -14,6 +14,8 @@
 //! - **args**: Default command-line arguments
 //! - **cwd**: Working directory (defaults to executable's parent directory)
 //! - **properties**: Custom metadata (icon, hidden flags, engine type, etc.)
+//! - **env_overrides**: Extra variables layered on top of the app's env
+//! - **detached**: Launch in its own session, outliving the launcher
 //!
 //! # Package.py Example
 //!
@@ -28,7 +30,7 @@
 //!     # Create environment
 //!     env = Env("default")
 //!     env.add(Evar("MAYA_ROOT", str(Path("/opt/autodesk/maya2026"))))
-//!     pkg.envs.append(env)
+//!     pkg.add_env(env)
 //!
 //!     # Create application
 //!     if sys.platform == "win32":
@@ -43,7 +45,7 @@
 //!         args=["-noAutoloadPlugins"],
 //!         properties={"icon": "maya.png", "engine": "tk-maya"}
 //!     )
-//!     pkg.apps.append(app)
+//!     pkg.add_app(app)
 //!
 //!     return pkg
 //! ```
@@ -86,20 +88,28 @@
 //!   "env_name": "default",
 //!   "args": ["-batch"],
 //!   "cwd": null,
-//!   "properties": {"icon": "maya.png"}
+//!   "properties": {"icon": "maya.png"},
+//!   "env_overrides": [{"name": "RENDER_NODE", "value": "1", "action": "set"}],
+//!   "detached": false
 //! }
 //! ```
 
+use crate::env::Env;
+use crate::error::{AppError, EnvError};
+use crate::evar::Evar;
+use crate::token;
+use log::warn;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 
 /// Application definition within a package.
 ///
-/// Represents an executable that can be launched with a specific environment.
-/// The actual launching logic is handled by CLI/GUI, not by App itself.
+/// Represents an executable that can be launched with a specific environment
+/// via [`launch`](App::launch).
 ///
 /// # Fields
 ///
@@ -109,6 +119,8 @@ use std::path::{Path, PathBuf};
 /// - `args`: Default arguments passed to the executable
 /// - `cwd`: Working directory for launch (None = use executable's parent)
 /// - `properties`: Arbitrary key-value metadata for UI and extensions
+/// - `env_overrides`: Evars applied on top of the named env at launch
+/// - `detached`: Fully detach the launched process from the launcher
 #[pyclass]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct App {
@@ -141,6 +153,20 @@ pub struct App {
     /// Convention-based keys - see module docs for common ones.
     #[pyo3(get, set)]
     pub properties: HashMap<String, String>,
+
+    /// Extra evars layered on top of the app's env.
+    /// Applied after the package env, so they win on conflicts.
+    /// From Python use `add_override` - the getter returns a copy, so
+    /// `app.env_overrides.append(...)` is lost.
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_overrides: Vec<Evar>,
+
+    /// Launch in a new session (Unix `setsid`, Windows `DETACHED_PROCESS`)
+    /// with null stdio, so the app survives the launcher exiting.
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub detached: bool,
 }
 
 #[pymethods]
@@ -154,8 +180,10 @@ impl App {
     /// * `args` - Optional default arguments
     /// * `cwd` - Optional working directory
     /// * `properties` - Optional custom properties
+    /// * `env_overrides` - Optional evars layered on top of the app's env
+    /// * `detached` - Detach the launched process (default: false)
     #[new]
-    #[pyo3(signature = (name, path = None, env_name = None, args = None, cwd = None, properties = None))]
+    #[pyo3(signature = (name, path = None, env_name = None, args = None, cwd = None, properties = None, env_overrides = None, detached = false))]
     pub fn new(
         name: String,
         path: Option<String>,
@@ -163,6 +191,8 @@ impl App {
         args: Option<Vec<String>>,
         cwd: Option<String>,
         properties: Option<HashMap<String, String>>,
+        env_overrides: Option<Vec<Evar>>,
+        detached: bool,
     ) -> Self {
         Self {
             name,
@@ -171,6 +201,8 @@ impl App {
             args: args.unwrap_or_default(),
             cwd,
             properties: properties.unwrap_or_default(),
+            env_overrides: env_overrides.unwrap_or_default(),
+            detached,
         }
     }
 
@@ -193,6 +225,14 @@ impl App {
         self.properties.remove(key)
     }
 
+    /// Add an evar layered on top of the app's env.
+    ///
+    /// Merged after the named env and before the final solve, so a `set`
+    /// shadows the env's value.
+    pub fn add_override(&mut self, evar: Evar) {
+        self.env_overrides.push(evar);
+    }
+
     /// Check if a property exists.
     pub fn has_prop(&self, key: &str) -> bool {
         self.properties.contains_key(key)
@@ -201,7 +241,8 @@ impl App {
     /// Get the effective working directory.
     ///
     /// Returns cwd if set, otherwise the parent directory of the executable.
-    /// Returns None if neither is available.
+    /// Returns None if neither is available (including bare names looked
+    /// up on PATH, like `"sh"`).
     pub fn effective_cwd(&self) -> Option<String> {
         if let Some(ref cwd) = self.cwd {
             return Some(cwd.clone());
@@ -211,6 +252,7 @@ impl App {
         self.path
             .as_ref()
             .and_then(|p| Path::new(p).parent())
+            .filter(|p| !p.as_os_str().is_empty())
             .map(|p| p.to_string_lossy().to_string())
     }
 
@@ -282,6 +324,13 @@ impl App {
         }
         dict.set_item("properties", props)?;
 
+        let overrides = PyList::empty(py);
+        for evar in &self.env_overrides {
+            overrides.append(evar.to_dict(py)?)?;
+        }
+        dict.set_item("env_overrides", overrides)?;
+        dict.set_item("detached", self.detached)?;
+
         Ok(dict.into())
     }
 
@@ -315,6 +364,19 @@ impl App {
             .map(|v| v.extract().unwrap_or_default())
             .unwrap_or_default();
 
+        let mut env_overrides = Vec::new();
+        if let Some(overrides_obj) = dict.get_item("env_overrides")? {
+            let evars_list: Vec<Bound<'_, PyDict>> = overrides_obj.extract()?;
+            for evar_dict in evars_list {
+                env_overrides.push(Evar::from_dict(&evar_dict)?);
+            }
+        }
+
+        let detached: bool = dict
+            .get_item("detached")?
+            .and_then(|v| v.extract().ok())
+            .unwrap_or(false);
+
         Ok(Self {
             name,
             path,
@@ -322,6 +384,8 @@ impl App {
             args,
             cwd,
             properties,
+            env_overrides,
+            detached,
         })
     }
 
@@ -388,6 +452,14 @@ impl App {
         slf
     }
 
+    /// Builder: add env override.
+    /// Returns self for method chaining.
+    #[pyo3(name = "with_override")]
+    fn py_with_override(mut slf: PyRefMut<'_, Self>, evar: Evar) -> PyRefMut<'_, Self> {
+        slf.env_overrides.push(evar);
+        slf
+    }
+
     /// Hash based on name (apps in a package should have unique names)
     fn __hash__(&self) -> u64 {
         use std::collections::hash_map::DefaultHasher;
@@ -404,19 +476,22 @@ impl App {
 
     /// Launch the application with the given environment.
     ///
+    /// `{TOKEN}` references in arguments are expanded against the launch
+    /// env; an unresolved token raises `ValueError` instead of launching.
+    ///
     /// # Arguments
     /// * `env` - Solved environment to use (optional, uses empty env if None)
-    /// * `extra_args` - Additional arguments to pass
+    /// * `args` - Additional arguments, after the app's default args
     /// * `wait` - Wait for process to complete (default: false)
     ///
     /// # Returns
-    /// Process exit code if wait=true, else 0.
+    /// Process exit code if wait=true, else the child's PID.
     ///
     /// # Examples
     /// ```python
     /// # With Env object
     /// env = pkg.effective_env("maya")
-    /// app.launch(env, extra_args=["--batch"])
+    /// pid = app.launch(env, args=["--batch"])
     ///
     /// # With dict (like subprocess.Popen)
     /// app.launch({"PATH": "/usr/bin", "HOME": "/home/user"})
@@ -424,69 +499,38 @@ impl App {
     /// # No environment
     /// app.launch()
     /// ```
-    #[pyo3(signature = (env = None, extra_args = None, wait = false))]
-    pub fn launch(
+    #[pyo3(name = "launch", signature = (env = None, args = None, wait = false))]
+    fn py_launch(
         &self,
-        _py: Python<'_>,
         env: Option<Bound<'_, PyAny>>,
-        extra_args: Option<Vec<String>>,
+        args: Option<Vec<String>>,
         wait: bool,
-    ) -> PyResult<i32> {
-        use std::process::Command;
-
-        let Some(exe_path) = &self.path else {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                format!("No executable path defined for app: {}", self.name)
-            ));
-        };
-
-        // Build command
-        let mut cmd = Command::new(exe_path);
-        
-        // Add arguments
-        let args = self.build_args(extra_args);
-        cmd.args(&args);
-
-        // Set working directory
-        if let Some(cwd) = self.effective_cwd() {
-            cmd.current_dir(cwd);
-        }
-
-        // Apply environment if provided (Env object or dict)
-        if let Some(env_obj) = env {
-            if let Ok(env) = env_obj.extract::<crate::env::Env>() {
-                // It's an Env object
-                for evar in &env.evars {
-                    cmd.env(&evar.name, &evar.value);
+    ) -> PyResult<i64> {
+        // Resolve launch environment (Env object or dict)
+        let env = match env {
+            Some(env_obj) => {
+                if let Ok(env) = env_obj.extract::<Env>() {
+                    env
+                } else if let Ok(dict) = env_obj.extract::<HashMap<String, String>>() {
+                    Env::from_evars("default", dict.into_iter().map(|(k, v)| Evar::set(k, v)))
+                } else {
+                    return Err(pyo3::exceptions::PyTypeError::new_err(
+                        "env must be Env object or dict[str, str]"
+                    ));
                 }
-            } else if let Ok(dict) = env_obj.extract::<HashMap<String, String>>() {
-                // It's a dict
-                for (key, value) in dict {
-                    cmd.env(&key, &value);
-                }
-            } else {
-                return Err(pyo3::exceptions::PyTypeError::new_err(
-                    "env must be Env object or dict[str, str]"
-                ));
             }
-        }
+            None => Env::new("default".to_string()),
+        };
 
-        // Launch
-        if wait {
-            match cmd.status() {
-                Ok(status) => Ok(status.code().unwrap_or(-1)),
-                Err(e) => Err(pyo3::exceptions::PyOSError::new_err(
-                    format!("Failed to launch {}: {}", self.name, e)
-                )),
-            }
-        } else {
-            match cmd.spawn() {
-                Ok(_) => Ok(0),
-                Err(e) => Err(pyo3::exceptions::PyOSError::new_err(
-                    format!("Failed to spawn {}: {}", self.name, e)
-                )),
-            }
+        let mut child = self.launch(&env, &args.unwrap_or_default())?;
+        if !wait {
+            return Ok(i64::from(child.id()));
         }
+        let status = child.wait().map_err(|e| AppError::LaunchFailed {
+            app: self.name.clone(),
+            reason: e.to_string(),
+        })?;
+        Ok(i64::from(status.code().unwrap_or(-1)))
     }
 }
 
@@ -501,6 +545,8 @@ impl App {
             args: Vec::new(),
             cwd: None,
             properties: HashMap::new(),
+            env_overrides: Vec::new(),
+            detached: false,
         }
     }
 
@@ -546,6 +592,105 @@ impl App {
         self
     }
 
+    /// Builder: add env override.
+    pub fn with_override(mut self, evar: Evar) -> Self {
+        self.env_overrides.push(evar);
+        self
+    }
+
+    /// Layer this app's env overrides on top of `env`.
+    ///
+    /// Overrides are merged last and compressed, so a `set` override
+    /// replaces the package value. `env` is taken as already solved: only
+    /// the override values are expanded, against the layered variables, so
+    /// they can reference package variables and each other.
+    ///
+    /// # Errors
+    /// Returns [`EnvError`] if expanding an override fails.
+    pub fn layer_env(&self, env: Env) -> Result<Env, EnvError> {
+        if self.env_overrides.is_empty() {
+            return Ok(env);
+        }
+        let layered = Env::from_evars(env.name.clone(), self.env_overrides.iter().cloned());
+        let lookup: HashMap<String, String> = env
+            .merge(&layered)
+            .compress()
+            .evars
+            .into_iter()
+            .map(|e| (e.name.to_lowercase(), e.value))
+            .collect();
+
+        let mut expander = token::Expander::new(&lookup, crate::env::DEFAULT_MAX_DEPTH).with_os_fallback(true);
+        let mut overrides = Vec::with_capacity(self.env_overrides.len());
+        for evar in &self.env_overrides {
+            let mut evar = evar.clone();
+            evar.value = expander.expand(&evar.value)?;
+            overrides.push(evar.resolve_file()?);
+        }
+        Ok(env.merge(&Env::from_evars(env.name.clone(), overrides)).compress())
+    }
+
+    /// Builder: detach the launched process.
+    pub fn with_detached(mut self, detached: bool) -> Self {
+        self.detached = detached;
+        self
+    }
+
+    /// Spawn the executable with `env` applied.
+    ///
+    /// The env is layered with [`env_overrides`](Self::env_overrides), cleared
+    /// of [`Env::default_blacklist`] names (overrides can't inject
+    /// `LD_PRELOAD` either) and committed into the child's environment on
+    /// top of the inherited one; unset evars are removed. `args` follow the app's default args and
+    /// `{TOKEN}`s in both are expanded against that launch env. The child
+    /// runs in [`effective_cwd`](Self::effective_cwd) and is fully detached
+    /// when [`detached`](Self::detached) is set.
+    ///
+    /// # Returns
+    /// Handle of the running child; the caller decides whether to wait.
+    ///
+    /// # Errors
+    /// - [`AppError::NoPath`] if no executable path is set
+    /// - [`AppError::UnresolvedToken`] if an argument can't be expanded
+    /// - [`AppError::LaunchFailed`] if layering the env or spawning fails
+    pub fn launch(&self, env: &Env, args: &[String]) -> Result<Child, AppError> {
+        let exe_path = self.path.as_ref().ok_or_else(|| AppError::NoPath {
+            app: self.name.clone(),
+        })?;
+        let failed = |reason: String| AppError::LaunchFailed {
+            app: self.name.clone(),
+            reason,
+        };
+        let env = self.layer_env(env.clone()).map_err(|e| failed(e.to_string()))?;
+        let (env, skipped) = env.without_names(&Env::default_blacklist());
+        for name in &skipped {
+            warn!("App {}: not passing blacklisted variable {}", self.name, name);
+        }
+
+        // Expand args against the inherited process env overlaid with the launch env
+        let mut launch_vars: HashMap<String, String> = std::env::vars().collect();
+        launch_vars.extend(env.to_map());
+        let args = self.expand_args(&self.build_args(Some(args.to_vec())), &launch_vars)?;
+
+        let mut cmd = Command::new(exe_path);
+        cmd.args(&args);
+        if let Some(cwd) = self.effective_cwd() {
+            cmd.current_dir(cwd);
+        }
+        for evar in &env.evars {
+            if evar.is_unset() {
+                cmd.env_remove(&evar.name);
+            } else {
+                cmd.env(&evar.name, &evar.value);
+            }
+        }
+        if self.detached {
+            detach(&mut cmd);
+        }
+
+        cmd.spawn().map_err(|e| failed(format!("{}: {}", exe_path, e)))
+    }
+
     /// Get path as PathBuf if set.
     pub fn path_buf(&self) -> Option<PathBuf> {
         self.path.as_ref().map(PathBuf::from)
@@ -555,6 +700,88 @@ impl App {
     pub fn cwd_path(&self) -> Option<PathBuf> {
         self.cwd.as_ref().map(PathBuf::from)
     }
+
+    /// Expand `{TOKEN}` references in arguments against the launch env.
+    ///
+    /// Lookup is case-insensitive, like env solving.
+    ///
+    /// # Errors
+    /// Returns [`AppError::UnresolvedToken`] if an argument references a
+    /// variable missing from `vars` - launching with a literal `{TOKEN}`
+    /// is never what the author meant.
+    pub fn expand_args(
+        &self,
+        args: &[String],
+        vars: &HashMap<String, String>,
+    ) -> Result<Vec<String>, AppError> {
+        let lookup = lowercase_keys(vars);
+
+        args.iter()
+            .map(|arg| {
+                let missing = token::extract(arg)
+                    .into_iter()
+                    .filter(|t| !lookup.contains_key(&t.to_lowercase()))
+                    .min();
+                if let Some(token) = missing {
+                    return Err(AppError::UnresolvedToken {
+                        app: self.name.clone(),
+                        arg: arg.clone(),
+                        token,
+                    });
+                }
+                Ok(token::expand_tokens(arg, |name| {
+                    lookup.get(&name.to_lowercase()).cloned()
+                }))
+            })
+            .collect()
+    }
+
+    /// Expand `{TOKEN}` references found in `vars`, leaving any other
+    /// `{word}` as typed.
+    ///
+    /// For ad-hoc commands (`pkg env maya -- cmd ...`), whose arguments
+    /// may use braces for their own syntax.
+    pub fn expand_known_args(args: &[String], vars: &HashMap<String, String>) -> Vec<String> {
+        let lookup = lowercase_keys(vars);
+        args.iter()
+            .map(|arg| token::expand_tokens(arg, |name| lookup.get(&name.to_lowercase()).cloned()))
+            .collect()
+    }
+}
+
+/// Copy of `vars` keyed by lowercase name, for case-insensitive lookup.
+fn lowercase_keys(vars: &HashMap<String, String>) -> HashMap<String, String> {
+    vars.iter().map(|(k, v)| (k.to_lowercase(), v.clone())).collect()
+}
+
+/// Start `cmd` in its own session with no stdio tied to the launcher.
+fn detach(cmd: &mut Command) {
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        extern "C" {
+            fn setsid() -> i32;
+        }
+        // SAFETY: setsid is async-signal-safe and touches no parent state
+        unsafe {
+            cmd.pre_exec(|| {
+                if setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
 }
 
 impl Default for App {
@@ -566,6 +793,8 @@ impl Default for App {
             args: Vec::new(),
             cwd: None,
             properties: HashMap::new(),
+            env_overrides: Vec::new(),
+            detached: false,
         }
     }
 }
@@ -611,6 +840,10 @@ mod tests {
         // Without either
         let app3 = App::named("test");
         assert!(app3.effective_cwd().is_none());
+
+        // Bare name resolved via PATH has no parent dir
+        let app4 = App::named("test").with_path("sh");
+        assert!(app4.effective_cwd().is_none());
     }
 
     #[test]
@@ -654,6 +887,25 @@ mod tests {
         assert_eq!(app, app2);
     }
 
+    #[test]
+    fn app_args_cwd_roundtrip() {
+        let app = App::named("maya")
+            .with_path("/opt/maya/bin/maya")
+            .with_args(vec!["-batch".to_string(), "-script".to_string(), "/pipe/setup.mel".to_string()])
+            .with_cwd("/shows/proj");
+
+        let json: App = serde_json::from_str(&serde_json::to_string(&app).unwrap()).unwrap();
+        assert_eq!(json, app);
+
+        Python::attach(|py| {
+            let dict = app.to_dict(py).unwrap();
+            let back = App::from_dict(dict.bind(py).cast::<PyDict>().unwrap()).unwrap();
+            assert_eq!(back.args, ["-batch", "-script", "/pipe/setup.mel"]);
+            assert_eq!(back.cwd.as_deref(), Some("/shows/proj"));
+            assert_eq!(back, app);
+        });
+    }
+
     #[test]
     fn app_equality() {
         let app1 = App::named("maya").with_path("/path");
@@ -668,4 +920,79 @@ mod tests {
         let app4 = App::named("maya").with_path("/other");
         assert_ne!(app1, app4);
     }
+
+    #[test]
+    fn app_expand_args() {
+        use crate::env::Env;
+        use crate::evar::Evar;
+
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("PKG_MAYA_ROOT", "/opt/maya"));
+        env.add(Evar::set("PROJECT", "{PKG_MAYA_ROOT}/projects"));
+        let solved = env.solve_impl(10, false).unwrap();
+
+        let app = App::named("maya").with_args(vec![
+            "--project".to_string(),
+            "{PROJECT}/default".to_string(),
+            "{pkg_maya_root}".to_string(),
+        ]);
+        let args = app.expand_args(&app.build_args(None), &solved.to_map()).unwrap();
+        assert_eq!(args, vec!["--project", "/opt/maya/projects/default", "/opt/maya"]);
+    }
+
+    #[test]
+    fn app_expand_args_unresolved() {
+        let app = App::named("maya").with_arg("--scene={SHOT_SCENE}");
+        let err = app.expand_args(&app.args, &HashMap::new()).unwrap_err();
+        assert_eq!(
+            err,
+            AppError::UnresolvedToken {
+                app: "maya".to_string(),
+                arg: "--scene={SHOT_SCENE}".to_string(),
+                token: "SHOT_SCENE".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn app_expand_known_args() {
+        let vars = HashMap::from([("SHOT".to_string(), "sh010".to_string())]);
+        let args = vec!["{shot}.ma".to_string(), "{name}".to_string(), "{}".to_string()];
+        assert_eq!(App::expand_known_args(&args, &vars), vec!["sh010.ma", "{name}", "{}"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn app_launch() {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("GREETING", "hello"));
+
+        let app = App::named("echo").with_path("sh").with_args(vec!["-c".to_string()]);
+        // $0 of the script is the expanded {GREETING} argument
+        let args = vec![
+            "test \"$GREETING\" = hello && test \"$0\" = hello".to_string(),
+            "{GREETING}".to_string(),
+        ];
+        let status = app.launch(&env, &args).unwrap().wait().unwrap();
+        assert!(status.success());
+
+        let status = app.launch(&env, &["exit 3".to_string()]).unwrap().wait().unwrap();
+        assert_eq!(status.code(), Some(3));
+
+        let err = App::named("none").launch(&env, &[]).unwrap_err();
+        assert!(matches!(err, AppError::NoPath { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn app_launch_blacklist() {
+        let env = Env::new("default".to_string());
+        let mut app = App::named("sh").with_path("sh").with_args(vec!["-c".to_string()]);
+        app.add_override(Evar::set("LD_AUDIT", "/nonexistent/pkg-audit.so"));
+        app.add_override(Evar::set("GREETING", "hello"));
+
+        let script = "test \"$GREETING\" = hello && test \"$LD_AUDIT\" != /nonexistent/pkg-audit.so";
+        let status = app.launch(&env, &[script.to_string()]).unwrap().wait().unwrap();
+        assert!(status.success());
+    }
 }
@@ -0,0 +1,108 @@
+//! Build planning for hybrid source/prebuilt setups.
+//!
+//! Packages marked [`source_only`](crate::Package::source_only) have no
+//! prebuilt payload. A resolved source-only package counts as installed
+//! once its [`install_path`](crate::Package::install_path) exists; until
+//! then it needs a build. Everything else is prebuilt.
+//!
+//! [`BuildPlan`] partitions a resolution into installed packages and
+//! packages to build, both in dependency order (dependencies first), so
+//! builds can run top to bottom.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! let plan = solver.build_partition_impl("maya-2026.1.0")?;
+//! for name in &plan.to_build {
+//!     println!("build {}", name);
+//! }
+//! ```
+
+use crate::package::Package;
+use crate::solver::PackageIndex;
+use semver::Version;
+use std::collections::{HashMap, HashSet};
+
+/// Resolution split into installed packages and packages to build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildPlan {
+    /// Prebuilt packages and source-only packages already installed.
+    pub installed: Vec<String>,
+    /// Source-only packages without an existing install.
+    pub to_build: Vec<String>,
+}
+
+impl BuildPlan {
+    /// Partition `resolved` package names using build metadata from `index`.
+    pub fn new(index: &PackageIndex, resolved: &[String]) -> Self {
+        let mut plan = Self::default();
+        for name in dependency_order(index, resolved) {
+            let needs = parse(&name).is_some_and(|(base, ver)| needs_build(index, &base, &ver));
+            if needs {
+                plan.to_build.push(name);
+            } else {
+                plan.installed.push(name);
+            }
+        }
+        plan
+    }
+}
+
+/// Source-only version whose install path is missing (or undeclared).
+pub fn needs_build(index: &PackageIndex, base: &str, version: &Version) -> bool {
+    index.is_source_only(base, version)
+        && !index.install_path(base, version).is_some_and(|p| p.exists())
+}
+
+/// Order resolved packages so dependencies come before dependents.
+///
+/// Roots are visited in name order, dependencies in declaration order,
+/// so the result is deterministic. Names that don't parse are appended
+/// as-is; cycles are broken at the first revisit.
+pub fn dependency_order(index: &PackageIndex, resolved: &[String]) -> Vec<String> {
+    let by_base: HashMap<String, (&String, Version)> = resolved
+        .iter()
+        .filter_map(|name| parse(name).map(|(base, ver)| (base, (name, ver))))
+        .collect();
+
+    let mut roots: Vec<&String> = resolved.iter().collect();
+    roots.sort();
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    for name in roots {
+        match parse(name) {
+            Some((base, _)) => visit(index, &by_base, &base, &mut visited, &mut order),
+            None => order.push(name.clone()),
+        }
+    }
+    order
+}
+
+/// Post-order DFS over resolved dependencies.
+fn visit(
+    index: &PackageIndex,
+    by_base: &HashMap<String, (&String, Version)>,
+    base: &str,
+    visited: &mut HashSet<String>,
+    order: &mut Vec<String>,
+) {
+    if !visited.insert(base.to_string()) {
+        return;
+    }
+    let Some((name, version)) = by_base.get(base) else {
+        return;
+    };
+    for spec in index.deps(base, version).into_iter().flatten() {
+        if by_base.contains_key(&spec.base) {
+            visit(index, by_base, &spec.base, visited, order);
+        }
+    }
+    order.push((*name).clone());
+}
+
+/// Split `base-version` into base and semver.
+fn parse(name: &str) -> Option<(String, Version)> {
+    let (base, version) = Package::parse_name(name).ok()?;
+    Some((base, Version::parse(&version).ok()?))
+}
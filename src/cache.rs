@@ -1,22 +1,82 @@
 //! Package cache for faster rescanning.
 //!
-//! Stores parsed packages with mtime for invalidation.
+//! Stores parsed packages with mtime and content hash for invalidation.
 //! Cache file is located next to the binary (pkg.cache).
+//!
+//! `$PKG_CACHE_MODE` selects what keeps an entry valid: `mtime`, `hash`
+//! (SHA-1 of the file, for mounts with unstable mtimes like NFS) or
+//! `both` (default) - either one matching is enough.
 
 use crate::package::Package;
 use log::{debug, info, trace, warn};
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// Environment variable selecting the [`CacheMode`].
+pub const CACHE_MODE_VAR: &str = "PKG_CACHE_MODE";
+
 /// Cache entry for a single package.py file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
     /// Modification time (seconds since UNIX epoch).
     pub mtime: u64,
-    /// Parsed package data.
-    pub package: Package,
+    /// SHA-1 of the file contents (hex). None for entries from older caches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Parsed packages (several if the file defines `get_packages()`).
+    pub packages: Vec<Package>,
+}
+
+/// What [`Cache::get`] compares to decide an entry is still valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// File mtime only.
+    Mtime,
+    /// Content hash only.
+    Hash,
+    /// Valid if either mtime or hash matches.
+    #[default]
+    Both,
+}
+
+impl CacheMode {
+    /// Mode from `$PKG_CACHE_MODE`; unset or unknown values give [`Both`](Self::Both).
+    pub fn from_env() -> Self {
+        match std::env::var(CACHE_MODE_VAR) {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                warn!("Cache: {}, using 'both'", e);
+                Self::Both
+            }),
+            Err(_) => Self::Both,
+        }
+    }
+}
+
+impl std::str::FromStr for CacheMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mtime" => Ok(Self::Mtime),
+            "hash" => Ok(Self::Hash),
+            "both" => Ok(Self::Both),
+            other => Err(format!("invalid {} '{}' (expected hash, mtime or both)", CACHE_MODE_VAR, other)),
+        }
+    }
+}
+
+/// Cache summary for `pkg cache stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cached package files.
+    pub entries: usize,
+    /// Size of the cache file on disk (0 if not saved yet).
+    pub bytes: u64,
+    /// Cache file location, if one can be determined.
+    pub path: Option<PathBuf>,
 }
 
 /// Package cache.
@@ -24,6 +84,9 @@ pub struct CacheEntry {
 pub struct Cache {
     /// Entries indexed by package.py path.
     pub entries: HashMap<PathBuf, CacheEntry>,
+    /// Validation mode (not persisted; from `$PKG_CACHE_MODE`).
+    #[serde(skip)]
+    pub mode: CacheMode,
 }
 
 impl Cache {
@@ -31,6 +94,7 @@ impl Cache {
     pub fn new() -> Self {
         Self {
             entries: HashMap::new(),
+            mode: CacheMode::from_env(),
         }
     }
 
@@ -47,16 +111,21 @@ impl Cache {
             debug!("Cache: no cache path available");
             return Self::new();
         };
+        Self::load_from(&path)
+    }
 
+    /// Load cache from `path`; missing or unreadable files give an empty cache.
+    pub fn load_from(path: &Path) -> Self {
         if !path.exists() {
             debug!("Cache: no cache file at {}", path.display());
             return Self::new();
         }
 
-        match std::fs::read_to_string(&path) {
-            Ok(content) => match serde_json::from_str(&content) {
-                Ok(cache) => {
+        match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<Self>(&content) {
+                Ok(mut cache) => {
                     info!("Cache: loaded from {}", path.display());
+                    cache.mode = CacheMode::from_env();
                     cache
                 }
                 Err(e) => {
@@ -77,10 +146,14 @@ impl Cache {
             debug!("Cache: no cache path available");
             return;
         };
+        self.save_to(&path);
+    }
 
+    /// Save cache to `path`; errors are logged, not returned.
+    pub fn save_to(&self, path: &Path) {
         match serde_json::to_string_pretty(self) {
             Ok(content) => {
-                if let Err(e) = std::fs::write(&path, content) {
+                if let Err(e) = std::fs::write(path, content) {
                     warn!("Cache: write error: {}", e);
                 } else {
                     info!("Cache: saved {} entries to {}", self.entries.len(), path.display());
@@ -92,14 +165,23 @@ impl Cache {
         }
     }
 
-    /// Get cached package if still valid (mtime matches).
-    pub fn get(&self, path: &Path) -> Option<&Package> {
+    /// Get cached packages if still valid under [`mode`](Self::mode).
+    ///
+    /// The file is only read for hashing when the mtime check alone
+    /// doesn't decide.
+    pub fn get(&self, path: &Path) -> Option<&[Package]> {
         let entry = self.entries.get(path)?;
         let current_mtime = get_mtime(path)?;
 
-        if entry.mtime == current_mtime {
+        let mtime_ok = self.mode != CacheMode::Hash && entry.mtime == current_mtime;
+        let valid = mtime_ok
+            || (self.mode != CacheMode::Mtime
+                && entry.hash.is_some()
+                && entry.hash == hash_file(path));
+
+        if valid {
             trace!("Cache: hit for {}", path.display());
-            Some(&entry.package)
+            Some(&entry.packages)
         } else {
             trace!("Cache: stale for {} (cached={}, current={})", 
                    path.display(), entry.mtime, current_mtime);
@@ -108,10 +190,66 @@ impl Cache {
     }
 
     /// Insert or update cache entry.
-    pub fn insert(&mut self, path: PathBuf, package: Package) {
+    pub fn insert(&mut self, path: PathBuf, packages: Vec<Package>) {
         if let Some(mtime) = get_mtime(&path) {
             trace!("Cache: storing {} (mtime={})", path.display(), mtime);
-            self.entries.insert(path, CacheEntry { mtime, package });
+            let hash = hash_file(&path);
+            self.entries.insert(path, CacheEntry { mtime, hash, packages });
+        }
+    }
+
+    /// Entry count plus the cache file's size and location.
+    pub fn stats(&self) -> CacheStats {
+        match Self::cache_path() {
+            Some(path) => self.stats_at(&path),
+            None => CacheStats {
+                entries: self.entries.len(),
+                bytes: 0,
+                path: None,
+            },
+        }
+    }
+
+    /// Like [`stats`](Self::stats) for a cache file at `path`.
+    pub fn stats_at(&self, path: &Path) -> CacheStats {
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+        CacheStats {
+            entries: self.entries.len(),
+            bytes,
+            path: Some(path.to_path_buf()),
+        }
+    }
+
+    /// Drop all entries and delete the cache file.
+    ///
+    /// # Returns
+    /// Whether a cache file existed and was removed.
+    ///
+    /// # Errors
+    /// IO error if the file exists but can't be deleted.
+    pub fn clear(&mut self) -> std::io::Result<bool> {
+        match Self::cache_path() {
+            Some(path) => self.clear_at(&path),
+            None => {
+                self.entries.clear();
+                Ok(false)
+            }
+        }
+    }
+
+    /// Like [`clear`](Self::clear) for a cache file at `path`.
+    ///
+    /// # Errors
+    /// IO error if the file exists but can't be deleted.
+    pub fn clear_at(&mut self, path: &Path) -> std::io::Result<bool> {
+        self.entries.clear();
+        match std::fs::remove_file(path) {
+            Ok(()) => {
+                info!("Cache: removed {}", path.display());
+                Ok(true)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
         }
     }
 
@@ -145,6 +283,12 @@ fn get_mtime(path: &Path) -> Option<u64> {
         .map(|d| d.as_secs())
 }
 
+/// SHA-1 of the file contents as lowercase hex.
+fn hash_file(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha1::digest(&bytes)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,9 +301,68 @@ mod tests {
         let pkg = Package::new("test".to_string(), "1.0.0".to_string());
         cache.entries.insert(
             PathBuf::from("/fake/path"),
-            CacheEntry { mtime: 12345, package: pkg },
+            CacheEntry { mtime: 12345, hash: None, packages: vec![pkg] },
         );
 
         assert_eq!(cache.len(), 1);
     }
+
+    #[test]
+    fn cache_stats_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pkg.cache");
+
+        let mut cache = Cache::new();
+        for version in ["1.0.0", "2.0.0"] {
+            cache.entries.insert(
+                PathBuf::from(format!("/fake/test/{}/package.py", version)),
+                CacheEntry { mtime: 1, hash: None, packages: vec![Package::new("test".to_string(), version.to_string())] },
+            );
+        }
+        assert_eq!(cache.stats_at(&path).bytes, 0);
+        cache.save_to(&path);
+
+        let mut saved = Cache::load_from(&path);
+        let stats = saved.stats_at(&path);
+        assert_eq!(stats.entries, 2);
+        assert!(stats.bytes > 0);
+        assert_eq!(stats.path.as_deref(), Some(path.as_path()));
+
+        assert!(saved.clear_at(&path).unwrap());
+        assert!(saved.is_empty());
+        assert!(!path.exists());
+        assert_eq!(saved.stats_at(&path), CacheStats { entries: 0, bytes: 0, path: Some(path.clone()) });
+        // Nothing left to remove
+        assert!(!saved.clear_at(&path).unwrap());
+    }
+
+    #[test]
+    fn cache_mode_touch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.py");
+        std::fs::write(&path, "def get_package():\n    return pkg.Package(\"ocio\", \"2.1.0\")\n").unwrap();
+
+        let mut cache = Cache::new();
+        cache.insert(path.clone(), vec![Package::new("ocio".to_string(), "2.1.0".to_string())]);
+
+        // Touch: new mtime, same content
+        let later = SystemTime::now() + std::time::Duration::from_secs(60);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+
+        cache.mode = CacheMode::Hash;
+        assert!(cache.get(&path).is_some());
+        cache.mode = CacheMode::Both;
+        assert!(cache.get(&path).is_some());
+        cache.mode = CacheMode::Mtime;
+        assert!(cache.get(&path).is_none());
+
+        // Changed content is a miss in hash mode
+        std::fs::write(&path, "def get_package():\n    return pkg.Package(\"ocio\", \"2.2.0\")\n").unwrap();
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        cache.mode = CacheMode::Hash;
+        assert!(cache.get(&path).is_none());
+
+        assert_eq!("HASH".parse::<CacheMode>(), Ok(CacheMode::Hash));
+        assert!("size".parse::<CacheMode>().is_err());
+    }
 }
@@ -0,0 +1,69 @@
+//! Cooperative cancellation for long-running operations.
+//!
+//! A [`CancelToken`] is a cheap, cloneable flag checked periodically by
+//! [`Storage`](crate::Storage) scans and [`Solver`](crate::Solver) runs.
+//! When set, those operations stop early with a `Cancelled` error instead
+//! of returning partial results.
+//!
+//! The CLI sets the [`global`] token from its Ctrl-C handler; library code
+//! that does not take an explicit token checks the global one.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use pkg_lib::cancel::CancelToken;
+//!
+//! let token = CancelToken::new();
+//! let worker = token.clone();
+//! // ... on another thread:
+//! token.cancel();
+//! assert!(worker.is_cancelled());
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Shared cancellation flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Create a new, unset token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Visible to all clones.
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+/// Process-wide token (set by the CLI's Ctrl-C handler).
+pub fn global() -> &'static CancelToken {
+    static GLOBAL: OnceLock<CancelToken> = OnceLock::new();
+    GLOBAL.get_or_init(CancelToken::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_shared_between_clones() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(!CancelToken::new().is_cancelled());
+    }
+}
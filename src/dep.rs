@@ -12,13 +12,17 @@
 //! - `name` - Any version (e.g., `redshift`)
 //! - `name@constraint` - Version constraint (e.g., `redshift@>=3.5,<4.0`)
 //! - `name@version` - Exact version (e.g., `redshift@3.5.0`)
+//! - `name[extra,...]` - Also pull the package's extra requirement groups
+//!   (e.g., `maya[gpu]@>=2026`), see `Package.extras`
 //!
 //! Constraint syntax follows SemVer (VersionReq):
 //! - `>=1.0.0` - Greater than or equal
 //! - `<2.0.0` - Less than
-//! - `^1.2.3` - Compatible (same major)
-//! - `~1.2.3` - Compatible (same major.minor)
+//! - `^1.2.3` - Compatible (same major), desugared to `>=1.2.3,<2.0.0`
+//! - `~1.2.3` - Compatible (same major.minor), desugared to `>=1.2.3,<1.3.0`
 //! - `>=1.0,<2.0` - Multiple constraints (comma-separated)
+//! - `>=2,!=2.1.3` - Exclude an exact version (combine with other terms)
+//! - `>=3.0,<4.0|>=5.0,<6.0` - Any of several ranges (`|`-separated)
 //!
 //! ## Resolved Dependencies
 //!
@@ -52,10 +56,23 @@
 
 use crate::error::PackageError;
 use pyo3::prelude::*;
-use semver::{Version, VersionReq};
+use semver::{Comparator, Op, Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Separator between alternative constraint ranges (`>=3,<4|>=5`).
+pub const OR_SEP: char = '|';
+
+/// Prefix marking a weak requirement (`~redshift@>=3.5`).
+pub const WEAK_PREFIX: char = '~';
+
+/// Constraint term opting into prereleases (`maya@>=2026,pre`).
+pub const PRERELEASE_FLAG: &str = "pre";
+
+/// Leading tags of prerelease identifiers (`rc.1`, `beta2`, `dev`).
+const PRERELEASE_TAGS: &[&str] =
+    &["alpha", "a", "beta", "b", "rc", "c", "pre", "preview", "dev", "snapshot", "nightly"];
+
 /// Dependency specification.
 ///
 /// Represents either:
@@ -67,6 +84,19 @@ use std::fmt;
 /// 1. If contains `@`: Split on `@` → (base, constraint)
 /// 2. If contains `-` followed by digit: Split → (base, exact version)
 /// 3. Otherwise: base only, any version
+///
+/// A leading `~` makes the requirement weak: it bounds the version if
+/// something else pulls the base in, but never pulls it in by itself.
+///
+/// # Prereleases
+///
+/// Prerelease versions (`2026.1.0-rc.1`, see [`is_prerelease`]) only match
+/// when the spec opts in: with a `pre` term (`maya@>=2026,pre`) or when the
+/// constraint itself names a prerelease (`maya@>=2026.1.0-0`).
+///
+/// Variants (`2026.0.0-win64`) aren't prereleases. They match by their
+/// release, so `maya@>=2026` accepts `maya-2026.0.0-win64`, unless the
+/// constraint names a tagged version itself (`maya@2026.0.0-win64`).
 #[pyclass]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DepSpec {
@@ -82,6 +112,21 @@ pub struct DepSpec {
     /// Original input string for reference.
     #[pyo3(get)]
     pub original: String,
+
+    /// Requested extra groups (`maya[gpu]` → `["gpu"]`).
+    #[pyo3(get)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extras: Vec<String>,
+
+    /// Weak requirement (`~name`): constrains, never selects.
+    #[pyo3(get)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub weak: bool,
+
+    /// Prereleases opted in with a `pre` term.
+    #[pyo3(get)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_prerelease: bool,
 }
 
 #[pymethods]
@@ -105,6 +150,9 @@ impl DepSpec {
             base,
             constraint,
             original,
+            extras: Vec::new(),
+            weak: false,
+            include_prerelease: false,
         }
     }
 
@@ -114,6 +162,7 @@ impl DepSpec {
     /// - `name` → any version
     /// - `name@constraint` → version constraint
     /// - `name-version` → exact version (resolved dependency)
+    /// - `~name@constraint` → weak requirement
     ///
     /// # Arguments
     /// * `spec` - Specification string
@@ -160,12 +209,50 @@ impl DepSpec {
         self.constraint == "*"
     }
 
-    /// Convert to requirement format (`name@constraint`).
+    /// Whether prerelease versions can match: opted in with `pre`, or a
+    /// prerelease is named in the constraint (exclusions don't count).
+    pub fn allows_prerelease(&self) -> bool {
+        self.include_prerelease || self.constraint_versions().any(|v| is_prerelease(&v))
+    }
+
+    /// Convert to requirement format (`name@constraint`, `~` kept for weak).
     pub fn to_req_str(&self) -> String {
-        if self.is_any() {
-            self.base.clone()
+        let base = self.weak_base();
+        match (self.is_any(), self.include_prerelease) {
+            (true, false) => base,
+            (true, true) => format!("{}@{}", base, PRERELEASE_FLAG),
+            (false, false) => format!("{}@{}", base, self.constraint),
+            (false, true) => format!("{}@{},{}", base, self.constraint, PRERELEASE_FLAG),
+        }
+    }
+
+    /// Convert to canonical requirement format.
+    ///
+    /// Equivalent specs produce the same string: shorthand is expanded
+    /// (`ocio@2` → `ocio@>=2.0.0,<3.0.0`), spaces are dropped and bounds are
+    /// ordered lower then upper. Exact versions become `name@x.y.z`, any
+    /// version becomes `name`. Unparseable constraints are kept as written.
+    /// Extras are kept, sorted (`maya[gpu,debug]` → `maya[debug,gpu]`), and
+    /// so are the weak marker and the `pre` opt-in (last).
+    pub fn canonical(&self) -> String {
+        let base = if self.extras.is_empty() {
+            self.weak_base()
         } else {
-            format!("{}@{}", self.base, self.constraint)
+            let mut extras = self.extras.clone();
+            extras.sort();
+            format!("{}[{}]", self.weak_base(), extras.join(","))
+        };
+        let constraint = match self.canonical_constraint() {
+            Some(c) if c == "*" => None,
+            Some(c) => Some(c),
+            None if self.is_any() => None,
+            None => Some(self.constraint.clone()),
+        };
+        match (constraint, self.include_prerelease) {
+            (None, false) => base,
+            (None, true) => format!("{}@{}", base, PRERELEASE_FLAG),
+            (Some(c), false) => format!("{}@{}", base, c),
+            (Some(c), true) => format!("{}@{},{}", base, c, PRERELEASE_FLAG),
         }
     }
 
@@ -192,6 +279,9 @@ impl DepSpec {
             original: format!("{}-{}", base, version),
             base,
             constraint: version,
+            extras: Vec::new(),
+            weak: false,
+            include_prerelease: false,
         })
     }
 
@@ -230,10 +320,33 @@ impl DepSpec {
             });
         }
 
+        // Weak: ~name@constraint → parse the rest, then mark
+        if let Some(rest) = spec.strip_prefix(WEAK_PREFIX) {
+            let mut dep = Self::parse_impl(rest)?;
+            if dep.weak {
+                return Err(PackageError::InvalidName {
+                    name: spec.to_string(),
+                    reason: "Duplicate weak marker".to_string(),
+                });
+            }
+            dep.weak = true;
+            dep.original = spec.to_string();
+            return Ok(dep);
+        }
+
+        // Extras: name[gpu]@constraint → parse without the group, then attach
+        if let Some((plain, extras)) = split_extras(spec)? {
+            let mut dep = Self::parse_impl(&plain)?;
+            dep.extras = extras;
+            dep.original = spec.to_string();
+            return Ok(dep);
+        }
+
         // Format 1: name@constraint (requirement)
         if let Some(at_pos) = spec.find('@') {
             let base = spec[..at_pos].to_string();
-            let constraint = spec[at_pos + 1..].to_string();
+            let (constraint, include_prerelease) = strip_prerelease_flag(&spec[at_pos + 1..]);
+            let constraint = desugar_shorthand(&constraint)?;
 
             if base.is_empty() {
                 return Err(PackageError::InvalidName {
@@ -249,6 +362,9 @@ impl DepSpec {
                 base,
                 constraint,
                 original: spec.to_string(),
+                extras: Vec::new(),
+                weak: false,
+                include_prerelease,
             });
         }
 
@@ -273,6 +389,9 @@ impl DepSpec {
                     base: pkg_id.name,
                     constraint,
                     original: spec.to_string(),
+                    extras: Vec::new(),
+                    weak: false,
+                    include_prerelease: false,
                 });
             }
         }
@@ -282,11 +401,53 @@ impl DepSpec {
             base: spec.to_string(),
             constraint: "*".to_string(),
             original: spec.to_string(),
+            extras: Vec::new(),
+            weak: false,
+            include_prerelease: false,
         })
     }
 
-    /// Validate a version constraint string.
+    /// Base with the `~` marker if weak.
+    fn weak_base(&self) -> String {
+        if self.weak {
+            format!("{}{}", WEAK_PREFIX, self.base)
+        } else {
+            self.base.clone()
+        }
+    }
+
+    /// OR branches of the constraint: `>=3,<4|>=5` → `[">=3,<4", ">=5"]`.
+    ///
+    /// A constraint without `|` is a single branch.
+    pub fn branches(&self) -> Vec<&str> {
+        self.constraint.split(OR_SEP).map(str::trim).collect()
+    }
+
+    /// Validate a version constraint string (every `|` branch).
     fn validate_constraint(constraint: &str) -> Result<(), PackageError> {
+        for branch in constraint.split(OR_SEP).map(str::trim) {
+            if branch.is_empty() {
+                return Err(PackageError::InvalidVersion {
+                    version: constraint.to_string(),
+                    reason: "Empty alternative in '|' group".to_string(),
+                });
+            }
+            Self::validate_branch(branch)?;
+        }
+        Ok(())
+    }
+
+    /// Validate a single constraint branch (no `|`).
+    fn validate_branch(constraint: &str) -> Result<(), PackageError> {
+        let (constraint, excluded) = split_exclusions(constraint);
+        for version in excluded {
+            Version::parse(version).map_err(|e| PackageError::InvalidVersion {
+                version: version.to_string(),
+                reason: format!("'!=' needs an exact version: {}", e),
+            })?;
+        }
+        let constraint = constraint.as_str();
+
         if constraint == "*" {
             return Ok(());
         }
@@ -305,32 +466,51 @@ impl DepSpec {
         Ok(())
     }
 
+    /// Whether the constraint names a tagged version (prerelease or variant).
+    ///
+    /// Such specs compare variants as-is; others compare them by release.
+    pub fn names_tagged(&self) -> bool {
+        self.constraint_versions().any(|v| !v.pre.is_empty())
+    }
+
+    /// Full versions named by the constraint terms, exclusions skipped.
+    fn constraint_versions(&self) -> impl Iterator<Item = Version> + '_ {
+        self.constraint
+            .split([OR_SEP, ','])
+            .map(str::trim)
+            .filter(|term| !term.starts_with("!="))
+            .map(|term| term.trim_start_matches(['>', '<', '=', '~', '^', ' ']))
+            .filter_map(|ver| Version::parse(ver).ok())
+    }
+
     /// Check if version matches (internal implementation).
+    ///
+    /// Prereleases only match if [`allows_prerelease`](Self::allows_prerelease);
+    /// variants match by their release unless [`names_tagged`](Self::names_tagged).
     pub fn matches_impl(&self, version: &str) -> Result<bool, PackageError> {
         let ver = Version::parse(version).map_err(|e| PackageError::InvalidVersion {
             version: version.to_string(),
             reason: e.to_string(),
         })?;
-
-        if self.constraint == "*" {
-            return Ok(true);
+        if is_prerelease(&ver) && !self.allows_prerelease() {
+            return Ok(false);
         }
+        let ver = match variant_release(&ver) {
+            Some(release) if !self.names_tagged() => release,
+            _ => ver,
+        };
 
-        // Try exact match first
-        if let Ok(exact) = Version::parse(&self.constraint) {
-            return Ok(ver == exact);
+        for branch in self.branches() {
+            if branch_matches(branch, &ver)? {
+                return Ok(true);
+            }
         }
-
-        // Try as version requirement
-        let req = VersionReq::parse(&self.constraint).map_err(|e| PackageError::InvalidVersion {
-            version: self.constraint.clone(),
-            reason: e.to_string(),
-        })?;
-
-        Ok(req.matches(&ver))
+        Ok(false)
     }
 
     /// Get parsed VersionReq for solver integration.
+    ///
+    /// Errors for `|` groups, which a single VersionReq can't express.
     pub fn version_req(&self) -> Result<VersionReq, PackageError> {
         if self.constraint == "*" {
             return VersionReq::parse("*").map_err(|e| PackageError::InvalidVersion {
@@ -355,6 +535,18 @@ impl DepSpec {
         })
     }
 
+    /// Normalize constraint to `>=lo,<hi` form (see [`canonical`](Self::canonical)).
+    ///
+    /// `|` groups are normalized per branch; any `*` branch makes the whole
+    /// constraint `*`.
+    fn canonical_constraint(&self) -> Option<String> {
+        let branches: Vec<String> = self.branches().into_iter().map(canonical_branch).collect::<Option<_>>()?;
+        if branches.iter().any(|b| b == "*") {
+            return Some("*".to_string());
+        }
+        Some(branches.join(&OR_SEP.to_string()))
+    }
+
     /// Get parsed Version for exact constraints.
     pub fn version(&self) -> Result<Version, PackageError> {
         Version::parse(&self.constraint).map_err(|e| PackageError::InvalidVersion {
@@ -364,6 +556,66 @@ impl DepSpec {
     }
 }
 
+/// Whether `ver` is a prerelease (`2026.1.0-rc.1`, `1.0.0-0`).
+///
+/// Only the first pre identifier counts: numeric, or starting with one of
+/// the usual tags (alpha/beta/rc/dev...). Anything else (`-win64`, `-py310`)
+/// is a variant of the release, see [`variant_release`].
+pub fn is_prerelease(ver: &Version) -> bool {
+    let Some(first) = ver.pre.as_str().split('.').next().filter(|id| !id.is_empty()) else {
+        return false;
+    };
+    if first.bytes().all(|b| b.is_ascii_digit()) {
+        return true;
+    }
+    let tag = &first[..first.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(first.len())];
+    PRERELEASE_TAGS.iter().any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+/// The release a variant belongs to: `2026.0.0-win64` → `2026.0.0`.
+///
+/// None for plain releases and prereleases.
+pub fn variant_release(ver: &Version) -> Option<Version> {
+    if ver.pre.is_empty() || is_prerelease(ver) {
+        return None;
+    }
+    Some(Version::new(ver.major, ver.minor, ver.patch))
+}
+
+/// Strip one `[extra,...]` group from a spec.
+///
+/// `maya[gpu,debug]@>=2026` → `("maya@>=2026", ["gpu", "debug"])`.
+/// Returns None if the spec has no group.
+pub fn split_extras(spec: &str) -> Result<Option<(String, Vec<String>)>, PackageError> {
+    let Some(open) = spec.find('[') else {
+        return Ok(None);
+    };
+    let invalid = |reason: &str| PackageError::InvalidName {
+        name: spec.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let close = spec[open..]
+        .find(']')
+        .map(|i| open + i)
+        .ok_or_else(|| invalid("Unclosed extras group"))?;
+    let plain = format!("{}{}", &spec[..open], &spec[close + 1..]);
+    if plain.contains('[') || plain.contains(']') {
+        return Err(invalid("Only one extras group allowed"));
+    }
+
+    let extras: Vec<String> = spec[open + 1..close]
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(String::from)
+        .collect();
+    if extras.is_empty() {
+        return Err(invalid("Empty extras group"));
+    }
+    Ok(Some((plain, extras)))
+}
+
 impl fmt::Display for DepSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.original)
@@ -376,6 +628,241 @@ impl Default for DepSpec {
     }
 }
 
+/// Normalize one constraint branch (no `|`) to `>=lo,<hi[,!=x]` form.
+///
+/// Exclusions are appended sorted by version.
+fn canonical_branch(constraint: &str) -> Option<String> {
+    let (rest, excluded) = split_exclusions(constraint);
+    if excluded.is_empty() {
+        return canonical_range(constraint);
+    }
+
+    let mut excluded: Vec<Version> = excluded.into_iter().map(|v| Version::parse(v).ok()).collect::<Option<_>>()?;
+    excluded.sort();
+    excluded.dedup();
+    let mut parts: Vec<String> = match canonical_range(&rest)? {
+        any if any == "*" => Vec::new(),
+        range => vec![range],
+    };
+    parts.extend(excluded.iter().map(|v| format!("!={}", v)));
+    Some(parts.join(","))
+}
+
+/// Normalize a plain range (no `|`, no `!=`) to `>=lo,<hi` form.
+fn canonical_range(constraint: &str) -> Option<String> {
+    if constraint == "*" {
+        return Some("*".to_string());
+    }
+    if let Ok(ver) = Version::parse(constraint) {
+        return Some(ver.to_string());
+    }
+
+    let req = VersionReq::parse(constraint).ok()?;
+
+    // Intersect all comparators: keep the tightest bound on each side
+    let mut lower: Option<Bound> = None;
+    let mut upper: Option<Bound> = None;
+    for cmp in &req.comparators {
+        let (lo, hi) = comparator_bounds(cmp)?;
+        if let Some(lo) = lo {
+            if lower.as_ref().is_none_or(|cur| lo.0 > cur.0 || (lo.0 == cur.0 && !lo.1)) {
+                lower = Some(lo);
+            }
+        }
+        if let Some(hi) = hi {
+            if upper.as_ref().is_none_or(|cur| hi.0 < cur.0 || (hi.0 == cur.0 && !hi.1)) {
+                upper = Some(hi);
+            }
+        }
+    }
+
+    if let (Some((lo, true)), Some((hi, true))) = (&lower, &upper) {
+        if lo == hi {
+            return Some(lo.to_string());
+        }
+    }
+
+    let mut parts = Vec::new();
+    if let Some((ver, inclusive)) = lower {
+        parts.push(format!("{}{}", if inclusive { ">=" } else { ">" }, ver));
+    }
+    if let Some((ver, inclusive)) = upper {
+        parts.push(format!("{}{}", if inclusive { "<=" } else { "<" }, ver));
+    }
+
+    if parts.is_empty() {
+        Some("*".to_string())
+    } else {
+        Some(parts.join(","))
+    }
+}
+
+/// Check one constraint branch (no `|`) against a parsed version.
+fn branch_matches(constraint: &str, ver: &Version) -> Result<bool, PackageError> {
+    let (constraint, excluded) = split_exclusions(constraint);
+    if excluded.iter().any(|v| Version::parse(v).is_ok_and(|v| v == *ver)) {
+        return Ok(false);
+    }
+    let constraint = constraint.as_str();
+
+    if constraint == "*" {
+        return Ok(true);
+    }
+
+    // Try exact match first
+    if let Ok(exact) = Version::parse(constraint) {
+        return Ok(*ver == exact);
+    }
+
+    // Try as version requirement
+    let req = VersionReq::parse(constraint).map_err(|e| PackageError::InvalidVersion {
+        version: constraint.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    // semver only lets a prerelease past comparators on the same x.y.z;
+    // the spec already decided whether prereleases are in, so just bound it
+    if !ver.pre.is_empty() {
+        return Ok(req
+            .comparators
+            .iter()
+            .all(|cmp| comparator_contains(cmp, ver).unwrap_or_else(|| cmp.matches(ver))));
+    }
+
+    Ok(req.matches(ver))
+}
+
+/// Whether `ver` lies within a comparator's bounds (None if unsupported).
+fn comparator_contains(cmp: &Comparator, ver: &Version) -> Option<bool> {
+    let (lower, upper) = comparator_bounds(cmp)?;
+    let above = lower.is_none_or(|(lo, inclusive)| *ver > lo || (inclusive && *ver == lo));
+    let below = upper.is_none_or(|(hi, inclusive)| *ver < hi || (inclusive && *ver == hi));
+    Some(above && below)
+}
+
+/// Remove `pre` terms from a constraint: `>=2026,pre` → (`>=2026`, true).
+///
+/// A branch left empty becomes `*`.
+fn strip_prerelease_flag(constraint: &str) -> (String, bool) {
+    let is_flag = |term: &str| term.trim() == PRERELEASE_FLAG;
+    if !constraint.split([OR_SEP, ',']).any(is_flag) {
+        return (constraint.to_string(), false);
+    }
+    let branches: Vec<String> = constraint
+        .split(OR_SEP)
+        .map(|branch| {
+            let terms: Vec<&str> = branch.split(',').map(str::trim).filter(|t| !is_flag(t)).collect();
+            if terms.is_empty() { "*".to_string() } else { terms.join(",") }
+        })
+        .collect();
+    (branches.join(&OR_SEP.to_string()), true)
+}
+
+/// Desugar `^`/`~` terms to explicit ranges: `^1.2` → `>=1.2.0,<2.0.0`.
+///
+/// Follows semver rules including the `0.x` cases (`^0.2.3` → `<0.3.0`,
+/// `^0.0.3` → `<0.0.4`, `^0` → `<1.0.0`) and partial tildes (`~1` → `<2.0.0`).
+/// Constraints without shorthand are returned unchanged.
+pub(crate) fn desugar_shorthand(constraint: &str) -> Result<String, PackageError> {
+    let is_shorthand = |term: &str| term.starts_with('^') || term.starts_with('~');
+    if !constraint.split([OR_SEP, ',']).map(str::trim).any(is_shorthand) {
+        return Ok(constraint.to_string());
+    }
+
+    let desugar_term = |term: &str| -> Result<String, PackageError> {
+        if !is_shorthand(term) {
+            return Ok(term.to_string());
+        }
+        let invalid = |reason: String| PackageError::InvalidVersion {
+            version: term.to_string(),
+            reason,
+        };
+        let req = VersionReq::parse(term).map_err(|e| invalid(e.to_string()))?;
+        match req.comparators.first().and_then(comparator_bounds) {
+            Some((Some((lo, _)), Some((hi, _)))) => Ok(format!(">={},<{}", lo, hi)),
+            _ => Err(invalid("Unsupported shorthand".to_string())),
+        }
+    };
+
+    let branches = constraint
+        .split(OR_SEP)
+        .map(|branch| {
+            let terms = branch.split(',').map(str::trim).map(&desugar_term).collect::<Result<Vec<_>, _>>()?;
+            Ok(terms.join(","))
+        })
+        .collect::<Result<Vec<_>, PackageError>>()?;
+    Ok(branches.join(&OR_SEP.to_string()))
+}
+
+/// Split `!=` terms off a branch: `>=2,!=2.1.3` → (`>=2`, `["2.1.3"]`).
+///
+/// The remainder is `*` when the branch only excludes versions.
+fn split_exclusions(constraint: &str) -> (String, Vec<&str>) {
+    if !constraint.contains("!=") {
+        return (constraint.to_string(), Vec::new());
+    }
+    let (excluded, rest): (Vec<&str>, Vec<&str>) =
+        constraint.split(',').map(str::trim).partition(|t| t.starts_with("!="));
+    let excluded = excluded.into_iter().map(|t| t[2..].trim()).collect();
+    let rest = if rest.is_empty() { "*".to_string() } else { rest.join(",") };
+    (rest, excluded)
+}
+
+/// Version bound: (version, inclusive).
+type Bound = (Version, bool);
+
+/// Expand a single semver comparator into explicit (lower, upper) bounds.
+///
+/// Follows semver's rules for partial versions: `<=1.2` → `<1.3.0`,
+/// `>1` → `>=2.0.0`, `^0.2` → `<0.3.0`, etc.
+fn comparator_bounds(cmp: &Comparator) -> Option<(Option<Bound>, Option<Bound>)> {
+    let major = cmp.major;
+    let minor = cmp.minor.unwrap_or(0);
+    let patch = cmp.patch.unwrap_or(0);
+
+    let mut floor = Version::new(major, minor, patch);
+    floor.pre = cmp.pre.clone();
+
+    // First version past the partially specified one: 1 → 2.0.0, 1.2 → 1.3.0
+    let past = match (cmp.minor, cmp.patch) {
+        (None, _) => Version::new(major + 1, 0, 0),
+        (Some(m), None) => Version::new(major, m + 1, 0),
+        (Some(m), Some(p)) => Version::new(major, m, p + 1),
+    };
+    let full = cmp.patch.is_some();
+
+    let bounds = match cmp.op {
+        Op::Exact | Op::Wildcard if full => (Some((floor.clone(), true)), Some((floor, true))),
+        Op::Exact | Op::Wildcard => (Some((floor, true)), Some((past, false))),
+        Op::Greater if full => (Some((floor, false)), None),
+        Op::Greater => (Some((past, true)), None),
+        Op::GreaterEq => (Some((floor, true)), None),
+        Op::Less => (None, Some((floor, false))),
+        Op::LessEq if full => (None, Some((floor, true))),
+        Op::LessEq => (None, Some((past, false))),
+        Op::Tilde => {
+            let upper = match cmp.minor {
+                None => Version::new(major + 1, 0, 0),
+                Some(m) => Version::new(major, m + 1, 0),
+            };
+            (Some((floor, true)), Some((upper, false)))
+        }
+        Op::Caret => {
+            let upper = if major > 0 || cmp.minor.is_none() {
+                Version::new(major + 1, 0, 0)
+            } else if minor > 0 || cmp.patch.is_none() {
+                Version::new(0, minor + 1, 0)
+            } else {
+                Version::new(0, 0, patch + 1)
+            };
+            (Some((floor, true)), Some((upper, false)))
+        }
+        _ => return None,
+    };
+
+    Some(bounds)
+}
+
 /// Parse multiple dependency specs from a list of strings.
 ///
 /// # Arguments
@@ -463,6 +950,25 @@ mod tests {
         assert_eq!(spec.constraint, "1.0.0");
     }
 
+    #[test]
+    fn depspec_parse_extras() {
+        let spec = DepSpec::parse_impl("maya[gpu, debug]@>=2026").unwrap();
+        assert_eq!(spec.base, "maya");
+        assert_eq!(spec.constraint, ">=2026");
+        assert_eq!(spec.extras, vec!["gpu", "debug"]);
+        assert_eq!(spec.original, "maya[gpu, debug]@>=2026");
+        assert_eq!(spec.canonical(), "maya[debug,gpu]@>=2026.0.0");
+
+        let resolved = DepSpec::parse_impl("maya[gpu]-2026.1.0").unwrap();
+        assert_eq!(resolved.base, "maya");
+        assert_eq!(resolved.constraint, "2026.1.0");
+
+        assert!(DepSpec::parse_impl("maya").unwrap().extras.is_empty());
+        assert!(DepSpec::parse_impl("maya[gpu").is_err());
+        assert!(DepSpec::parse_impl("maya[]").is_err());
+        assert!(DepSpec::parse_impl("maya[a][b]").is_err());
+    }
+
     #[test]
     fn depspec_matches() {
         // Range constraint
@@ -501,6 +1007,147 @@ mod tests {
         assert!(!tilde.matches_impl("1.3.0").unwrap());
     }
 
+    #[test]
+    fn depspec_or_groups() {
+        let spec = DepSpec::parse_impl("redshift@>=3.0,<4.0|>=5.0,<6.0").unwrap();
+        assert_eq!(spec.branches(), vec![">=3.0,<4.0", ">=5.0,<6.0"]);
+        assert!(spec.matches_impl("3.5.0").unwrap());
+        assert!(spec.matches_impl("5.2.1").unwrap());
+        assert!(!spec.matches_impl("4.1.0").unwrap());
+        assert!(!spec.matches_impl("6.0.0").unwrap());
+        assert!(!spec.is_exact());
+        assert!(!spec.is_any());
+        assert_eq!(spec.canonical(), "redshift@>=3.0.0,<4.0.0|>=5.0.0,<6.0.0");
+
+        // Exact alternatives
+        let either = DepSpec::parse_impl("ocio@2.1.0 | 2.3.0").unwrap();
+        assert!(either.matches_impl("2.3.0").unwrap());
+        assert!(!either.matches_impl("2.2.0").unwrap());
+
+        // Simple specs are unaffected
+        assert!(DepSpec::parse_impl("ocio@2.3.0").unwrap().is_exact());
+        assert!(DepSpec::parse_impl("ocio").unwrap().is_any());
+        assert_eq!(DepSpec::parse_impl("ocio@2|*").unwrap().canonical(), "ocio");
+
+        assert!(DepSpec::parse_impl("ocio@2|").is_err());
+        assert!(DepSpec::parse_impl("ocio@2|bogus").is_err());
+    }
+
+    #[test]
+    fn depspec_exclusions() {
+        let spec = DepSpec::parse_impl("ocio@>=2,!=2.1.3").unwrap();
+        assert!(spec.matches_impl("2.1.2").unwrap());
+        assert!(!spec.matches_impl("2.1.3").unwrap());
+        assert!(spec.matches_impl("2.1.4").unwrap());
+        assert!(!spec.matches_impl("1.9.0").unwrap());
+        assert_eq!(spec.canonical(), "ocio@>=2.0.0,!=2.1.3");
+
+        // Exclusion alone means any other version
+        let only = DepSpec::parse_impl("ocio@!=2.1.3").unwrap();
+        assert!(only.matches_impl("3.0.0").unwrap());
+        assert!(!only.matches_impl("2.1.3").unwrap());
+        assert!(!only.is_any());
+
+        // Per OR branch
+        let either = DepSpec::parse_impl("ocio@>=2,<3,!=2.1.3|>=4").unwrap();
+        assert!(!either.matches_impl("2.1.3").unwrap());
+        assert!(either.matches_impl("4.0.0").unwrap());
+
+        assert!(DepSpec::parse_impl("ocio@>=2,!=bogus").is_err());
+    }
+
+    #[test]
+    fn depspec_weak() {
+        let spec = DepSpec::parse_impl("~redshift@>=3.5").unwrap();
+        assert!(spec.weak);
+        assert_eq!(spec.base, "redshift");
+        assert!(spec.matches_impl("3.6.0").unwrap());
+        assert_eq!(spec.to_req_str(), "~redshift@>=3.5");
+        assert_eq!(spec.canonical(), "~redshift@>=3.5.0");
+        assert_eq!(DepSpec::parse_impl("~redshift").unwrap().canonical(), "~redshift");
+
+        // Not confused with the tilde operator
+        let tilde = DepSpec::parse_impl("redshift@~3.5").unwrap();
+        assert!(!tilde.weak);
+        assert!(DepSpec::parse_impl("~redshift@~3.5").unwrap().weak);
+
+        assert!(DepSpec::parse_impl("~~redshift").is_err());
+    }
+
+    #[test]
+    fn depspec_prerelease_opt_in() {
+        let plain = DepSpec::parse_impl("maya@>=2026.0.0").unwrap();
+        assert!(!plain.allows_prerelease());
+        assert!(!plain.matches_impl("2026.1.0-rc.1").unwrap());
+        assert!(plain.matches_impl("2026.1.0").unwrap());
+        assert!(!DepSpec::parse_impl("maya").unwrap().matches_impl("2026.1.0-rc.1").unwrap());
+
+        // Constraint names a prerelease
+        let named = DepSpec::parse_impl("maya@>=2026.1.0-0").unwrap();
+        assert!(named.allows_prerelease());
+        assert!(named.matches_impl("2026.1.0-rc.1").unwrap());
+        assert!(named.matches_impl("2026.2.0-beta.1").unwrap());
+        assert!(!named.matches_impl("2026.0.5").unwrap());
+
+        // Explicit opt-in
+        let opted = DepSpec::parse_impl("maya@>=2026.0.0,pre").unwrap();
+        assert!(opted.include_prerelease);
+        assert_eq!(opted.constraint, ">=2026.0.0");
+        assert!(opted.matches_impl("2026.1.0-rc.1").unwrap());
+        assert!(!opted.matches_impl("2025.9.0-rc.1").unwrap());
+        assert_eq!(opted.canonical(), "maya@>=2026.0.0,pre");
+        assert_eq!(opted.to_req_str(), "maya@>=2026.0.0,pre");
+
+        let any = DepSpec::parse_impl("maya@pre").unwrap();
+        assert!(any.is_any());
+        assert!(any.matches_impl("1.0.0-alpha").unwrap());
+        assert_eq!(any.canonical(), "maya@pre");
+
+        // Naming a prerelease only to exclude it doesn't opt in
+        assert!(!DepSpec::parse_impl("maya@!=2026.1.0-rc.1").unwrap().allows_prerelease());
+    }
+
+    #[test]
+    fn depspec_variants_match_by_release() {
+        let win = Version::parse("2026.0.0-win64").unwrap();
+        assert!(!is_prerelease(&win));
+        assert_eq!(variant_release(&win), Some(Version::new(2026, 0, 0)));
+        for pre in ["2026.1.0-rc.1", "1.0.0-0", "1.0.0-beta2", "1.0.0-DEV.3"] {
+            assert!(is_prerelease(&Version::parse(pre).unwrap()), "{pre}");
+        }
+
+        assert!(DepSpec::parse_impl("maya@>=2026").unwrap().matches_impl("2026.0.0-win64").unwrap());
+        assert!(DepSpec::parse_impl("maya").unwrap().matches_impl("2026.0.0-py310").unwrap());
+        assert!(!DepSpec::parse_impl("maya@<2026").unwrap().matches_impl("2026.0.0-win64").unwrap());
+        assert!(DepSpec::parse_impl("maya-2026.0.0-win64").unwrap().matches_impl("2026.0.0-win64").unwrap());
+        assert!(!DepSpec::parse_impl("maya-2026.0.0-win64").unwrap().matches_impl("2026.0.0").unwrap());
+    }
+
+    #[test]
+    fn depspec_caret_tilde_desugar() {
+        let constraint = |s: &str| DepSpec::parse_impl(s).unwrap().constraint;
+
+        assert_eq!(constraint("pkg@^1.2.3"), ">=1.2.3,<2.0.0");
+        assert_eq!(constraint("pkg@^0.2.3"), ">=0.2.3,<0.3.0");
+        assert_eq!(constraint("pkg@^0.0.3"), ">=0.0.3,<0.0.4");
+        assert_eq!(constraint("pkg@^1"), ">=1.0.0,<2.0.0");
+        assert_eq!(constraint("pkg@^0"), ">=0.0.0,<1.0.0");
+        assert_eq!(constraint("pkg@~1.2"), ">=1.2.0,<1.3.0");
+        assert_eq!(constraint("pkg@~1"), ">=1.0.0,<2.0.0");
+        assert_eq!(constraint("maya@^2026.0.0"), ">=2026.0.0,<2027.0.0");
+
+        // Mixed with other terms and OR groups
+        assert_eq!(constraint("pkg@^1.2,!=1.4.0|~3.1"), ">=1.2.0,<2.0.0,!=1.4.0|>=3.1.0,<3.2.0");
+
+        // Plain constraints are stored as written
+        assert_eq!(constraint("pkg@>= 2, < 3"), ">= 2, < 3");
+
+        let spec = DepSpec::parse_impl("pkg@^0.2.3").unwrap();
+        assert!(spec.matches_impl("0.2.9").unwrap());
+        assert!(!spec.matches_impl("0.3.0").unwrap());
+        assert!(DepSpec::parse_impl("pkg@^bogus").is_err());
+    }
+
     #[test]
     fn depspec_to_formats() {
         let req = DepSpec::new("redshift".to_string(), Some(">=3.5".to_string()));
@@ -531,6 +1178,43 @@ mod tests {
         assert!(!matches.contains(&"redshift-4.0.0"));
     }
 
+    #[test]
+    fn depspec_canonical() {
+        let canon = |s: &str| DepSpec::parse_impl(s).unwrap().canonical();
+
+        // Major shorthand
+        for s in [
+            "ocio@2",
+            "ocio@^2",
+            "ocio@2.x",
+            "ocio@~2",
+            "ocio@>=2.0.0,<3.0.0",
+            "ocio@<3.0.0, >=2.0.0",
+            "ocio@>= 2, < 3",
+        ] {
+            assert_eq!(canon(s), "ocio@>=2.0.0,<3.0.0", "input: {}", s);
+        }
+
+        // Tilde / minor shorthand
+        for s in ["ocio@~2.1", "ocio@2.1.*", "ocio@>=2.1,<2.2", "ocio@>=2.1.0,<=2.1"] {
+            assert_eq!(canon(s), "ocio@>=2.1.0,<2.2.0", "input: {}", s);
+        }
+
+        // Exact versions
+        for s in ["ocio@2.1.0", "ocio@=2.1.0", "ocio-2.1.0", "ocio@>=2.1.0,<=2.1.0"] {
+            assert_eq!(canon(s), "ocio@2.1.0", "input: {}", s);
+        }
+
+        // Any version
+        assert_eq!(canon("ocio"), "ocio");
+        assert_eq!(canon("ocio@*"), "ocio");
+
+        // Tightest bound wins
+        assert_eq!(canon("ocio@>=2.0,>=2.5,<4,<3"), "ocio@>=2.5.0,<3.0.0");
+        assert_eq!(canon("ocio@>2.0.0,>=2.0.0"), "ocio@>2.0.0");
+        assert_eq!(canon("ocio@^0.2.3"), "ocio@>=0.2.3,<0.3.0");
+    }
+
     #[test]
     fn depspec_invalid() {
         // Empty
@@ -54,7 +54,7 @@
 //! ```
 
 use crate::error::EnvError;
-use crate::evar::Evar;
+use crate::evar::{Action, Evar};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -63,6 +63,138 @@ use std::collections::{HashMap, HashSet};
 /// Prevents infinite recursion in circular references.
 pub const DEFAULT_MAX_DEPTH: usize = 10;
 
+/// Environment variable overriding the prelude file location.
+const PRELUDE_VAR: &str = "PKG_PRELUDE";
+
+/// Prelude installed by the application, merged under every package env.
+static PRELUDE: std::sync::RwLock<Option<Env>> = std::sync::RwLock::new(None);
+
+/// Prefix for backup variables in activation scripts.
+const BACKUP_PREFIX: &str = "_PKG_OLD_";
+
+/// Backup value marking a variable that was unset before activation.
+const UNSET_MARKER: &str = "__PKG_UNSET__";
+
+/// Environment variable overriding the blacklist (comma-separated names).
+const BLACKLIST_VAR: &str = "PKG_ENV_BLACKLIST";
+
+/// Variables never applied unless `$PKG_ENV_BLACKLIST` says otherwise:
+/// they inject code into every child process.
+const DEFAULT_BLACKLIST: &[&str] = &["LD_PRELOAD", "LD_AUDIT", "DYLD_INSERT_LIBRARIES"];
+
+/// Well-known Windows variables in canonical casing.
+///
+/// Windows treats names case-insensitively, but tools reading the exported
+/// script (or child processes on other shells) may not.
+const WINDOWS_CANONICAL_NAMES: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "TEMP",
+    "TMP",
+    "COMSPEC",
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "USERPROFILE",
+    "USERNAME",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "PSMODULEPATH",
+    "PYTHONPATH",
+    "PYTHONHOME",
+];
+
+/// Single-quote a word for fish unless it only has safe characters.
+fn fish_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+:=,@%".contains(c));
+    if safe {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Escape a value for a double-quoted PowerShell string.
+///
+/// Backticks, `$` and double quotes (including the typographic ones
+/// PowerShell also accepts) get a backtick, so the value is taken literally.
+pub fn ps1_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '`' | '$' | '"' | '\u{201C}' | '\u{201D}' | '\u{201E}') {
+            escaped.push('`');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Single-quote a value for POSIX shells; embedded `'` becomes `'\''`.
+///
+/// Nothing inside single quotes is expanded, so `$(...)`, backticks and
+/// `\` stay literal when the output is `eval`ed or sourced.
+pub fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Whether `name` is a valid shell variable name (`[A-Za-z_][A-Za-z0-9_]*`).
+///
+/// Shell exports skip other names rather than emit them unquoted.
+pub fn is_shell_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Quote a value for a dotenv file if it needs it.
+fn dotenv_value(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '$' | '#' | '\\' | '`'));
+    if plain {
+        return value.to_string();
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Remove repeated `sep`-separated entries, keeping the first of each.
+fn dedup_entries(value: &str, sep: &str) -> String {
+    let mut seen = HashSet::new();
+    value
+        .split(sep)
+        .filter(|entry| {
+            let key = if cfg!(windows) { entry.to_lowercase() } else { entry.to_string() };
+            seen.insert(key)
+        })
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// Variable name as written by exporters: canonical casing for known
+/// names when `normalize` is set, unchanged otherwise.
+fn export_name(name: &str, normalize: bool) -> &str {
+    if normalize {
+        if let Some(canonical) = WINDOWS_CANONICAL_NAMES
+            .iter()
+            .find(|c| c.eq_ignore_ascii_case(name))
+        {
+            return *canonical;
+        }
+    }
+    name
+}
+
 /// Named collection of environment variables.
 ///
 /// An Env groups related [`Evar`]s together under a name. Packages can have
@@ -200,36 +332,16 @@ impl Env {
 
     /// Compress same-name evars into single evars.
     ///
-    /// Iterates through evars in order, merging evars with the same name
-    /// using their action semantics. The result has at most one evar per name.
-    ///
-    /// This is typically called after merging multiple environments.
-    ///
-    /// # Example
-    /// ```text
-    /// // Before: PATH=/a (append), PATH=/b (append)
-    /// // After:  PATH=/a:/b (set)
-    /// ```
-    pub fn compress(&self) -> Env {
-        let mut result = Env::new(self.name.clone());
-        let mut seen: HashMap<String, usize> = HashMap::new(); // name -> index in result
-
-        for evar in &self.evars {
-            let name_lower = evar.name.to_lowercase();
-
-            if let Some(&idx) = seen.get(&name_lower) {
-                // Merge with existing evar
-                let existing = &result.evars[idx];
-                let merged = existing.merge(evar);
-                result.evars[idx] = merged;
-            } else {
-                // First occurrence of this name
-                seen.insert(name_lower, result.evars.len());
-                result.evars.push(evar.clone());
-            }
+    /// # Arguments
+    /// * `dedup` - Also drop repeated entries from list variables
+    ///   (see [`compress_dedup`](Self::compress_dedup))
+    #[pyo3(name = "compress", signature = (dedup = false))]
+    fn py_compress(&self, dedup: bool) -> Env {
+        if dedup {
+            self.compress_dedup()
+        } else {
+            self.compress()
         }
-
-        result
     }
 
     /// Solve all token references in evars.
@@ -241,6 +353,8 @@ impl Env {
     /// # Arguments
     /// * `max_depth` - Maximum recursion depth (default: 10)
     /// * `use_os_fallback` - If true, fallback to std::env for unknown vars
+    /// * `strict` - Only substitute evars marked `referencable`
+    /// * `dollar_syntax` - Also expand `${VAR}` (`$${` escapes to `${`)
     ///
     /// # Returns
     /// New Env with all tokens expanded.
@@ -248,15 +362,19 @@ impl Env {
     /// # Errors
     /// - Circular reference detected
     /// - Maximum depth exceeded
-    #[pyo3(signature = (max_depth = None, use_os_fallback = None))]
+    #[pyo3(signature = (max_depth = None, use_os_fallback = None, strict = false, dollar_syntax = false))]
     pub fn solve(
         &self,
         max_depth: Option<usize>,
         use_os_fallback: Option<bool>,
+        strict: bool,
+        dollar_syntax: bool,
     ) -> PyResult<Env> {
-        self.solve_impl(
+        self.solve_tokens(
             max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
             use_os_fallback.unwrap_or(true),
+            strict,
+            dollar_syntax,
         )
         .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
@@ -271,17 +389,116 @@ impl Env {
         }
     }
 
+    /// Apply all evars like [`commit`](Self::commit), returning a guard that
+    /// puts the touched variables back when dropped.
+    ///
+    /// In Python the guard is a context manager:
+    /// `with env.commit_scoped(): launch(...)`.
+    #[must_use = "the environment is restored when the guard is dropped"]
+    pub fn commit_scoped(&self) -> EnvGuard {
+        let saved = self
+            .unique_names()
+            .into_iter()
+            .map(|name| (name.to_string(), std::env::var(name).ok()))
+            .collect();
+        self.commit();
+        EnvGuard { saved, restored: false }
+    }
+
+    /// Apply evars to the process environment, skipping blacklisted names.
+    ///
+    /// # Arguments
+    /// * `blacklist` - Names never to set (case-insensitive); defaults to
+    ///   `Env.default_blacklist()`
+    ///
+    /// # Returns
+    /// Names that were skipped.
+    #[pyo3(name = "commit_filtered", signature = (blacklist = None))]
+    fn py_commit_filtered(&self, blacklist: Option<Vec<String>>) -> Vec<String> {
+        self.commit_filtered(&blacklist.unwrap_or_else(Self::default_blacklist))
+    }
+
+    /// Preview what [`commit`](Self::commit) would change in the current
+    /// OS environment.
+    ///
+    /// Simulates each evar's action, in order, against `std::env::vars()`.
+    ///
+    /// # Returns
+    /// (name, old value, new value) per variable that would change, in
+    /// first-seen order. Old is None for new variables; unset variables
+    /// report an empty new value.
+    pub fn diff_os(&self) -> Vec<(String, Option<String>, String)> {
+        let mut order: Vec<String> = Vec::new();
+        let mut state: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
+        for evar in &self.evars {
+            let (_, current) = state.entry(evar.name.clone()).or_insert_with(|| {
+                order.push(evar.name.clone());
+                let os = std::env::var(&evar.name).ok();
+                (os.clone(), os)
+            });
+            *current = evar.apply(current.as_deref());
+        }
+
+        order
+            .into_iter()
+            .filter_map(|name| {
+                let (old, new) = state.remove(&name)?;
+                (old != new).then(|| (name, old, new.unwrap_or_default()))
+            })
+            .collect()
+    }
+
+    /// Names that must never be applied.
+    ///
+    /// Read from `$PKG_ENV_BLACKLIST` (comma-separated, empty disables),
+    /// otherwise `LD_PRELOAD`, `LD_AUDIT` and `DYLD_INSERT_LIBRARIES`.
+    #[staticmethod]
+    pub fn default_blacklist() -> Vec<String> {
+        match std::env::var(BLACKLIST_VAR) {
+            Ok(names) => names
+                .split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(String::from)
+                .collect(),
+            Err(_) => DEFAULT_BLACKLIST.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+
     /// Convert to HashMap for current OS.
     ///
     /// Returns a dict mapping variable names to their values.
     /// If there are multiple evars with the same name, the last one wins.
+    /// Unset variables are omitted.
     pub fn to_map(&self) -> HashMap<String, String> {
         self.evars
             .iter()
+            .filter(|e| !e.is_unset())
             .map(|e| (e.name.clone(), e.value.clone()))
             .collect()
     }
 
+    /// Convert to ordered (name, value) pairs.
+    ///
+    /// Unlike `to_map`, keeps evar order and duplicates. Call on a solved
+    /// env to get one pair per variable.
+    pub fn to_pairs(&self) -> Vec<(String, String)> {
+        self.evars
+            .iter()
+            .map(|e| (e.name.clone(), e.value.clone()))
+            .collect()
+    }
+
+    /// Variables defined more than once (before compression).
+    ///
+    /// Names compare case-insensitively, like [`get`](Self::get).
+    ///
+    /// # Returns
+    /// (name, count) pairs in first-seen order.
+    pub fn duplicates(&self) -> Vec<(String, usize)> {
+        self.count_names(|_| true)
+    }
+
     /// Convert to dictionary.
     ///
     /// Returns dict with keys: name, evars
@@ -324,27 +541,44 @@ impl Env {
 
     /// Export as Windows CMD script.
     ///
-    /// Generates `SET VAR=value` lines for cmd.exe.
+    /// Generates `SET VAR=value` lines for cmd.exe (`SET VAR=` for unset).
     /// Use with: `env.to_cmd() > setup.cmd`
-    pub fn to_cmd(&self) -> String {
+    ///
+    /// # Arguments
+    /// * `normalize_case` - Emit well-known names in canonical casing
+    ///   (`Path` → `PATH`). Defaults to on for Windows, off elsewhere.
+    #[pyo3(signature = (normalize_case = None))]
+    pub fn to_cmd(&self, normalize_case: Option<bool>) -> String {
+        let normalize = normalize_case.unwrap_or(cfg!(windows));
         self.evars
             .iter()
-            .map(|e| format!("SET {}={}", e.name, e.value))
+            .map(|e| {
+                let value = if e.is_unset() { "" } else { e.value.as_str() };
+                format!("SET {}={}", export_name(&e.name, normalize), value)
+            })
             .collect::<Vec<_>>()
             .join("\r\n")
     }
 
     /// Export as PowerShell script.
     ///
-    /// Generates `$env:VAR = "value"` lines.
+    /// Generates `$env:VAR = "value"` lines (`Remove-Item Env:VAR` for unset).
     /// Use with: `env.to_ps1() > setup.ps1`
-    pub fn to_ps1(&self) -> String {
-        self.evars
-            .iter()
+    ///
+    /// # Arguments
+    /// * `normalize_case` - Same as [`to_cmd`](Self::to_cmd)
+    #[pyo3(signature = (normalize_case = None))]
+    pub fn to_ps1(&self, normalize_case: Option<bool>) -> String {
+        let normalize = normalize_case.unwrap_or(cfg!(windows));
+        self.shell_evars()
             .map(|e| {
-                // Escape double quotes in value
-                let escaped = e.value.replace('"', "`\"");
-                format!("$env:{} = \"{}\"", e.name, escaped)
+                if e.is_unset() {
+                    return format!(
+                        "Remove-Item Env:{} -ErrorAction SilentlyContinue",
+                        export_name(&e.name, normalize)
+                    );
+                }
+                format!("$env:{} = \"{}\"", export_name(&e.name, normalize), ps1_escape(&e.value))
             })
             .collect::<Vec<_>>()
             .join("\n")
@@ -352,20 +586,87 @@ impl Env {
 
     /// Export as Bash/sh script.
     ///
-    /// Generates `export VAR="value"` lines.
+    /// Generates `export VAR='value'` lines (`unset VAR` for unset), values
+    /// single-quoted with [`sh_quote`].
     /// Use with: `env.to_sh() > setup.sh`
     pub fn to_sh(&self) -> String {
-        self.evars
-            .iter()
+        self.shell_evars()
             .map(|e| {
-                // Escape double quotes and backslashes
-                let escaped = e.value.replace('\\', "\\\\").replace('"', "\\\"");
-                format!("export {}=\"{}\"", e.name, escaped)
+                if e.is_unset() {
+                    return format!("unset {}", e.name);
+                }
+                format!("export {}={}", e.name, sh_quote(&e.value))
             })
             .collect::<Vec<_>>()
             .join("\n")
     }
 
+    /// Export as self-contained Bash activation script.
+    ///
+    /// Like [`to_sh`](Self::to_sh), but first saves prior values into
+    /// `_PKG_OLD_<VAR>` backups and defines a `deactivate` function that
+    /// restores them (unsetting variables that did not exist before).
+    /// Use with: `source activate.sh`, then `deactivate`
+    pub fn to_activation_sh(&self) -> String {
+        let mut names = self.unique_names();
+        names.retain(|n| is_shell_name(n));
+        let mut lines = vec!["# Save previous values".to_string()];
+        for name in &names {
+            lines.push(format!(
+                "{}{}=\"${{{}-{}}}\"",
+                BACKUP_PREFIX, name, name, UNSET_MARKER
+            ));
+        }
+        lines.push(String::new());
+        lines.push(self.to_sh());
+        lines.push(String::new());
+        lines.push("deactivate() {".to_string());
+        for name in &names {
+            let backup = format!("{}{}", BACKUP_PREFIX, name);
+            lines.push(format!(
+                "    if [ \"${}\" = \"{}\" ]; then unset {}; else export {}=\"${}\"; fi",
+                backup, UNSET_MARKER, name, name, backup
+            ));
+            lines.push(format!("    unset {}", backup));
+        }
+        lines.push("    unset -f deactivate".to_string());
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    /// Export as self-contained PowerShell activation script.
+    ///
+    /// Like [`to_ps1`](Self::to_ps1), but first saves prior values into
+    /// `$global:_PKG_OLD_<VAR>` backups and defines a global `deactivate`
+    /// function that restores them.
+    /// Use with: `. .\activate.ps1`, then `deactivate`
+    pub fn to_activation_ps1(&self) -> String {
+        let mut names = self.unique_names();
+        names.retain(|n| is_shell_name(n));
+        let mut lines = vec!["# Save previous values".to_string()];
+        for name in &names {
+            lines.push(format!("$global:{}{} = $env:{}", BACKUP_PREFIX, name, name));
+        }
+        lines.push(String::new());
+        lines.push(self.to_ps1(None));
+        lines.push(String::new());
+        lines.push("function global:deactivate {".to_string());
+        for name in &names {
+            let backup = format!("$global:{}{}", BACKUP_PREFIX, name);
+            lines.push(format!(
+                "    if ($null -eq {}) {{ Remove-Item Env:{} -ErrorAction SilentlyContinue }} else {{ $env:{} = {} }}",
+                backup, name, name, backup
+            ));
+            lines.push(format!(
+                "    Remove-Variable -Name {}{} -Scope Global -ErrorAction SilentlyContinue",
+                BACKUP_PREFIX, name
+            ));
+        }
+        lines.push("    Remove-Item Function:deactivate".to_string());
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
     /// Export as Python script.
     ///
     /// Generates `os.environ['VAR'] = 'value'` lines.
@@ -381,6 +682,81 @@ impl Env {
         lines.join("\n")
     }
 
+    /// Export as fish shell script.
+    ///
+    /// Generates `set -gx VAR value` lines. Fish treats variables ending in
+    /// `PATH` as lists, so their values are split on the separator and
+    /// appends/prepends extend the existing list (`set -gx PATH $PATH /new/bin`).
+    /// Unset variables become `set -e VAR`.
+    /// Use with: `env.to_fish() | source`
+    pub fn to_fish(&self) -> String {
+        self.shell_evars()
+            .map(|e| {
+                if e.is_unset() {
+                    return format!("set -e {}", e.name);
+                }
+                let current = format!("${}", e.name);
+                let value = if e.name.ends_with("PATH") {
+                    let items: Vec<String> = e
+                        .value
+                        .split(e.list_separator().as_str())
+                        .filter(|item| !item.is_empty())
+                        .map(fish_quote)
+                        .collect();
+                    let items = items.join(" ");
+                    match e.get_action() {
+                        Action::Append => format!("{} {}", current, items),
+                        Action::Prepend | Action::Insert => format!("{} {}", items, current),
+                        _ => items,
+                    }
+                } else {
+                    let sep = fish_quote(&e.list_separator());
+                    let quoted = fish_quote(&e.value);
+                    match e.get_action() {
+                        Action::Append => format!("\"{}\"{}{}", current, sep, quoted),
+                        Action::Prepend | Action::Insert => format!("{}{}\"{}\"", quoted, sep, current),
+                        _ => quoted,
+                    }
+                };
+                format!("set -gx {} {}", e.name, value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Export as dotenv (`.env`) file.
+    ///
+    /// Generates `VAR=value` lines for docker/CI. Values with whitespace
+    /// or special characters are double-quoted, escaping `\`, `"`, `$`
+    /// and newlines. Unset variables are omitted.
+    /// Use with: `env.to_dotenv() > .env`
+    pub fn to_dotenv(&self) -> String {
+        self.evars
+            .iter()
+            .filter(|e| !e.is_unset())
+            .map(|e| format!("{}={}", e.name, dotenv_value(&e.value)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Compare with another env.
+    ///
+    /// Returns dict with keys: only_self, only_other (lists of names),
+    /// differing (name -> (self value, other value)).
+    #[pyo3(name = "compare")]
+    fn py_compare(&self, py: Python<'_>, other: &Env) -> PyResult<Py<PyAny>> {
+        let cmp = self.compare(other);
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("only_self", &cmp.only_self)?;
+        dict.set_item("only_other", &cmp.only_other)?;
+        let differing = pyo3::types::PyDict::new(py);
+        for (name, ours, theirs) in &cmp.differing {
+            differing.set_item(name, (ours, theirs))?;
+        }
+        dict.set_item("differing", differing)?;
+        Ok(dict.into())
+    }
+
     /// Serialize to JSON string.
     pub fn to_json(&self) -> PyResult<String> {
         use crate::error::IntoPyErr;
@@ -394,6 +770,45 @@ impl Env {
         serde_json::from_str(json).py_err()
     }
 
+    /// Load the site-wide prelude env, if configured.
+    ///
+    /// Read from `$PKG_PRELUDE` or `~/.pkg-rs/prelude.json` (Env JSON).
+    /// [`Package`](crate::Package) envs merge it first, so its values act as
+    /// overridable defaults. Returns None if the file is missing or invalid.
+    #[staticmethod]
+    pub fn from_prelude() -> Option<Self> {
+        let path = Self::prelude_path()?;
+        if !path.is_file() {
+            return None;
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Env>(&s).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(env) => Some(env),
+            Err(e) => {
+                log::warn!("Ignoring prelude {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Install the prelude merged under every [`Package`](crate::Package) env.
+    ///
+    /// Applications load it once (usually via [`from_prelude`](Self::from_prelude))
+    /// and install it here; `None` removes it. Nothing is installed by default.
+    #[staticmethod]
+    pub fn set_prelude(prelude: Option<Env>) {
+        let mut slot = PRELUDE.write().unwrap_or_else(|e| e.into_inner());
+        *slot = prelude;
+    }
+
+    /// Currently installed prelude, if any.
+    #[staticmethod]
+    pub fn prelude() -> Option<Env> {
+        PRELUDE.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     /// String representation for Python
     fn __repr__(&self) -> String {
         format!("Env({:?}, {} evars)", self.name, self.evars.len())
@@ -411,6 +826,51 @@ impl Env {
 
 // Rust-only methods (not exposed to Python)
 impl Env {
+    /// Returns variable names in first-seen order, without duplicates.
+    /// Evars whose names shells accept, warning about the rest.
+    fn shell_evars(&self) -> impl Iterator<Item = &Evar> {
+        self.evars.iter().filter(|e| {
+            let valid = is_shell_name(&e.name);
+            if !valid {
+                log::warn!("Skipping variable with invalid name {:?} in shell output", e.name);
+            }
+            valid
+        })
+    }
+
+    fn unique_names(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.evars
+            .iter()
+            .map(|e| e.name.as_str())
+            .filter(|n| seen.insert(*n))
+            .collect()
+    }
+
+    /// Variables with more than one `set` evar, where the last silently wins.
+    ///
+    /// Repeated appends/inserts are legitimate and not reported.
+    pub fn duplicate_sets(&self) -> Vec<(String, usize)> {
+        self.count_names(|e| e.get_action() == Action::Set)
+    }
+
+    /// Count evars per name (case-insensitive) matching `filter`, keeping counts > 1.
+    fn count_names(&self, filter: impl Fn(&Evar) -> bool) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for evar in self.evars.iter().filter(|e| filter(e)) {
+            match index.get(&evar.name.to_lowercase()) {
+                Some(&i) => counts[i].1 += 1,
+                None => {
+                    index.insert(evar.name.to_lowercase(), counts.len());
+                    counts.push((evar.name.clone(), 1));
+                }
+            }
+        }
+        counts.retain(|(_, n)| *n > 1);
+        counts
+    }
+
     /// Returns evars sorted by name (for display).
     pub fn evars_sorted(&self) -> Vec<&Evar> {
         let mut sorted: Vec<_> = self.evars.iter().collect();
@@ -439,8 +899,234 @@ impl EnvIter {
     }
 }
 
+/// Restore handle returned by [`Env::commit_scoped`].
+///
+/// Holds the values the touched variables had before the commit
+/// (None = not set) and writes them back on [`restore`](Self::restore)
+/// or drop, whichever comes first.
+#[pyclass]
+pub struct EnvGuard {
+    saved: Vec<(String, Option<String>)>,
+    restored: bool,
+}
+
+#[pymethods]
+impl EnvGuard {
+    /// Put the saved values back. Later calls are no-ops.
+    pub fn restore(&mut self) {
+        if self.restored {
+            return;
+        }
+        for (name, value) in &self.saved {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+        self.restored = true;
+    }
+
+    /// Names of the variables this guard restores.
+    #[getter]
+    pub fn names(&self) -> Vec<String> {
+        self.saved.iter().map(|(n, _)| n.clone()).collect()
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.restore();
+        false
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+/// Result of [`Env::compare`]: variable-level drift between two envs.
+///
+/// Names are taken from the env they were found in; `differing` holds
+/// `(name, self value, other value)`. All lists are sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvComparison {
+    /// Variables present only in self
+    pub only_self: Vec<String>,
+    /// Variables present only in other
+    pub only_other: Vec<String>,
+    /// Variables present in both with different values
+    pub differing: Vec<(String, String, String)>,
+}
+
+impl EnvComparison {
+    /// True if both envs are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.only_self.is_empty() && self.only_other.is_empty() && self.differing.is_empty()
+    }
+}
+
 // Pure Rust API
 impl Env {
+    /// Compress same-name evars into single evars.
+    ///
+    /// Groups evars by name and collapses each group with
+    /// [`Evar::normalize_sequence`]. The result has at most one evar per name,
+    /// in order of first appearance.
+    ///
+    /// This is typically called after merging multiple environments.
+    ///
+    /// # Example
+    /// ```text
+    /// // Before: PATH=/a (append), PATH=/b (append)
+    /// // After:  PATH=/a:/b (set)
+    /// ```
+    pub fn compress(&self) -> Env {
+        // Group evars by name (case-insensitive), keeping first-seen order
+        let mut order: Vec<String> = Vec::new();
+        let mut groups: HashMap<String, Vec<Evar>> = HashMap::new();
+
+        for evar in &self.evars {
+            let name_lower = evar.name.to_lowercase();
+            groups
+                .entry(name_lower.clone())
+                .or_insert_with(|| {
+                    order.push(name_lower);
+                    Vec::new()
+                })
+                .push(evar.clone());
+        }
+
+        let mut result = Env::new(self.name.clone());
+        result.evars = order
+            .iter()
+            .map(|name| Evar::normalize_sequence(&groups[name]))
+            .collect();
+
+        result
+    }
+
+    /// Like [`compress`](Self::compress), then drop repeated entries from
+    /// list variables.
+    ///
+    /// A variable is list-type if any of its evars appends, prepends or
+    /// inserts. Its value is split on the evar's separator and later
+    /// duplicates are removed, keeping first-occurrence order. Entries
+    /// compare case-insensitively on Windows, case-sensitively elsewhere.
+    ///
+    /// # Example
+    /// ```text
+    /// // Before: PATH=/a (append), PATH=/b (append), PATH=/b (insert)
+    /// // After:  PATH=/b:/a (set)
+    /// ```
+    pub fn compress_dedup(&self) -> Env {
+        let lists: HashSet<String> = self
+            .evars
+            .iter()
+            .filter(|e| matches!(e.get_action(), Action::Append | Action::Prepend | Action::Insert))
+            .map(|e| e.name.to_lowercase())
+            .collect();
+
+        let mut result = self.compress();
+        for evar in &mut result.evars {
+            if lists.contains(&evar.name.to_lowercase()) {
+                evar.value = dedup_entries(&evar.value, &evar.list_separator());
+            }
+        }
+        result
+    }
+
+    /// Compare two envs variable by variable.
+    ///
+    /// Both sides are canonicalized first: compressed to one value per
+    /// variable (see [`compress`](Self::compress)) and matched by
+    /// case-insensitive name, so evar order and action splitting don't
+    /// count as drift.
+    pub fn compare(&self, other: &Env) -> EnvComparison {
+        let canonical = |env: &Env| -> HashMap<String, Evar> {
+            env.compress()
+                .evars
+                .into_iter()
+                .map(|e| (e.name.to_lowercase(), e))
+                .collect()
+        };
+        let left = canonical(self);
+        let right = canonical(other);
+
+        let mut result = EnvComparison::default();
+        for (key, evar) in &left {
+            match right.get(key) {
+                None => result.only_self.push(evar.name.clone()),
+                Some(theirs) if theirs.value != evar.value => result.differing.push((
+                    evar.name.clone(),
+                    evar.value.clone(),
+                    theirs.value.clone(),
+                )),
+                Some(_) => {}
+            }
+        }
+        for (key, evar) in &right {
+            if !left.contains_key(key) {
+                result.only_other.push(evar.name.clone());
+            }
+        }
+
+        result.only_self.sort();
+        result.only_other.sort();
+        result.differing.sort();
+        result
+    }
+
+    /// Prelude file location: `$PKG_PRELUDE` or `~/.pkg-rs/prelude.json`.
+    pub fn prelude_path() -> Option<std::path::PathBuf> {
+        if let Ok(path) = std::env::var(PRELUDE_VAR) {
+            if !path.is_empty() {
+                return Some(path.into());
+            }
+        }
+        dirs::home_dir().map(|h| h.join(".pkg-rs").join("prelude.json"))
+    }
+
+    /// Split off blacklisted variables (names compared case-insensitively).
+    ///
+    /// # Returns
+    /// (env without them, skipped names in first-seen order)
+    pub fn without_names(&self, blacklist: &[String]) -> (Env, Vec<String>) {
+        let blocked = |name: &str| blacklist.iter().any(|b| b.eq_ignore_ascii_case(name));
+        let mut kept = Env::new(self.name.clone());
+        let mut skipped: Vec<String> = Vec::new();
+        for evar in &self.evars {
+            if !blocked(&evar.name) {
+                kept.evars.push(evar.clone());
+            } else if !skipped.contains(&evar.name) {
+                skipped.push(evar.name.clone());
+            }
+        }
+        (kept, skipped)
+    }
+
+    /// [`commit`](Self::commit) everything except blacklisted variables.
+    ///
+    /// # Returns
+    /// Names that were skipped.
+    pub fn commit_filtered(&self, blacklist: &[String]) -> Vec<String> {
+        let (kept, skipped) = self.without_names(blacklist);
+        for name in &skipped {
+            log::warn!("Not applying blacklisted variable {}", name);
+        }
+        kept.commit();
+        skipped
+    }
+
     /// Create env from iterator of Evars.
     pub fn from_evars(name: impl Into<String>, evars: impl IntoIterator<Item = Evar>) -> Self {
         Self {
@@ -468,40 +1154,68 @@ impl Env {
     /// 1. Compress to get single evar per name
     /// 2. Expand tokens using shared token module (with recursion + cycle detection)
     pub fn solve_impl(&self, max_depth: usize, use_os_fallback: bool) -> Result<Env, EnvError> {
+        self.solve_tokens(max_depth, use_os_fallback, false, false)
+    }
+
+    /// Like [`solve_impl`](Self::solve_impl), also expanding shell-style
+    /// `${VAR}` references.
+    ///
+    /// `${VAR}` and `{VAR}` are treated identically; a literal `$${`
+    /// escapes to `${`.
+    pub fn solve_dollar_impl(&self, max_depth: usize, use_os_fallback: bool) -> Result<Env, EnvError> {
+        self.solve_tokens(max_depth, use_os_fallback, false, true)
+    }
+
+    /// Solve in strict token mode.
+    ///
+    /// `{VAR}` only resolves against evars marked
+    /// [`referencable`](Evar::referencable), so internal scratch variables
+    /// don't leak into other values. References to non-referencable evars
+    /// stay literal and never fall back to the OS environment.
+    pub fn solve_strict_impl(&self, max_depth: usize, use_os_fallback: bool) -> Result<Env, EnvError> {
+        self.solve_tokens(max_depth, use_os_fallback, true, false)
+    }
+
+    /// Shared solve for normal and strict token modes.
+    fn solve_tokens(
+        &self,
+        max_depth: usize,
+        use_os_fallback: bool,
+        strict: bool,
+        dollar_syntax: bool,
+    ) -> Result<Env, EnvError> {
         use crate::token;
 
         // First compress to have single value per variable
         let compressed = self.compress();
 
-        // Build lookup map from compressed evars
-        let lookup_map: HashMap<String, String> = compressed
+        // Build lookup map from compressed evars (strict: referencable only)
+        let (exported, private): (Vec<&Evar>, Vec<&Evar>) = compressed
             .evars
+            .iter()
+            .partition(|e| !strict || e.referencable);
+        let lookup_map: HashMap<String, String> = exported
             .iter()
             .map(|e| (e.name.to_lowercase(), e.value.clone()))
             .collect();
+        let private: HashSet<String> = private.iter().map(|e| e.name.to_lowercase()).collect();
 
-        // Solve each evar using token module
+        // Solve each evar; shared sub-references are expanded once per solve
+        let mut expander = token::Expander::new(&lookup_map, max_depth)
+            .with_os_fallback(use_os_fallback)
+            .with_dollar_syntax(dollar_syntax)
+            .with_private(private);
         let mut solved_evars = Vec::new();
         for evar in &compressed.evars {
-            let solved_value = if use_os_fallback {
-                token::expand_with_fallback(&evar.value, &lookup_map, max_depth)
-            } else {
-                token::expand_recursive(&evar.value, &lookup_map, max_depth)
-            }
-            .map_err(|e| match e {
-                token::TokenError::CircularReference { name } => {
-                    EnvError::CircularReference { name }
-                }
-                token::TokenError::DepthExceeded { name, max_depth } => {
-                    EnvError::DepthExceeded { name, max_depth }
-                }
-            })?;
+            let solved_value = expander.expand(&evar.value)?;
 
-            solved_evars.push(Evar::new(
-                evar.name.clone(),
-                solved_value,
-                evar.get_action(),
-            ));
+            // Resolve secret file: references after expansion so paths may use tokens
+            let solved = Evar {
+                value: solved_value,
+                ..evar.clone()
+            }
+            .resolve_file()?;
+            solved_evars.push(solved);
         }
 
         Ok(Env {
@@ -580,6 +1294,114 @@ mod tests {
         assert!(path.value().contains("/c"));
     }
 
+    #[test]
+    fn env_compress_dedup() {
+        let sep = crate::evar::path_sep();
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::append("PATH", "/a"));
+        env.add(Evar::append("PATH", "/b"));
+        env.add(Evar::insert("PATH", "/b", None));
+        env.add(Evar::append("PATH", "/a"));
+        env.add(Evar::set("ROOT", "/opt"));
+
+        assert_eq!(env.compress().get("PATH").unwrap().value(), ["/b", "/a", "/b", "/a"].join(&sep));
+        let deduped = env.compress_dedup();
+        assert_eq!(deduped.get("PATH").unwrap().value(), ["/b", "/a"].join(&sep));
+        assert_eq!(deduped.get("ROOT").unwrap().value(), "/opt");
+    }
+
+    #[test]
+    fn env_unset() {
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("PKG_TEST_UNSET_HOME", "/opt/python"));
+        env.add(Evar::append("PKG_TEST_UNSET_HOME", "/lib"));
+        env.add(Evar::unset("PKG_TEST_UNSET_HOME"));
+
+        let compressed = env.compress();
+        assert!(compressed.get("PKG_TEST_UNSET_HOME").unwrap().is_unset());
+        assert!(!compressed.to_map().contains_key("PKG_TEST_UNSET_HOME"));
+        assert!(compressed.to_sh().contains("unset PKG_TEST_UNSET_HOME"));
+        assert_eq!(compressed.to_cmd(Some(false)), "SET PKG_TEST_UNSET_HOME=");
+        assert!(compressed.to_ps1(Some(false)).contains("Remove-Item Env:PKG_TEST_UNSET_HOME"));
+
+        std::env::set_var("PKG_TEST_UNSET_HOME", "/inherited");
+        compressed.commit();
+        assert!(std::env::var("PKG_TEST_UNSET_HOME").is_err());
+
+        // A later evar starts the variable afresh
+        env.add(Evar::append("PKG_TEST_UNSET_HOME", "/new"));
+        assert_eq!(env.compress().get("PKG_TEST_UNSET_HOME").unwrap().value(), "/new");
+    }
+
+    #[test]
+    fn env_commit_scoped() {
+        std::env::set_var("PKG_TEST_SCOPED", "original");
+        std::env::remove_var("PKG_TEST_SCOPED_NEW");
+
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("PKG_TEST_SCOPED", "override"));
+        env.add(Evar::set("PKG_TEST_SCOPED_NEW", "1"));
+        {
+            let _guard = env.commit_scoped();
+            assert_eq!(std::env::var("PKG_TEST_SCOPED").unwrap(), "override");
+            assert_eq!(std::env::var("PKG_TEST_SCOPED_NEW").unwrap(), "1");
+        }
+        assert_eq!(std::env::var("PKG_TEST_SCOPED").unwrap(), "original");
+        assert!(std::env::var("PKG_TEST_SCOPED_NEW").is_err());
+
+        // Explicit restore, then drop is a no-op
+        let mut guard = env.commit_scoped();
+        guard.restore();
+        assert_eq!(std::env::var("PKG_TEST_SCOPED").unwrap(), "original");
+        std::env::set_var("PKG_TEST_SCOPED", "later");
+        drop(guard);
+        assert_eq!(std::env::var("PKG_TEST_SCOPED").unwrap(), "later");
+    }
+
+    #[test]
+    fn env_diff_os() {
+        let sep = crate::evar::path_sep();
+        std::env::set_var("PKG_TEST_DIFF_APPEND", "/os");
+        std::env::set_var("PKG_TEST_DIFF_INSERT", "/os");
+        std::env::set_var("PKG_TEST_DIFF_SET", "old");
+        std::env::set_var("PKG_TEST_DIFF_SAME", "same");
+        std::env::remove_var("PKG_TEST_DIFF_NEW");
+
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::append("PKG_TEST_DIFF_APPEND", "/a"));
+        env.add(Evar::insert("PKG_TEST_DIFF_INSERT", "/b", None));
+        env.add(Evar::set("PKG_TEST_DIFF_SET", "new"));
+        env.add(Evar::set("PKG_TEST_DIFF_SAME", "same"));
+        env.add(Evar::set("PKG_TEST_DIFF_NEW", "/x"));
+        env.add(Evar::append("PKG_TEST_DIFF_NEW", "/y"));
+
+        assert_eq!(
+            env.diff_os(),
+            vec![
+                ("PKG_TEST_DIFF_APPEND".to_string(), Some("/os".to_string()), format!("/os{sep}/a")),
+                ("PKG_TEST_DIFF_INSERT".to_string(), Some("/os".to_string()), format!("/b{sep}/os")),
+                ("PKG_TEST_DIFF_SET".to_string(), Some("old".to_string()), "new".to_string()),
+                ("PKG_TEST_DIFF_NEW".to_string(), None, format!("/x{sep}/y")),
+            ]
+        );
+        // Only a preview: the process environment is untouched
+        assert_eq!(std::env::var("PKG_TEST_DIFF_SET").unwrap(), "old");
+    }
+
+    #[test]
+    fn env_compress_separator() {
+        for sep in [";", ":"] {
+            let mut env = Env::new("test".to_string());
+            env.add(Evar::append("PATH", "/a").with_separator(sep));
+            env.add(Evar::append("PATH", "/b").with_separator(sep));
+            env.add(Evar::append("PATH", "/c").with_separator(sep));
+
+            let path = env.compress().get("PATH").unwrap();
+            assert_eq!(path.value(), ["/a", "/b", "/c"].join(sep));
+            assert_eq!(path.separator.as_deref(), Some(sep));
+        }
+    }
+
     #[test]
     fn env_solve_simple() {
         let mut env = Env::new("test".to_string());
@@ -590,6 +1412,18 @@ mod tests {
         assert_eq!(solved.get("PATH").unwrap().value(), "/opt/maya/bin");
     }
 
+    #[test]
+    fn env_solve_dollar_syntax() {
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("ROOT", "/opt/maya"));
+        env.add(Evar::set("PATH", "${ROOT}/bin:{ROOT}/lib"));
+        env.add(Evar::set("PROMPT", "$${ROOT}"));
+
+        let solved = env.solve_dollar_impl(10, false).unwrap();
+        assert_eq!(solved.get("PATH").unwrap().value(), "/opt/maya/bin:/opt/maya/lib");
+        assert_eq!(solved.get("PROMPT").unwrap().value(), "${ROOT}");
+    }
+
     #[test]
     fn env_solve_chain() {
         let mut env = Env::new("test".to_string());
@@ -647,7 +1481,7 @@ mod tests {
         env.add(Evar::set("PATH", "C:\\bin"));
         env.add(Evar::set("ROOT", "C:\\opt"));
 
-        let cmd = env.to_cmd();
+        let cmd = env.to_cmd(None);
         assert!(cmd.contains("SET PATH=C:\\bin"));
         assert!(cmd.contains("SET ROOT=C:\\opt"));
         assert!(cmd.contains("\r\n")); // CRLF for Windows
@@ -659,9 +1493,12 @@ mod tests {
         env.add(Evar::set("PATH", "/bin"));
         env.add(Evar::set("MSG", "hello \"world\""));
 
-        let ps1 = env.to_ps1();
+        env.add(Evar::set("PRICE", "$HOME `x`"));
+
+        let ps1 = env.to_ps1(None);
         assert!(ps1.contains("$env:PATH = \"/bin\""));
         assert!(ps1.contains("`\""));  // escaped quote
+        assert!(ps1.contains("= \"`$HOME ``x``\""));
     }
 
     #[test]
@@ -669,10 +1506,60 @@ mod tests {
         let mut env = Env::new("test".to_string());
         env.add(Evar::set("PATH", "/bin"));
         env.add(Evar::set("MSG", "hello \"world\""));
+        env.add(Evar::set("CMD", "$(rm -rf ~) `id` it's \\"));
+        env.add(Evar::set("BAD;rm -rf ~", "x"));
 
         let sh = env.to_sh();
-        assert!(sh.contains("export PATH=\"/bin\""));
-        assert!(sh.contains("\\\"")); // escaped quote
+        assert!(sh.contains("export PATH='/bin'"));
+        assert!(sh.contains("export MSG='hello \"world\"'"));
+        assert!(sh.contains("export CMD='$(rm -rf ~) `id` it'\\''s \\'"));
+        assert!(!sh.contains("BAD"));
+    }
+
+    #[test]
+    fn env_to_fish() {
+        let sep = crate::evar::path_sep();
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::append("PATH", "/new/bin"));
+        env.add(Evar::prepend("PYTHONPATH", "/new/python"));
+        env.add(Evar::set("LD_LIBRARY_PATH", ["/a", "/b"].join(&sep)));
+        env.add(Evar::set("TITLE", "Maya 2026"));
+        env.add(Evar::set("NOTE", "it's $HOME"));
+        env.add(Evar::unset("PYTHONHOME"));
+
+        let lines: Vec<String> = env.to_fish().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "set -gx PATH $PATH /new/bin",
+                "set -gx PYTHONPATH /new/python $PYTHONPATH",
+                "set -gx LD_LIBRARY_PATH /a /b",
+                "set -gx TITLE 'Maya 2026'",
+                "set -gx NOTE 'it\\'s $HOME'",
+                "set -e PYTHONHOME",
+            ]
+        );
+    }
+
+    #[test]
+    fn env_to_dotenv() {
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("ROOT", "/opt/maya"));
+        env.add(Evar::set("TITLE", "Maya 2026"));
+        env.add(Evar::set("MSG", "say \"hi\" to $USER"));
+        env.add(Evar::set("MOTD", "line1\nline2"));
+        env.add(Evar::unset("PYTHONHOME"));
+
+        let lines: Vec<String> = env.to_dotenv().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "ROOT=/opt/maya",
+                "TITLE=\"Maya 2026\"",
+                "MSG=\"say \\\"hi\\\" to \\$USER\"",
+                "MOTD=\"line1\\nline2\"",
+            ]
+        );
     }
 
     #[test]
@@ -686,4 +1573,231 @@ mod tests {
         assert!(py.contains("os.environ['PATH'] = '/bin'"));
         assert!(py.contains("\\'"));  // escaped single quote
     }
+
+    #[test]
+    fn env_to_activation_sh() {
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("PATH", "/opt/maya/bin"));
+        env.add(Evar::set("MAYA_ROOT", "/opt/maya"));
+
+        let sh = env.to_activation_sh();
+        assert!(sh.contains("_PKG_OLD_PATH=\"${PATH-__PKG_UNSET__}\""));
+        assert!(sh.contains("export PATH='/opt/maya/bin'"));
+        assert!(sh.contains("export MAYA_ROOT='/opt/maya'"));
+
+        // deactivate restores backups after the exports
+        let deactivate = sh.find("deactivate() {").unwrap();
+        assert!(sh.find("export PATH=").unwrap() < deactivate);
+        let body = &sh[deactivate..];
+        assert!(body.contains("export PATH=\"$_PKG_OLD_PATH\""));
+        assert!(body.contains("unset MAYA_ROOT"));
+        assert!(body.contains("unset _PKG_OLD_MAYA_ROOT"));
+        assert!(body.contains("unset -f deactivate"));
+    }
+
+    #[test]
+    fn env_to_activation_ps1() {
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("PATH", "C:\\maya\\bin"));
+
+        let ps1 = env.to_activation_ps1();
+        assert!(ps1.contains("$global:_PKG_OLD_PATH = $env:PATH"));
+        assert!(ps1.contains("$env:PATH = \"C:\\maya\\bin\""));
+        assert!(ps1.contains("function global:deactivate {"));
+        assert!(ps1.contains("$env:PATH = $global:_PKG_OLD_PATH"));
+    }
+
+    #[test]
+    fn env_compare() {
+        let mut base = Env::new("base".to_string());
+        base.add(Evar::set("ROOT", "/opt/maya"));
+        let path = format!("/opt/maya/bin{}/usr/bin", crate::evar::path_sep());
+        base.add(Evar::set("PATH", &path));
+        base.add(Evar::set("OCIO", "/cfg/v1.ocio"));
+
+        // Same values, different shape: split PATH, lowercase name, new order
+        let mut other = Env::new("other".to_string());
+        other.add(Evar::set("ocio", "/cfg/v2.ocio"));
+        other.add(Evar::set("PATH", "/opt/maya/bin"));
+        other.add(Evar::append("PATH", "/usr/bin"));
+        other.add(Evar::set("ROOT", "/opt/maya"));
+
+        let cmp = base.compare(&other);
+        assert!(cmp.only_self.is_empty());
+        assert!(cmp.only_other.is_empty());
+        assert_eq!(
+            cmp.differing,
+            vec![(
+                "OCIO".to_string(),
+                "/cfg/v1.ocio".to_string(),
+                "/cfg/v2.ocio".to_string()
+            )]
+        );
+        assert!(base.compare(&base).is_empty());
+
+        other.add(Evar::set("EXTRA", "1"));
+        let cmp = base.compare(&other);
+        assert_eq!(cmp.only_other, vec!["EXTRA"]);
+        assert!(other.compare(&base).only_self.contains(&"EXTRA".to_string()));
+    }
+
+    #[test]
+    fn env_export_case_normalization() {
+        let mut env = Env::new("test".to_string());
+        env.add(Evar::set("Path", "C:\\bin"));
+        env.add(Evar::set("MayaRoot", "C:\\maya"));
+
+        // Platform default: normalized on Windows only
+        let cmd = env.to_cmd(None);
+        if cfg!(windows) {
+            assert!(cmd.contains("SET PATH=C:\\bin"));
+        } else {
+            assert!(cmd.contains("SET Path=C:\\bin"));
+        }
+
+        // Explicit on/off; unknown names are never touched
+        assert!(env.to_cmd(Some(true)).contains("SET PATH=C:\\bin"));
+        assert!(env.to_cmd(Some(true)).contains("SET MayaRoot="));
+        assert!(env.to_ps1(Some(true)).contains("$env:PATH = "));
+        assert!(env.to_ps1(Some(false)).contains("$env:Path = "));
+    }
+
+    #[test]
+    fn env_secret_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("token");
+        std::fs::write(&secret, "s3cr3t\n").unwrap();
+
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("SECRET_DIR", dir.path().to_string_lossy()));
+        env.add(Evar::set("PKG_TEST_SECRET_TOKEN", "file:{SECRET_DIR}/token").with_secret(true));
+        // Without the secret marker file: is an ordinary value
+        env.add(Evar::set("PLAIN_URL", "file:{SECRET_DIR}/token"));
+
+        let solved = env.solve_impl(10, false).unwrap();
+        let token = solved.get("PKG_TEST_SECRET_TOKEN").unwrap();
+        assert_eq!(token.value(), "s3cr3t");
+        assert!(token.secret);
+        assert_eq!(token.display_value(), crate::evar::SECRET_MASK);
+        assert!(!token.to_string().contains("s3cr3t"));
+        let plain = solved.get("PLAIN_URL").unwrap();
+        assert_eq!(plain.value(), format!("file:{}/token", dir.path().to_string_lossy()));
+        assert!(!plain.secret);
+
+        solved.commit();
+        assert_eq!(std::env::var("PKG_TEST_SECRET_TOKEN").unwrap(), "s3cr3t");
+
+        // Unreadable file is an error
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("TOKEN", "file:/nonexistent/pkg-secret").with_secret(true));
+        assert!(matches!(
+            env.solve_impl(10, false),
+            Err(EnvError::SecretFile { .. })
+        ));
+    }
+
+    #[test]
+    fn env_duplicates() {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::append("PATH", "/a"));
+        env.add(Evar::append("PATH", "/b"));
+        env.add(Evar::insert("path", "/c", None));
+        env.add(Evar::set("ROOT", "/opt/one"));
+        env.add(Evar::set("ROOT", "/opt/two"));
+        env.add(Evar::set("SINGLE", "x"));
+
+        assert_eq!(
+            env.duplicates(),
+            vec![("PATH".to_string(), 3), ("ROOT".to_string(), 2)]
+        );
+
+        // Multiple appends/inserts are fine; double set is suspicious
+        assert_eq!(env.duplicate_sets(), vec![("ROOT".to_string(), 2)]);
+    }
+
+    #[test]
+    fn env_solve_strict() {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("MAYA_ROOT", "/opt/maya").with_referencable(true));
+        env.add(Evar::set("SCRATCH", "/tmp/build"));
+        env.add(Evar::set("BIN", "{MAYA_ROOT}/bin"));
+        env.add(Evar::set("OUT", "{SCRATCH}/out"));
+
+        let strict = env.solve_strict_impl(10, true).unwrap();
+        assert_eq!(strict.get("BIN").unwrap().value(), "/opt/maya/bin");
+        assert_eq!(strict.get("OUT").unwrap().value(), "{SCRATCH}/out");
+        assert!(strict.get("MAYA_ROOT").unwrap().referencable);
+
+        // Default mode resolves everything
+        let loose = env.solve_impl(10, false).unwrap();
+        assert_eq!(loose.get("OUT").unwrap().value(), "/tmp/build/out");
+    }
+
+    #[test]
+    fn env_solve_memoized() {
+        use crate::token;
+
+        // Wide env: many variables share a two-level base
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("ROOT", "/opt/maya"));
+        env.add(Evar::set("BASE", "{ROOT}/2026"));
+        for i in 0..50 {
+            env.add(Evar::set(format!("VAR_{i}"), format!("{{BASE}}/lib{i}:{{ROOT}}")));
+        }
+        env.add(Evar::set("SELF", "{SELF}/x"));
+        let solved = env.solve_impl(10, false);
+        assert!(matches!(solved, Err(EnvError::CircularReference { .. })));
+        env.evars.pop();
+
+        let lookup: HashMap<String, String> = env
+            .evars
+            .iter()
+            .map(|e| (e.name.to_lowercase(), e.value.clone()))
+            .collect();
+
+        // Identical to per-variable expansion
+        let solved = env.solve_impl(10, false).unwrap();
+        for evar in &env.evars {
+            let expected = token::expand_recursive(&evar.value, &lookup, 10, false).unwrap();
+            assert_eq!(solved.get(&evar.name).unwrap().value(), expected);
+        }
+        assert_eq!(solved.get("VAR_7").unwrap().value(), "/opt/maya/2026/lib7:/opt/maya");
+
+        // Each referenced variable is expanded once: ROOT and BASE
+        let mut expander = token::Expander::new(&lookup, 10);
+        for evar in &env.evars {
+            expander.expand(&evar.value).unwrap();
+        }
+        assert_eq!(expander.expansions(), 2);
+
+        // Depth limit still applies to cached expansions
+        let mut shallow = token::Expander::new(&lookup, 2);
+        assert!(shallow.expand("{BASE}").is_ok());
+        assert!(matches!(
+            shallow.expand("{VAR_0}"),
+            Err(token::TokenError::DepthExceeded { .. })
+        ));
+        assert_eq!(
+            token::expand_recursive("{VAR_0}", &lookup, 2, false).is_err(),
+            shallow.expand("{VAR_0}").is_err()
+        );
+    }
+
+    #[test]
+    fn env_commit_filtered() {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("PKG_TEST_BLACKLIST_OK", "1"));
+        env.add(Evar::set("PKG_TEST_BLACKLIST_PRELOAD", "/tmp/evil.so"));
+        env.add(Evar::append("pkg_test_blacklist_preload", "/tmp/more.so"));
+
+        let blacklist = vec!["PKG_TEST_BLACKLIST_PRELOAD".to_string()];
+        let skipped = env.commit_filtered(&blacklist);
+        assert_eq!(skipped, vec!["PKG_TEST_BLACKLIST_PRELOAD", "pkg_test_blacklist_preload"]);
+        assert_eq!(std::env::var("PKG_TEST_BLACKLIST_OK").unwrap(), "1");
+        assert!(std::env::var("PKG_TEST_BLACKLIST_PRELOAD").is_err());
+
+        let (kept, _) = env.without_names(&blacklist);
+        assert_eq!(kept.names(), vec!["PKG_TEST_BLACKLIST_OK"]);
+        assert!(!kept.to_sh().contains("evil"));
+    }
 }
@@ -12,6 +12,7 @@
 //! - [`SolverError`] - Errors from dependency resolution
 //! - [`StorageError`] - Errors from package scanning/loading
 //! - [`LoaderError`] - Errors from package.py execution
+//! - [`AppError`] - Errors from App launching
 //!
 //! # Usage
 //!
@@ -51,6 +52,10 @@ pub enum PkgError {
     #[error("loader error: {0}")]
     Loader(#[from] LoaderError),
 
+    /// Error from application launching
+    #[error("app error: {0}")]
+    App(#[from] AppError),
+
     /// IO error (file operations)
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
@@ -83,12 +88,23 @@ pub enum EvarError {
         name: String,
     },
 
-    /// Invalid action string (must be "set", "append", or "insert")
-    #[error("invalid action '{action}', expected: set, append, insert")]
+    /// Invalid action string (must be "set", "append", "insert", "prepend", or "unset")
+    #[error("invalid action '{action}', expected: set, append, insert, prepend, unset")]
     InvalidAction {
         /// The invalid action string
         action: String,
     },
+
+    /// `file:` value references a file that can't be read
+    #[error("cannot read secret file for '{name}': {path}: {reason}")]
+    SecretFile {
+        /// Variable name
+        name: String,
+        /// Referenced file path
+        path: String,
+        /// IO error message
+        reason: String,
+    },
 }
 
 /// Errors from [`Env`](crate::Env) operations.
@@ -118,6 +134,45 @@ pub enum EnvError {
         /// Missing variable name
         name: String,
     },
+
+    /// `file:` value references a file that can't be read (from EvarError)
+    #[error("cannot read secret file for '{name}': {path}: {reason}")]
+    SecretFile {
+        /// Variable name
+        name: String,
+        /// Referenced file path
+        path: String,
+        /// IO error message
+        reason: String,
+    },
+
+    /// Invalid evar action (from EvarError)
+    #[error("invalid action '{action}', expected: set, append, insert, prepend, unset")]
+    InvalidAction {
+        /// The invalid action string
+        action: String,
+    },
+}
+
+impl From<EvarError> for EnvError {
+    fn from(err: EvarError) -> Self {
+        match err {
+            EvarError::DepthExceeded { name, max_depth } => EnvError::DepthExceeded { name, max_depth },
+            EvarError::CircularReference { name } => EnvError::CircularReference { name },
+            EvarError::InvalidAction { action } => EnvError::InvalidAction { action },
+            EvarError::SecretFile { name, path, reason } => EnvError::SecretFile { name, path, reason },
+        }
+    }
+}
+
+impl From<crate::token::TokenError> for EnvError {
+    fn from(err: crate::token::TokenError) -> Self {
+        use crate::token::TokenError;
+        match err {
+            TokenError::CircularReference { name } => EnvError::CircularReference { name },
+            TokenError::DepthExceeded { name, max_depth } => EnvError::DepthExceeded { name, max_depth },
+        }
+    }
 }
 
 /// Errors from [`Package`](crate::Package) operations.
@@ -155,6 +210,15 @@ pub enum PackageError {
         reason: String,
     },
 
+    /// Requested extra group not defined in package.extras
+    #[error("package {package} has no extra '{extra}'")]
+    ExtraNotFound {
+        /// Package name
+        package: String,
+        /// Missing extra name
+        extra: String,
+    },
+
     /// Dependencies not yet solved (call solve_deps first)
     #[error("dependencies not solved for package: {name}")]
     DepsNotSolved {
@@ -222,6 +286,10 @@ pub enum SolverError {
         message: String,
     },
 
+    /// Two incompatible constraints on one package, with who introduced each
+    #[error("{0}")]
+    VersionConflict(Box<ConflictExplanation>),
+
     /// Dependency chain too deep
     #[error("dependency depth exceeded: max {max}, got {actual}")]
     DepthExceeded {
@@ -253,6 +321,107 @@ pub enum SolverError {
         /// Missing version
         version: String,
     },
+
+    /// Solution exceeds the package cap
+    #[error("too many packages: solution exceeds limit of {limit}")]
+    TooManyPackages {
+        /// Maximum allowed number of resolved packages
+        limit: usize,
+    },
+
+    /// Pinned resolution violates a package requirement
+    #[error("{package} requires {requirement}, but pinned set has {pinned}")]
+    PinViolation {
+        /// Pinned package declaring the requirement
+        package: String,
+        /// Violated requirement
+        requirement: String,
+        /// What the pinned set provides instead (e.g. "ocio-2.0.0" or "no ocio")
+        pinned: String,
+    },
+
+    /// Required dependency's base is outside the allowed set
+    #[error("forbidden dependency: {base} is not in the allowed bases")]
+    ForbiddenDependency {
+        /// Disallowed base name
+        base: String,
+    },
+
+    /// Lockfile pin no longer valid against the current packages
+    #[error("lock pin {pin} drifted: {reason}")]
+    LockDrift {
+        /// Pin as `base-version` (or just `base` if missing from the lock)
+        pin: String,
+        /// What changed
+        reason: String,
+    },
+
+    /// Resolution gave up after the configured number of provider calls
+    #[error("resolution budget exhausted after {steps} steps")]
+    Budget {
+        /// Step limit that was hit
+        steps: usize,
+    },
+
+    /// Solve interrupted via [`CancelToken`](crate::cancel::CancelToken)
+    #[error("solve cancelled")]
+    Cancelled,
+}
+
+/// Structured explanation of a [`SolverError::VersionConflict`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictExplanation {
+    /// Package the constraints disagree on
+    pub package: String,
+    /// First constraint and its requirer chain
+    pub left: ConflictSide,
+    /// Second constraint and its requirer chain
+    pub right: ConflictSide,
+    /// Full PubGrub report when it explains more than the two constraints
+    /// (other requirements or missing versions); empty otherwise
+    pub report: String,
+}
+
+/// One side of a [`ConflictExplanation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictSide {
+    /// Requirers from the root down; the last one declares `constraint`.
+    /// Empty when the constraint comes from the request itself.
+    pub chain: Vec<String>,
+    /// Constraint on the conflicting package (e.g. "ocio@>=3.0.0")
+    pub constraint: String,
+}
+
+impl ConflictSide {
+    /// Package declaring the constraint (or "the request").
+    pub fn requirer(&self) -> &str {
+        self.chain.last().map_or("the request", String::as_str)
+    }
+}
+
+impl std::fmt::Display for ConflictExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "conflicting requirements on {}:", self.package)?;
+        for side in [&self.left, &self.right] {
+            write!(f, "  {} requires {}", side.requirer(), side.constraint)?;
+            if side.chain.len() > 1 {
+                write!(f, " (via {})", side.chain.join(" -> "))?;
+            }
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "because {} requires {} and {} requires {}",
+            self.left.requirer(),
+            self.left.constraint,
+            self.right.requirer(),
+            self.right.constraint
+        )?;
+        if !self.report.is_empty() {
+            write!(f, "\n\nfull report:\n{}", self.report)?;
+        }
+        Ok(())
+    }
 }
 
 /// Errors from [`Storage`](crate::Storage) operations.
@@ -303,6 +472,28 @@ pub enum StorageError {
         reason: String,
     },
 
+    /// Scan interrupted via [`CancelToken`](crate::cancel::CancelToken)
+    #[error("scan cancelled")]
+    Cancelled,
+
+    /// Failed to set up a file watch
+    #[error("watch failed for {}: {reason}", path.display())]
+    WatchFailed {
+        /// Watched location
+        path: PathBuf,
+        /// Failure reason
+        reason: String,
+    },
+
+    /// Failed to fetch a remote repository manifest
+    #[error("remote repository {url}: {reason}")]
+    RemoteFailed {
+        /// Manifest URL
+        url: String,
+        /// Failure reason
+        reason: String,
+    },
+
     /// IO error during scanning
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
@@ -338,6 +529,35 @@ pub enum LoaderError {
         reason: String,
     },
 
+    /// Python exception raised by package.py, with the line it was raised
+    /// on when the traceback reaches into the file
+    #[error(
+        "{}{}: {message}{}",
+        path.display(),
+        line.map(|l| format!(":{}", l)).unwrap_or_default(),
+        traceback.as_ref().map(|t| format!("\n{}", t.trim_end())).unwrap_or_default()
+    )]
+    Execution {
+        /// Path to package.py
+        path: PathBuf,
+        /// 1-based line in package.py
+        line: Option<usize>,
+        /// Exception type and message (e.g. `ValueError: bad root`)
+        message: String,
+        /// Full Python traceback when the exception was raised outside
+        /// package.py (helper modules, imports); None when `line` says it all
+        traceback: Option<String>,
+    },
+
+    /// Malformed `package.toml`
+    #[error("invalid {}: {reason}", path.display())]
+    InvalidToml {
+        /// Path to package.toml
+        path: PathBuf,
+        /// Parse error
+        reason: String,
+    },
+
     /// Python execution error (legacy)
     #[error("python error in {}: {message}", path.display())]
     PythonError {
@@ -404,6 +624,37 @@ pub enum LoaderError {
     Io(#[from] std::io::Error),
 }
 
+/// Errors from [`App`](crate::App) operations.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// Argument references a token not present in the launch env
+    #[error("app '{app}': unresolved token '{{{token}}}' in argument '{arg}'")]
+    UnresolvedToken {
+        /// Application name
+        app: String,
+        /// Argument as written
+        arg: String,
+        /// Token name (without braces)
+        token: String,
+    },
+
+    /// App has no executable path to launch
+    #[error("app '{app}': no executable path defined")]
+    NoPath {
+        /// Application name
+        app: String,
+    },
+
+    /// Spawning the executable failed
+    #[error("app '{app}': failed to launch: {reason}")]
+    LaunchFailed {
+        /// Application name
+        app: String,
+        /// OS error message
+        reason: String,
+    },
+}
+
 /// Result type alias using PkgError
 pub type Result<T> = std::result::Result<T, PkgError>;
 
@@ -458,6 +709,15 @@ impl From<LoaderError> for PyErr {
     }
 }
 
+impl From<AppError> for PyErr {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::LaunchFailed { .. } => pyo3::exceptions::PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
 // ============================================================================
 // Helper trait for external error types (orphan rule workaround)
 // ============================================================================
@@ -491,7 +751,7 @@ mod tests {
         };
         assert_eq!(
             err.to_string(),
-            "invalid action 'invalid', expected: set, append, insert"
+            "invalid action 'invalid', expected: set, append, insert, prepend, unset"
         );
     }
 
@@ -503,4 +763,25 @@ mod tests {
         let pkg_err: PkgError = evar_err.into();
         assert!(matches!(pkg_err, PkgError::Evar(_)));
     }
+
+    #[test]
+    fn error_conflict_report() {
+        let side = |requirer: &str, constraint: &str| ConflictSide {
+            chain: vec![requirer.to_string()],
+            constraint: constraint.to_string(),
+        };
+        let mut explanation = ConflictExplanation {
+            package: "ocio".to_string(),
+            left: side("a-1.0.0", "ocio@>=3"),
+            right: side("b-1.0.0", "ocio@<3"),
+            report: String::new(),
+        };
+        let short = explanation.to_string();
+        assert!(short.ends_with("because a-1.0.0 requires ocio@>=3 and b-1.0.0 requires ocio@<3"));
+
+        explanation.report = "Because c-1.0.0 depends on ocio@>=4 ...".to_string();
+        let full = explanation.to_string();
+        assert!(full.starts_with(&short));
+        assert!(full.ends_with("Because c-1.0.0 depends on ocio@>=4 ..."));
+    }
 }
@@ -15,7 +15,28 @@
 //!
 //! - **Set**: Replace the variable value entirely
 //! - **Append**: Add to the end of existing value (with path separator)
-//! - **Insert**: Add to the beginning of existing value (with path separator)
+//! - **Prepend**: Add to the beginning of existing value (with path separator)
+//! - **Insert**: Add at `position` in the existing list (beginning if unset)
+//! - **Unset**: Remove the variable from the environment
+//!
+//! # Action Algebra
+//!
+//! A sequence of evars for one variable is folded left to right, starting
+//! from the first value (see [`Evar::normalize_sequence`]):
+//!
+//! - **Set** discards everything accumulated so far
+//! - **Append** adds after the accumulated value
+//! - **Prepend** adds before the accumulated value
+//! - **Insert** adds at its `position` among the accumulated entries
+//!   (split on the separator, clamped to the end); without a position it
+//!   behaves like Prepend
+//! - **Unset** discards everything accumulated so far and removes the
+//!   variable; a later evar starts it afresh
+//!
+//! The first evar's action only matters when it stands alone; once two or
+//! more are folded the result is a concrete `Set`. So `A(append), B(insert),
+//! C(append)` yields `B:A:C`, `A, B(set), C(insert)` yields `C:B`, and
+//! `A, B(append), C(append), D(insert at 1)` yields `A:D:B:C`.
 //!
 //! # Token Expansion
 //!
@@ -86,17 +107,26 @@ pub enum Action {
     #[default]
     Append,
 
-    /// Insert new value before existing value.
+    /// Insert new value at the evar's `position` in the existing list,
+    /// or before it when no position is given.
     /// Uses OS path separator.
-    /// Example: existing="A", new="B" -> "B:A"
+    /// Example: existing="A:C", new="B" at 1 -> "A:B:C"
     Insert,
+
+    /// Prepend new value to existing value (always index 0).
+    /// Uses OS path separator.
+    /// Example: existing="A", new="B" -> "B:A"
+    Prepend,
+
+    /// Remove the variable; the value is ignored.
+    Unset,
 }
 
 impl Action {
     /// Parse action from string.
     ///
     /// # Arguments
-    /// * `s` - One of: "set", "append", "insert" (case-insensitive)
+    /// * `s` - One of: "set", "append", "insert", "prepend", "unset" (case-insensitive)
     ///
     /// # Errors
     /// Returns [`EvarError::InvalidAction`] if string is not recognized.
@@ -105,6 +135,8 @@ impl Action {
             "set" => Ok(Action::Set),
             "append" => Ok(Action::Append),
             "insert" => Ok(Action::Insert),
+            "prepend" => Ok(Action::Prepend),
+            "unset" => Ok(Action::Unset),
             _ => Err(EvarError::InvalidAction {
                 action: s.to_string(),
             }),
@@ -117,6 +149,8 @@ impl Action {
             Action::Set => "set",
             Action::Append => "append",
             Action::Insert => "insert",
+            Action::Prepend => "prepend",
+            Action::Unset => "unset",
         }
     }
 }
@@ -139,6 +173,14 @@ impl fmt::Display for Action {
 /// - `name`: Variable name (e.g., "PATH", "PYTHONPATH")
 /// - `value`: Variable value, may contain `{TOKENS}` for expansion
 /// - `action`: How this value merges with existing values
+/// - `secret`: Mask the value in display output (repr, `pkg env`, GUI)
+/// - `referencable`: May be referenced as `{NAME}` in strict solve mode
+///
+/// # Secret Files
+///
+/// On an evar marked `secret`, a value of the form `file:/path/to/secret`
+/// is replaced by the file's trimmed contents when the env is solved.
+/// Without the marker `file:` values are left alone and nothing is read.
 ///
 /// # Serialization
 ///
@@ -160,8 +202,35 @@ pub struct Evar {
     /// Action for merging with existing values
     #[serde(default)]
     action: Action,
+
+    /// Mask value in display output (export formats keep the real value)
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+
+    /// Exported for `{NAME}` references in strict solve mode
+    /// (see [`Env::solve_strict_impl`](crate::Env::solve_strict_impl))
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub referencable: bool,
+
+    /// List index for [`Action::Insert`] (None = front, like Prepend)
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+
+    /// List separator for append/prepend/insert (None = [`path_sep`])
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
 }
 
+/// Value prefix referencing a file whose contents become the value.
+pub const FILE_SCHEME: &str = "file:";
+
+/// Placeholder shown instead of secret values.
+pub const SECRET_MASK: &str = "********";
+
 #[pymethods]
 impl Evar {
     /// Create a new environment variable.
@@ -169,24 +238,44 @@ impl Evar {
     /// # Arguments
     /// * `name` - Variable name
     /// * `value` - Variable value (may contain {TOKENS})
-    /// * `action` - Optional merge action: "set", "append", "insert" (default: "append")
+    /// * `action` - Optional merge action: "set", "append", "insert", "prepend" (default: "append")
+    /// * `position` - List index for "insert" (default: front)
+    /// * `separator` - List separator (default: OS path separator)
     ///
     /// # Python Example
     /// ```python
     /// e = Evar("PATH", "/opt/bin")  # default append
     /// e = Evar("ROOT", "/opt", action="set")
+    /// e = Evar("PATH", "/opt/tools", action="insert", position=2)
+    /// e = Evar("MAYA_SCRIPT_PATH", "/opt/scripts", separator=";")
     /// ```
     #[new]
-    #[pyo3(signature = (name, value, action = None))]
-    pub fn py_new(name: String, value: String, action: Option<&str>) -> PyResult<Self> {
+    #[pyo3(signature = (name, value, action = None, secret = false, referencable = false, position = None, separator = None))]
+    pub fn py_new(
+        name: String,
+        value: String,
+        action: Option<&str>,
+        secret: bool,
+        referencable: bool,
+        position: Option<usize>,
+        separator: Option<String>,
+    ) -> PyResult<Self> {
         let action = match action {
             Some(s) => Action::from_str(s)?,
             None => Action::Append,
         };
-        Ok(Self { name, value, action })
+        Ok(Self {
+            name,
+            value,
+            action,
+            secret,
+            referencable,
+            position,
+            separator,
+        })
     }
 
-    /// Get action as string ("set", "append", "insert")
+    /// Get action as string ("set", "append", "insert", "prepend")
     #[getter]
     pub fn action(&self) -> &str {
         self.action.as_str()
@@ -209,6 +298,10 @@ impl Evar {
         dict.set_item("name", &self.name)?;
         dict.set_item("value", &self.value)?;
         dict.set_item("action", self.action.as_str())?;
+        dict.set_item("secret", self.secret)?;
+        dict.set_item("referencable", self.referencable)?;
+        dict.set_item("position", self.position)?;
+        dict.set_item("separator", &self.separator)?;
         Ok(dict.into())
     }
 
@@ -230,7 +323,31 @@ impl Evar {
             Some(a) => Action::from_str(a.extract::<String>()?.as_str())?,
             None => Action::Append,
         };
-        Ok(Self { name, value, action })
+        let secret = match dict.get_item("secret")? {
+            Some(v) => v.extract()?,
+            None => false,
+        };
+        let referencable = match dict.get_item("referencable")? {
+            Some(v) => v.extract()?,
+            None => false,
+        };
+        let position = match dict.get_item("position")? {
+            Some(v) => v.extract()?,
+            None => None,
+        };
+        let separator = match dict.get_item("separator")? {
+            Some(v) => v.extract()?,
+            None => None,
+        };
+        Ok(Self {
+            name,
+            value,
+            action,
+            secret,
+            referencable,
+            position,
+            separator,
+        })
     }
 
     /// Serialize to JSON string.
@@ -249,8 +366,11 @@ impl Evar {
     /// String representation for Python
     fn __repr__(&self) -> String {
         format!(
-            "Evar({:?}, {:?}, action={:?})",
-            self.name, self.value, self.action.as_str()
+            "Evar({:?}, {:?}, action={:?}{})",
+            self.name,
+            self.display_value(),
+            self.action.as_str(),
+            if self.secret { ", secret=True" } else { "" }
         )
     }
 
@@ -281,9 +401,37 @@ impl Evar {
             name: name.into(),
             value: value.into(),
             action,
+            secret: false,
+            referencable: false,
+            position: None,
+            separator: None,
         }
     }
 
+    /// Use `sep` to join list values instead of the OS path separator
+    /// (builder style).
+    pub fn with_separator(mut self, sep: impl Into<String>) -> Self {
+        self.separator = Some(sep.into());
+        self
+    }
+
+    /// Separator used when combining values: the explicit one, or [`path_sep`].
+    pub fn list_separator(&self) -> String {
+        self.separator.clone().unwrap_or_else(path_sep)
+    }
+
+    /// Mark this evar as secret (builder style).
+    pub fn with_secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Mark this evar as referencable in strict mode (builder style).
+    pub fn with_referencable(mut self, referencable: bool) -> Self {
+        self.referencable = referencable;
+        self
+    }
+
     /// Create an Evar with Set action.
     pub fn set(name: impl Into<String>, value: impl Into<String>) -> Self {
         Self::new(name, value, Action::Set)
@@ -294,9 +442,27 @@ impl Evar {
         Self::new(name, value, Action::Append)
     }
 
-    /// Create an Evar with Insert action.
-    pub fn insert(name: impl Into<String>, value: impl Into<String>) -> Self {
-        Self::new(name, value, Action::Insert)
+    /// Create an Evar with Insert action at `position` (None = front).
+    pub fn insert(name: impl Into<String>, value: impl Into<String>, position: Option<usize>) -> Self {
+        Self {
+            position,
+            ..Self::new(name, value, Action::Insert)
+        }
+    }
+
+    /// Create an Evar with Prepend action.
+    pub fn prepend(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::new(name, value, Action::Prepend)
+    }
+
+    /// Create an Evar that removes `name` from the environment.
+    pub fn unset(name: impl Into<String>) -> Self {
+        Self::new(name, "", Action::Unset)
+    }
+
+    /// True if this evar removes its variable.
+    pub fn is_unset(&self) -> bool {
+        self.action == Action::Unset
     }
 
     /// Get the action.
@@ -309,6 +475,39 @@ impl Evar {
         &self.value
     }
 
+    /// Value for display: [`SECRET_MASK`] if secret, else the real value.
+    pub fn display_value(&self) -> &str {
+        if self.secret {
+            SECRET_MASK
+        } else {
+            &self.value
+        }
+    }
+
+    /// Resolve a secret `file:` value to the referenced file's trimmed
+    /// contents.
+    ///
+    /// Only evars marked [`secret`](Self::secret) are resolved; others and
+    /// values without the scheme are returned unchanged.
+    ///
+    /// # Errors
+    /// Returns `EvarError::SecretFile` if the file can't be read.
+    pub fn resolve_file(&self) -> Result<Evar, EvarError> {
+        let Some(path) = self.value.strip_prefix(FILE_SCHEME).filter(|_| self.secret) else {
+            return Ok(self.clone());
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| EvarError::SecretFile {
+            name: self.name.clone(),
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+        Ok(Evar {
+            value: contents.trim().to_string(),
+            secret: true,
+            ..self.clone()
+        })
+    }
+
     /// Merge another Evar into this one.
     ///
     /// Both Evars must have the same name. The `other` Evar's action
@@ -316,7 +515,11 @@ impl Evar {
     ///
     /// - Set: other.value replaces self.value
     /// - Append: self.value + separator + other.value
-    /// - Insert: other.value + separator + self.value
+    /// - Prepend: other.value + separator + self.value
+    /// - Insert: other.value at other.position among self's entries
+    /// - Unset: empty value, result stays Unset
+    ///
+    /// The separator is other's, then self's, then [`path_sep`].
     ///
     /// # Panics
     /// Panics if names don't match. Use `try_merge` for fallible version.
@@ -329,34 +532,67 @@ impl Evar {
             other.name
         );
 
+        let separator = other.separator.clone().or_else(|| self.separator.clone());
         let new_value = match other.action {
             Action::Set => other.value.clone(),
-            Action::Append => {
-                if self.value.is_empty() {
-                    other.value.clone()
-                } else if other.value.is_empty() {
-                    self.value.clone()
-                } else {
-                    format!("{}{}{}", self.value, path_sep(), other.value)
-                }
-            }
-            Action::Insert => {
-                if self.value.is_empty() {
-                    other.value.clone()
-                } else if other.value.is_empty() {
-                    self.value.clone()
-                } else {
-                    format!("{}{}{}", other.value, path_sep(), self.value)
-                }
-            }
+            Action::Unset => String::new(),
+            _ => other.apply_to(&self.value, separator.as_deref().unwrap_or(&path_sep())),
         };
 
         Evar {
             name: self.name.clone(),
             value: new_value,
-            // After merge, action becomes Set (value is now concrete)
-            action: Action::Set,
+            // After merge, action becomes Set (value is now concrete),
+            // unless the variable ends up removed
+            action: if other.is_unset() { Action::Unset } else { Action::Set },
+            secret: self.secret || other.secret,
+            referencable: self.referencable || other.referencable,
+            position: None,
+            separator,
+        }
+    }
+
+    /// Combine this evar's value with `current` according to its action,
+    /// joining list entries with `sep`.
+    fn apply_to(&self, current: &str, sep: &str) -> String {
+        if current.is_empty() {
+            return self.value.clone();
+        }
+        if self.value.is_empty() {
+            return current.to_string();
         }
+        match (self.action, self.position) {
+            (Action::Set, _) => self.value.clone(),
+            (Action::Unset, _) => String::new(),
+            (Action::Append, _) => format!("{}{}{}", current, sep, self.value),
+            (Action::Prepend, _) | (Action::Insert, None) => {
+                format!("{}{}{}", self.value, sep, current)
+            }
+            (Action::Insert, Some(position)) => {
+                let mut entries: Vec<&str> = current.split(sep).collect();
+                entries.insert(position.min(entries.len()), &self.value);
+                entries.join(sep)
+            }
+        }
+    }
+
+    /// Collapse a sequence of evars for one variable into a single evar.
+    ///
+    /// Folds left to right with [`merge`](Self::merge), following the action
+    /// algebra from the module docs: sets reset, appends/prepends/inserts build around
+    /// the accumulated value. The result has action `Set` with the canonical
+    /// final value. A single evar is returned unchanged so it still applies
+    /// relative to the process environment on commit.
+    ///
+    /// # Panics
+    /// Panics if `evars` is empty, or if names differ other than in case
+    /// (see [`merge`](Self::merge)); [`Env::compress`](crate::env::Env::compress)
+    /// only passes non-empty groups of one variable.
+    pub fn normalize_sequence(evars: &[Evar]) -> Evar {
+        let (first, rest) = evars
+            .split_first()
+            .expect("normalize_sequence requires at least one evar");
+        rest.iter().fold(first.clone(), |acc, evar| acc.merge(evar))
     }
 
     /// Find all `{TOKEN}` patterns in the value.
@@ -424,9 +660,8 @@ impl Evar {
         visiting.remove(&name_lower);
 
         Ok(Evar {
-            name: self.name.clone(),
             value: solved_value,
-            action: self.action,
+            ..self.clone()
         })
     }
 
@@ -435,37 +670,35 @@ impl Evar {
     /// Uses `std::env::set_var` with action semantics:
     /// - Set: overwrites
     /// - Append: adds to end
-    /// - Insert: adds to beginning
+    /// - Prepend: adds to beginning
+    /// - Insert: adds at `position` (beginning if unset)
+    /// - Unset: removes the variable (`std::env::remove_var`)
     pub fn commit(&self) {
+        match self.apply(std::env::var(&self.name).ok().as_deref()) {
+            Some(value) => std::env::set_var(&self.name, value),
+            None => std::env::remove_var(&self.name),
+        }
+    }
+
+    /// Value this evar produces on top of `current` (None = not set).
+    ///
+    /// Same semantics as [`commit`](Self::commit) without touching the
+    /// process environment.
+    ///
+    /// # Returns
+    /// The new value, or None if the variable ends up unset.
+    pub fn apply(&self, current: Option<&str>) -> Option<String> {
         match self.action {
-            Action::Set => {
-                std::env::set_var(&self.name, &self.value);
-            }
-            Action::Append => {
-                let current = std::env::var(&self.name).unwrap_or_default();
-                let new_value = if current.is_empty() {
-                    self.value.clone()
-                } else {
-                    format!("{}{}{}", current, path_sep(), self.value)
-                };
-                std::env::set_var(&self.name, new_value);
-            }
-            Action::Insert => {
-                let current = std::env::var(&self.name).unwrap_or_default();
-                let new_value = if current.is_empty() {
-                    self.value.clone()
-                } else {
-                    format!("{}{}{}", self.value, path_sep(), current)
-                };
-                std::env::set_var(&self.name, new_value);
-            }
+            Action::Set => Some(self.value.clone()),
+            Action::Unset => None,
+            _ => Some(self.apply_to(current.unwrap_or_default(), &self.list_separator())),
         }
     }
 }
 
 impl fmt::Display for Evar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}={} ({})", self.name, self.value, self.action)
+        write!(f, "{}={} ({})", self.name, self.display_value(), self.action)
     }
 }
 
@@ -478,6 +711,9 @@ mod tests {
         assert_eq!(Action::from_str("set").unwrap(), Action::Set);
         assert_eq!(Action::from_str("APPEND").unwrap(), Action::Append);
         assert_eq!(Action::from_str("Insert").unwrap(), Action::Insert);
+        assert_eq!(Action::from_str("prepend").unwrap(), Action::Prepend);
+        assert_eq!(Action::Prepend.as_str(), "prepend");
+        assert_eq!(Action::from_str("unset").unwrap(), Action::Unset);
         assert!(Action::from_str("invalid").is_err());
     }
 
@@ -516,6 +752,24 @@ mod tests {
         assert!(c.value.starts_with("/b"));
     }
 
+    #[test]
+    fn evar_prepend_and_positional_insert() {
+        let sep = path_sep();
+        let seq = vec![
+            Evar::append("PATH", "/a"),
+            Evar::append("PATH", "/b"),
+            Evar::prepend("PATH", "/p"),
+            Evar::append("PATH", "/c"),
+            Evar::insert("PATH", "/i", Some(2)),
+        ];
+        let folded = Evar::normalize_sequence(&seq);
+        assert_eq!(folded.value, ["/p", "/a", "/i", "/b", "/c"].join(&sep));
+
+        // Out-of-range positions clamp to the end
+        let seq = vec![Evar::set("PATH", "/a"), Evar::insert("PATH", "/z", Some(9))];
+        assert_eq!(Evar::normalize_sequence(&seq).value, ["/a", "/z"].join(&sep));
+    }
+
     #[test]
     fn extract_tokens_basic() {
         let tokens = token::extract("{ROOT}/bin/{LIB}");
@@ -560,4 +814,64 @@ mod tests {
         let e2: Evar = serde_json::from_str(&json).unwrap();
         assert_eq!(e, e2);
     }
+
+    #[test]
+    fn normalize_sequence_single() {
+        let e = Evar::append("PATH", "/a");
+        assert_eq!(Evar::normalize_sequence(std::slice::from_ref(&e)), e);
+    }
+
+    #[test]
+    fn normalize_sequence_all_triples() {
+        use Action::{Append as A, Insert as I, Set as S};
+
+        // (actions for values "a", "b", "c") -> expected value with ':' as separator
+        let table: [([Action; 3], &str); 27] = [
+            ([S, S, S], "c"),
+            ([S, S, A], "b:c"),
+            ([S, S, I], "c:b"),
+            ([S, A, S], "c"),
+            ([S, A, A], "a:b:c"),
+            ([S, A, I], "c:a:b"),
+            ([S, I, S], "c"),
+            ([S, I, A], "b:a:c"),
+            ([S, I, I], "c:b:a"),
+            ([A, S, S], "c"),
+            ([A, S, A], "b:c"),
+            ([A, S, I], "c:b"),
+            ([A, A, S], "c"),
+            ([A, A, A], "a:b:c"),
+            ([A, A, I], "c:a:b"),
+            ([A, I, S], "c"),
+            ([A, I, A], "b:a:c"),
+            ([A, I, I], "c:b:a"),
+            ([I, S, S], "c"),
+            ([I, S, A], "b:c"),
+            ([I, S, I], "c:b"),
+            ([I, A, S], "c"),
+            ([I, A, A], "a:b:c"),
+            ([I, A, I], "c:a:b"),
+            ([I, I, S], "c"),
+            ([I, I, A], "b:a:c"),
+            ([I, I, I], "c:b:a"),
+        ];
+
+        let sep = path_sep();
+        for (actions, expected) in table {
+            let evars: Vec<Evar> = actions
+                .iter()
+                .zip(["a", "b", "c"])
+                .map(|(action, value)| Evar::new("VAR", value, *action))
+                .collect();
+
+            let result = Evar::normalize_sequence(&evars);
+            assert_eq!(
+                result.value(),
+                expected.replace(':', &sep),
+                "actions {:?}",
+                actions
+            );
+            assert_eq!(result.get_action(), Action::Set, "actions {:?}", actions);
+        }
+    }
 }
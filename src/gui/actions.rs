@@ -4,7 +4,7 @@
 
 use eframe::egui::{self, Color32, RichText, Ui};
 use log::{debug, info, warn};
-use crate::{Solver, Storage};
+use crate::{Env, Evar, Solver, Storage};
 use super::state::AppState;
 
 /// Resolved app info.
@@ -23,7 +23,12 @@ pub struct SolveResult {
     pub pkg_name: String,
     pub packages: Vec<String>,
     pub apps: Vec<ResolvedApp>,
+    /// Real values, for launching and export.
     pub env_lines: Vec<(String, String)>,
+    /// Same pairs with secret values masked, for display.
+    pub env_display: Vec<(String, String)>,
+    /// Solved environment (for the Environment tab).
+    pub env: Option<Env>,
     pub error: Option<String>,
 }
 
@@ -205,10 +210,10 @@ pub fn render_solve_inline(ui: &mut Ui, state: &mut super::AppState, result: &mu
                     .id_salt("solve_env_inline")
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        if result.env_lines.is_empty() {
+                        if result.env_display.is_empty() {
                             ui.label(RichText::new("(no env vars)").color(Color32::GRAY));
                         } else {
-                            for (name, value) in &result.env_lines {
+                            for (name, value) in &result.env_display {
                                 let id = egui::Id::new(("env_var", name));
 
                                 // Apply expand/collapse before showing
@@ -238,6 +243,8 @@ fn run_solve(pkg_name: &str, storage: &Storage, result: &mut SolveResult) {
     result.packages.clear();
     result.apps.clear();
     result.env_lines.clear();
+    result.env_display.clear();
+    result.env = None;
     result.error = None;
 
     debug!("[GUI] Running solve for: {}", pkg_name);
@@ -259,33 +266,41 @@ fn run_solve(pkg_name: &str, storage: &Storage, result: &mut SolveResult) {
             info!("[GUI] Solved {}: {} packages", pkg_name, pkgs.len());
             result.packages = pkgs.clone();
             
-            // Collect apps and env from all resolved packages
-            let mut merged_env: std::collections::HashMap<String, String> = std::collections::HashMap::new();
-            
-            for resolved_name in &pkgs {
-                if let Some(pkg) = storage.get(resolved_name) {
-                    // Collect apps
-                    for app in &pkg.apps {
-                        result.apps.push(ResolvedApp {
-                            name: app.name.clone(),
-                            path: app.path.clone(),
-                            from_pkg: pkg.base.clone(),
-                        });
-                    }
-                    
-                    // Merge env
-                    if let Some(env) = pkg.envs.first() {
-                        for evar in &env.evars {
-                            merged_env.insert(evar.name.clone(), evar.value.clone());
-                        }
-                    }
+            // Collect apps from all resolved packages
+            let resolved: Vec<_> = pkgs.iter().filter_map(|name| storage.get(name)).collect();
+            for pkg in &resolved {
+                for app in &pkg.apps {
+                    result.apps.push(ResolvedApp {
+                        name: app.name.clone(),
+                        path: app.path.clone(),
+                        from_pkg: pkg.base.clone(),
+                    });
                 }
             }
-            
-            // Sort env
-            let mut env_vec: Vec<_> = merged_env.into_iter().collect();
-            env_vec.sort_by(|a, b| a.0.cmp(&b.0));
-            result.env_lines = env_vec;
+
+            // Env the same way the CLI builds it: prelude, {PKG_ROOT}, deps, solved
+            let solved = resolved
+                .iter()
+                .find(|p| p.name == pkg_name || p.base == pkg_name)
+                .cloned()
+                .and_then(|mut root| {
+                    root.deps = resolved.iter().filter(|p| p.name != root.name).cloned().collect();
+                    root.default_env()
+                })
+                .unwrap_or_else(|| {
+                    warn!("[GUI] No env for {}", pkg_name);
+                    Env::new("default".to_string())
+                });
+
+            // Sort env; secrets stay masked on screen
+            let mut evars: Vec<&Evar> = solved.evars.iter().collect();
+            evars.sort_by(|a, b| a.name.cmp(&b.name));
+            result.env_lines = evars.iter().map(|e| (e.name.clone(), e.value.clone())).collect();
+            result.env_display = evars
+                .iter()
+                .map(|e| (e.name.clone(), e.display_value().to_string()))
+                .collect();
+            result.env = Some(solved);
             
             debug!("[GUI] Resolved: {} apps, {} env vars", result.apps.len(), result.env_lines.len());
             result.show = true;
@@ -351,7 +366,7 @@ fn export_env(result: &SolveResult, format: ExportFormat) {
         ExportFormat::Ps1 => {
             let mut s = String::new();
             for (k, v) in &result.env_lines {
-                s.push_str(&format!("$env:{} = \"{}\"\n", k, v.replace('"', "`\"")));
+                s.push_str(&format!("$env:{} = \"{}\"\n", k, crate::env::ps1_escape(v)));
             }
             s
         }
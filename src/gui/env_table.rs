@@ -0,0 +1,182 @@
+//! Environment table (right panel).
+//!
+//! Shows the solved environment from the last Solve as a sortable
+//! table: name, action, value. Path-list variables are expanded into
+//! one row per entry.
+
+use eframe::egui::{self, Color32, RichText, Ui};
+use crate::evar::path_sep;
+use crate::Env;
+use super::actions::SolveResult;
+use super::state::{AppState, EnvSortColumn};
+
+/// Values longer than this are truncated (full value in tooltip).
+const MAX_VALUE_CHARS: usize = 80;
+
+/// One display row of the environment table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvRow {
+    /// Variable name.
+    pub name: String,
+    /// Action string ("set", "append", "insert", "prepend", "unset").
+    pub action: String,
+    /// Value (or single path entry), masked if secret.
+    pub value: String,
+    /// Entry index within a path-list variable (0 for plain values).
+    pub index: usize,
+}
+
+/// Path-list variables (PATH, PYTHONPATH, LD_LIBRARY_PATH, ...) are split per entry.
+fn is_path_list(name: &str) -> bool {
+    name.to_uppercase().ends_with("PATH")
+}
+
+/// Flatten an env into display rows, one per path entry for path-list vars.
+pub fn env_rows(env: &Env) -> Vec<EnvRow> {
+    let sep = path_sep();
+    let mut rows = Vec::new();
+    for evar in &env.evars {
+        let action = evar.get_action().as_str().to_string();
+        let value = evar.display_value();
+        if evar.secret || !is_path_list(&evar.name) {
+            rows.push(EnvRow {
+                name: evar.name.clone(),
+                action,
+                value: value.to_string(),
+                index: 0,
+            });
+            continue;
+        }
+        let entries = value.split(sep.as_str()).filter(|p| !p.is_empty());
+        for (index, entry) in entries.enumerate() {
+            rows.push(EnvRow {
+                name: evar.name.clone(),
+                action: action.clone(),
+                value: entry.to_string(),
+                index,
+            });
+        }
+    }
+    rows
+}
+
+/// Sort rows by column. Stable, so path entries keep their order within a variable.
+pub fn sort_rows(rows: &mut [EnvRow], column: EnvSortColumn, descending: bool) {
+    rows.sort_by(|a, b| {
+        let ord = match column {
+            EnvSortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            EnvSortColumn::Action => a.action.cmp(&b.action),
+            EnvSortColumn::Value => a.value.cmp(&b.value),
+        };
+        if descending { ord.reverse() } else { ord }
+    });
+}
+
+/// Truncate to `max` chars with "...", or None if it fits.
+fn truncate(value: &str, max: usize) -> Option<String> {
+    if value.chars().count() <= max {
+        return None;
+    }
+    let head: String = value.chars().take(max.saturating_sub(3)).collect();
+    Some(format!("{}...", head))
+}
+
+/// Render environment table panel.
+pub fn render(ui: &mut Ui, state: &mut AppState, result: &SolveResult) {
+    let Some(env) = result.env.as_ref().filter(|_| result.show && result.error.is_none()) else {
+        ui.centered_and_justified(|ui| {
+            ui.label(RichText::new("Solve a package to see its environment").color(Color32::GRAY));
+        });
+        return;
+    };
+
+    let mut rows = env_rows(env);
+    sort_rows(&mut rows, state.env_sort, state.env_sort_desc);
+
+    ui.horizontal(|ui| {
+        ui.heading(&result.pkg_name);
+        ui.label(RichText::new(format!("{} vars, {} rows", env.evars.len(), rows.len()))
+            .color(Color32::GRAY));
+    });
+    ui.add_space(4.0);
+
+    egui::ScrollArea::both()
+        .id_salt("env_table")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("env_table_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (column, title) in [
+                        (EnvSortColumn::Name, "Name"),
+                        (EnvSortColumn::Action, "Action"),
+                        (EnvSortColumn::Value, "Value"),
+                    ] {
+                        let arrow = match (state.env_sort == column, state.env_sort_desc) {
+                            (true, false) => " ▲",
+                            (true, true) => " ▼",
+                            _ => "",
+                        };
+                        let header = RichText::new(format!("{}{}", title, arrow)).strong();
+                        if ui.selectable_label(state.env_sort == column, header).clicked() {
+                            if state.env_sort == column {
+                                state.env_sort_desc = !state.env_sort_desc;
+                            } else {
+                                state.env_sort = column;
+                                state.env_sort_desc = false;
+                            }
+                        }
+                    }
+                    ui.end_row();
+
+                    for row in &rows {
+                        // Continuation rows of a path list are dimmed
+                        let name_color = if row.index == 0 { Color32::LIGHT_BLUE } else { Color32::GRAY };
+                        ui.label(RichText::new(&row.name).color(name_color));
+                        ui.label(RichText::new(&row.action).color(Color32::GRAY));
+                        match truncate(&row.value, MAX_VALUE_CHARS) {
+                            Some(short) => {
+                                ui.label(short).on_hover_text(&row.value);
+                            }
+                            None => {
+                                ui.label(&row.value);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Evar;
+
+    #[test]
+    fn env_rows_flatten_paths() {
+        let sep = path_sep();
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("MAYA_ROOT", "/opt/maya"));
+        env.add(Evar::append("PATH", format!("/opt/maya/bin{sep}/usr/bin")));
+        env.add(Evar::set("TOKEN", "hunter2").with_secret(true));
+
+        let mut rows = env_rows(&env);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1].value, "/opt/maya/bin");
+        assert_eq!(rows[2].value, "/usr/bin");
+        assert_eq!(rows[2].index, 1);
+        assert_eq!(rows[2].action, "append");
+        assert_eq!(rows[3].value, crate::evar::SECRET_MASK);
+
+        // Name sort is stable: path entries keep order
+        sort_rows(&mut rows, EnvSortColumn::Name, true);
+        let names: Vec<_> = rows.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["TOKEN", "PATH", "PATH", "MAYA_ROOT"]);
+        assert_eq!(rows[1].value, "/opt/maya/bin");
+
+        assert_eq!(truncate("short", 10), None);
+        assert_eq!(truncate("abcdefghijkl", 8).unwrap(), "abcde...");
+    }
+}
@@ -11,6 +11,7 @@ mod tree_editor;
 mod node_graph;
 mod node_layout;
 mod actions;
+mod env_table;
 mod toolset_editor;
 
 pub use state::{AppState, Selection, ViewMode};
@@ -18,6 +19,8 @@ use actions::SolveResult;
 use toolset_editor::ToolsetEditorState;
 
 use eframe::egui;
+use std::sync::mpsc;
+use crate::watch::StorageWatcher;
 use crate::{Storage, toolset};
 
 /// Main GUI application.
@@ -26,6 +29,10 @@ pub struct PkgApp {
     storage: Storage,
     solve_result: SolveResult,
     toolset_editor: ToolsetEditorState,
+    /// Live reload, kept alive with the app; None if watching failed.
+    _watcher: Option<StorageWatcher>,
+    /// Storages rescanned by the watcher, applied in `update`.
+    reloads: mpsc::Receiver<Storage>,
 }
 
 impl PkgApp {
@@ -37,11 +44,28 @@ impl PkgApp {
         // Use dark mode by default
         cc.egui_ctx.set_visuals(egui::Visuals::dark());
 
+        // file:// image loading for package icons
+        egui_extras::install_image_loaders(&cc.egui_ctx);
+
+        // Rescan on package.py / toolset changes and wake the UI
+        let (tx, reloads) = mpsc::channel();
+        let ctx = cc.egui_ctx.clone();
+        let watcher = storage
+            .watch(move |fresh| {
+                if tx.send(fresh).is_ok() {
+                    ctx.request_repaint();
+                }
+            })
+            .inspect_err(|e| log::warn!("[GUI] Live reload disabled: {}", e))
+            .ok();
+
         Self {
             state,
             storage,
             solve_result: SolveResult::default(),
             toolset_editor: ToolsetEditorState::default(),
+            _watcher: watcher,
+            reloads,
         }
     }
 
@@ -130,8 +154,11 @@ impl PkgApp {
     }
     
     /// Refresh storage from disk.
+    ///
+    /// Rescans even while the watcher is live: it can miss changes, e.g. on
+    /// network mounts without change notifications.
     fn refresh_storage(&mut self) {
-        if let Ok(new_storage) = Storage::scan_impl(Some(self.storage.location_paths())) {
+        if let Ok(new_storage) = self.storage.rescan() {
             self.storage = new_storage;
         }
     }
@@ -202,6 +229,12 @@ impl eframe::App for PkgApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Force dark mode (override system theme detection)
         ctx.set_visuals(egui::Visuals::dark());
+
+        // Apply live reloads (keep only the newest)
+        if let Some(fresh) = self.reloads.try_iter().last() {
+            log::info!("[GUI] Package files changed, reloaded {} packages", fresh.count());
+            self.storage = fresh;
+        }
         
         // Exit on Escape
         if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
@@ -227,6 +260,7 @@ impl eframe::App for PkgApp {
                 ui.separator();
                 ui.selectable_value(&mut self.state.right_panel, state::RightPanel::Tree, "Tree");
                 ui.selectable_value(&mut self.state.right_panel, state::RightPanel::Graph, "Graph");
+                ui.selectable_value(&mut self.state.right_panel, state::RightPanel::Environment, "Environment");
             });
         });
 
@@ -265,6 +299,10 @@ impl eframe::App for PkgApp {
                         node_graph::render(ui, &mut self.state, &self.storage);
                         None
                     }
+                    state::RightPanel::Environment => {
+                        env_table::render(ui, &mut self.state, &self.solve_result);
+                        None
+                    }
                 }
             }).inner;
 
@@ -288,9 +326,7 @@ impl eframe::App for PkgApp {
         // Toolset editor window
         if toolset_editor::render(ctx, &mut self.toolset_editor) {
             // Reload storage to pick up new/edited toolset
-            if let Ok(new_storage) = Storage::scan_impl(Some(self.storage.location_paths())) {
-                self.storage = new_storage;
-            }
+            self.refresh_storage();
         }
     }
 }
@@ -5,7 +5,7 @@
 use eframe::egui::{self, Color32, RichText, Ui};
 use log::{info, trace};
 use crate::Storage;
-use super::state::{AppState, ViewMode};
+use super::state::{AppState, KindFilter, ViewMode};
 
 /// Action returned from package list.
 #[derive(Debug, Clone)]
@@ -23,9 +23,18 @@ pub enum ListAction {
 }
 
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::path::Path;
 use crate::Package;
 
+/// Draw the package's icon, if it resolves to a file, at label size.
+fn package_icon(ui: &mut Ui, pkg: &Package) {
+    if let Some(icon) = pkg.resolved_icon() {
+        let uri = format!("file://{}", icon.display());
+        ui.add(egui::Image::new(uri).fit_to_exact_size(egui::vec2(16.0, 16.0)));
+    }
+}
+
 /// Render packages grouped by base name.
 fn render_packages(
     ui: &mut Ui,
@@ -50,26 +59,32 @@ fn render_packages(
         if versions.len() == 1 {
             let pkg = versions[0];
             let selected = state.selection.package.as_ref() == Some(&pkg.name);
-            if ui.selectable_label(selected, &pkg.name).clicked() {
-                info!("[GUI] Selected package: {}", pkg.name);
-                state.selection.package = Some(pkg.name.clone());
-                if let Some(gs) = &mut state.graph_state {
-                    gs.set_package(&pkg.name);
+            ui.horizontal(|ui| {
+                package_icon(ui, pkg);
+                if ui.selectable_label(selected, &pkg.name).clicked() {
+                    info!("[GUI] Selected package: {}", pkg.name);
+                    state.selection.package = Some(pkg.name.clone());
+                    if let Some(gs) = &mut state.graph_state {
+                        gs.set_package(&pkg.name);
+                    }
                 }
-            }
+            });
         } else {
             egui::CollapsingHeader::new(base)
                 .default_open(false)
                 .show(ui, |ui| {
                     for pkg in versions {
                         let selected = state.selection.package.as_ref() == Some(&pkg.name);
-                        if ui.selectable_label(selected, &pkg.version).clicked() {
-                            info!("[GUI] Selected package: {}", pkg.name);
-                            state.selection.package = Some(pkg.name.clone());
-                            if let Some(gs) = &mut state.graph_state {
-                                gs.set_package(&pkg.name);
+                        ui.horizontal(|ui| {
+                            package_icon(ui, pkg);
+                            if ui.selectable_label(selected, &pkg.version).clicked() {
+                                info!("[GUI] Selected package: {}", pkg.name);
+                                state.selection.package = Some(pkg.name.clone());
+                                if let Some(gs) = &mut state.graph_state {
+                                    gs.set_package(&pkg.name);
+                                }
                             }
-                        }
+                        });
                     }
                 });
         }
@@ -198,12 +213,32 @@ pub fn render(ui: &mut Ui, state: &mut AppState, storage: &Storage) -> Option<Li
         }
     });
 
+    // Kind filter (packages only)
+    if state.view_mode == ViewMode::Packages {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut state.kind_filter, KindFilter::All, "All");
+            ui.selectable_value(&mut state.kind_filter, KindFilter::Applications, "Apps");
+            ui.selectable_value(&mut state.kind_filter, KindFilter::Libraries, "Libs");
+        });
+    }
+
     ui.separator();
 
     // Package list
     egui::ScrollArea::vertical().show(ui, |ui| {
         let filter_lower = state.filter.to_lowercase();
 
+        // Allowed names for the kind filter (None = all)
+        let kind_names: Option<HashSet<String>> = match (state.view_mode, state.kind_filter) {
+            (ViewMode::Packages, KindFilter::Applications) => {
+                Some(storage.applications().into_iter().collect())
+            }
+            (ViewMode::Packages, KindFilter::Libraries) => {
+                Some(storage.libraries().into_iter().collect())
+            }
+            _ => None,
+        };
+
         // Get packages, optionally filtered
         let packages: Vec<_> = storage.packages_iter()
             .filter(|pkg| {
@@ -214,6 +249,10 @@ pub fn render(ui: &mut Ui, state: &mut AppState, storage: &Storage) -> Option<Li
                     ViewMode::Toolsets => is_toolset,
                 }
             })
+            .filter(|pkg| {
+                // Filter by kind
+                kind_names.as_ref().is_none_or(|names| names.contains(&pkg.name))
+            })
             .filter(|pkg| {
                 // Filter by search text
                 filter_lower.is_empty() || pkg.name.to_lowercase().contains(&filter_lower)
@@ -27,12 +27,34 @@ pub enum ViewMode {
     Toolsets,
 }
 
+/// Package kind filter (Packages view).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KindFilter {
+    #[default]
+    All,
+    /// Packages providing at least one app.
+    Applications,
+    /// Packages without apps.
+    Libraries,
+}
+
 /// Right panel mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum RightPanel {
     #[default]
     Tree,
     Graph,
+    /// Solved environment table (after Solve).
+    Environment,
+}
+
+/// Environment table sort column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EnvSortColumn {
+    #[default]
+    Name,
+    Action,
+    Value,
 }
 
 /// Current selection.
@@ -51,7 +73,7 @@ pub struct Selection {
 pub struct AppState {
     /// Current view mode (Packages or Toolsets).
     pub view_mode: ViewMode,
-    /// Right panel mode (Tree or Graph).
+    /// Right panel mode (Tree, Graph or Environment).
     pub right_panel: RightPanel,
     /// Current selection.
     pub selection: Selection,
@@ -68,6 +90,15 @@ pub struct AppState {
     pub filter: String,
     /// Show only toolsets in list.
     pub toolsets_only: bool,
+    /// Package kind filter (all / applications / libraries).
+    #[serde(default)]
+    pub kind_filter: KindFilter,
+    /// Environment table sort column.
+    #[serde(default)]
+    pub env_sort: EnvSortColumn,
+    /// Environment table sorted descending.
+    #[serde(default)]
+    pub env_sort_desc: bool,
     /// Node graph state (lazy init).
     #[serde(skip)]
     pub graph_state: Option<NodeGraphState>,
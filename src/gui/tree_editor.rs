@@ -134,10 +134,11 @@ pub fn render(ui: &mut Ui, state: &mut AppState, storage: &Storage) -> Option<Tr
                                                 ui.label(RichText::new(&evar.name).color(Color32::LIGHT_BLUE));
                                                 ui.label("=");
                                                 // Truncate long values
-                                                let val = if evar.value.len() > 50 {
-                                                    format!("{}...", &evar.value[..47])
+                                                let shown = evar.display_value();
+                                                let val = if shown.len() > 50 {
+                                                    format!("{}...", &shown[..47])
                                                 } else {
-                                                    evar.value.clone()
+                                                    shown.to_string()
                                                 };
                                                 ui.label(&val);
                                                 ui.end_row();
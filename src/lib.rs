@@ -111,6 +111,8 @@
 //! # Modules
 //!
 //! - [`app`] - Application definitions
+//! - [`build`] - Build planning for source-only packages
+//! - [`cancel`] - Cooperative cancellation (Ctrl-C)
 //! - [`dep`] - Dependency specification parsing
 //! - [`env`](mod@env) - Environment collections
 //! - [`error`] - Error types
@@ -119,13 +121,20 @@
 //! - [`package`] - Package definitions
 //! - [`solver`] - Dependency resolution
 //! - [`storage`] - Package discovery
+//! - [`watch`] - Live reload on package file changes
+//! - `server` - Read-only HTTP query server (feature `serve`)
+//! - `remote` - Package repositories over HTTP (feature `remote`)
 //!
 //! # Features
 //!
 //! - `python` (default) - Enable Python bindings via PyO3
+//! - `serve` - HTTP query server (`pkg serve`)
+//! - `remote` - [`Storage::scan_url`] for HTTP package repositories
 
 pub mod app;
+pub mod build;
 pub mod cache;
+pub mod cancel;
 pub mod dep;
 pub mod env;
 pub mod error;
@@ -133,22 +142,33 @@ pub mod evar;
 pub mod loader;
 pub mod name;
 pub mod package;
+pub mod rxt;
 pub mod solver;
 pub mod storage;
 pub mod token;
 pub mod toolset;
+pub mod watch;
 
 pub mod gui;
 
+#[cfg(feature = "serve")]
+pub mod server;
+
+#[cfg(feature = "remote")]
+pub mod remote;
+
 // Re-exports for convenience
 pub use app::App;
 pub use dep::DepSpec;
 pub use env::Env;
-pub use error::{EnvError, EvarError, LoaderError, PackageError, PkgError, SolverError, StorageError};
+pub use error::{
+    AppError, ConflictExplanation, ConflictSide, EnvError, EvarError, LoaderError, PackageError, PkgError, SolverError,
+    StorageError,
+};
 pub use evar::{Action, Evar};
 pub use loader::Loader;
-pub use package::{Package, SolveStatus};
-pub use solver::{PackageIndex, Solver};
+pub use package::{Package, PackageDiff, SolveStatus};
+pub use solver::{Lockfile, PackageIndex, Solver, VersionStrategy};
 pub use storage::Storage;
 
 use pyo3::prelude::*;
@@ -183,6 +203,7 @@ fn pkg(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Core classes
     m.add_class::<Package>()?;
     m.add_class::<Env>()?;
+    m.add_class::<env::EnvGuard>()?;
     m.add_class::<Evar>()?;
     m.add_class::<App>()?;
     m.add_class::<Action>()?;
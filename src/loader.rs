@@ -9,7 +9,7 @@
 //! Package definitions are written in Python (`package.py` files) that define
 //! a `get_package()` function. The loader:
 //!
-//! 1. Reads the `package.py` file
+//! 1. Reads the `package.py` file (or `package.py.gz`, decompressed in memory)
 //! 2. Creates a Python execution context with pkg classes injected
 //! 3. Executes the file to define `get_package()`
 //! 4. Calls `get_package(*args, **kwargs)` with optional arguments
@@ -52,6 +52,19 @@
 //!     return pkg
 //! ```
 //!
+//! # Several Packages per File
+//!
+//! A file may define `get_packages(*args, **kwargs)` instead, returning a
+//! list (e.g. several versions of one base, or a base plus a variant shim).
+//! [`Loader::load_path_all`] returns all of them and storage scans index
+//! each; [`Loader::load_path`] returns the first. If both functions are
+//! defined, `get_packages` wins and a warning is logged.
+//!
+//! # Static TOML Packages
+//!
+//! A `package.toml` is parsed by the [`toml`](mod@toml) submodule instead
+//! of being executed; load arguments don't apply to it.
+//!
 //! # Module Registration
 //!
 //! The loader registers `pkg` module in `sys.modules` with these classes:
@@ -65,6 +78,34 @@
 //!
 //! Standard library modules (`pathlib`, `sys`, `os`) are also pre-imported.
 //!
+//! # Helper Modules
+//!
+//! Shared helper modules are found via [`Loader::with_sys_paths`] and
+//! `$PKG_SYS_PATH` (OS path list, also set by `pkg --sys-path`). These
+//! directories are prepended to `sys.path` before each package.py runs;
+//! ones already on `sys.path` are left where they are.
+//!
+//! # Load Arguments
+//!
+//! Positional args and kwargs given to the loader are forwarded verbatim
+//! to `get_package(*args, **kwargs)`, all as strings. Storage scans pass
+//! none, so a package.py must work without them; arguments select
+//! variants at load time (`kwargs.get("variant", "default")`).
+//! Parameterized loads bypass the loader cache, which is keyed by path.
+//!
+//! # Versions from Git
+//!
+//! CI-built packages can set the version to [`GIT_DESCRIBE_VERSION`]
+//! (`"git-describe"`), in package.py or package.toml. The loader then runs
+//! `git describe --tags` in the definition file's directory and converts the result to semver:
+//!
+//! - `v1.2.3` -> `1.2.3`
+//! - `v1.2-4-gabc1234` -> `1.2.0+4.gabc1234`
+//! - `1.2.3-dirty` -> `1.2.3+dirty`
+//!
+//! If git is unavailable or no tag is found, a warning is logged and
+//! `0.0.0` is used.
+//!
 //! # Usage
 //!
 //! ```ignore
@@ -78,10 +119,11 @@
 //! println!("Loaded: {}", pkg.name);
 //!
 //! // Load with arguments
-//! let pkg = loader.load_with_args(
+//! let kwargs = HashMap::from([("project".to_string(), "my_project".to_string())]);
+//! let pkg = loader.load_path_with_args(
 //!     Path::new("/packages/maya/2026.1.0/package.py"),
-//!     &[],
-//!     &[("project", "my_project")],
+//!     vec![],
+//!     kwargs,
 //! )?;
 //! ```
 //!
@@ -94,8 +136,9 @@
 //! pkg = loader.load("/path/to/package.py")
 //!
 //! # With arguments
-//! pkg = loader.load_with_kwargs(
+//! pkg = loader.load(
 //!     "/path/to/package.py",
+//!     "gpu",
 //!     project="my_project",
 //!     user="artist"
 //! )
@@ -106,12 +149,128 @@ use crate::env::Env;
 use crate::error::LoaderError;
 use crate::evar::{Action, Evar};
 use crate::package::Package;
-use log::{debug, trace};
+use flate2::read::GzDecoder;
+use log::{debug, trace, warn};
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyTuple};
-use std::ffi::CString;
 use std::collections::HashMap;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub mod toml;
+
+/// Version sentinel: resolve from `git describe` at load time.
+pub const GIT_DESCRIBE_VERSION: &str = "git-describe";
+
+/// Version used when `git describe` fails.
+const GIT_FALLBACK_VERSION: &str = "0.0.0";
+
+/// Convert `git describe --tags` output to a semver string.
+///
+/// Accepts an optional `v` prefix, 1-3 numeric components, an optional
+/// `-<commits>-g<hash>` suffix and an optional `-dirty` marker. Commits
+/// since the tag and dirtiness go into build metadata.
+///
+/// # Returns
+/// Semver string, or None if the tag isn't version-like.
+pub fn describe_to_semver(describe: &str) -> Option<String> {
+    static DESCRIBE: OnceLock<regex::Regex> = OnceLock::new();
+    let re = DESCRIBE.get_or_init(|| {
+        regex::Regex::new(
+            r"^[vV]?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:-(\d+)-g([0-9a-fA-F]+))?(-dirty)?$",
+        )
+        .expect("valid git describe regex")
+    });
+    let caps = re.captures(describe.trim())?;
+    let part = |i: usize| caps.get(i).map_or("0", |m| m.as_str());
+    let mut version = format!("{}.{}.{}", part(1), part(2), part(3));
+
+    let mut build = Vec::new();
+    if let (Some(commits), Some(hash)) = (caps.get(4), caps.get(5)) {
+        if commits.as_str() != "0" {
+            build.push(commits.as_str().to_string());
+            build.push(format!("g{}", hash.as_str()));
+        }
+    }
+    if caps.get(6).is_some() {
+        build.push("dirty".to_string());
+    }
+    if !build.is_empty() {
+        version.push('+');
+        version.push_str(&build.join("."));
+    }
+    Some(version)
+}
+
+/// Run `git describe --tags --dirty` in `dir`.
+fn git_describe(dir: &Path) -> Result<String, String> {
+    let output = std::process::Command::new("git")
+        .args(["describe", "--tags", "--dirty"])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Resolve a version for package.py in `dir` from git, falling back to 0.0.0.
+pub fn resolve_git_version(dir: &Path) -> String {
+    match git_describe(dir) {
+        Ok(describe) => describe_to_semver(&describe).unwrap_or_else(|| {
+            warn!(
+                "git describe in {}: '{}' is not a version tag, using {}",
+                dir.display(),
+                describe,
+                GIT_FALLBACK_VERSION
+            );
+            GIT_FALLBACK_VERSION.to_string()
+        }),
+        Err(e) => {
+            warn!(
+                "git describe failed in {}: {}, using {}",
+                dir.display(),
+                e,
+                GIT_FALLBACK_VERSION
+            );
+            GIT_FALLBACK_VERSION.to_string()
+        }
+    }
+}
+
+/// Environment variable with helper module dirs (OS path list).
+pub const SYS_PATH_VAR: &str = "PKG_SYS_PATH";
+
+/// Helper module dirs from `$PKG_SYS_PATH`.
+fn default_sys_paths() -> Vec<PathBuf> {
+    std::env::var_os(SYS_PATH_VAR)
+        .map(|paths| std::env::split_paths(&paths).filter(|p| !p.as_os_str().is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Read package source, gunzipping `.gz` files in memory.
+fn read_source(path: &Path) -> Result<String, LoaderError> {
+    let read_error = |e: std::io::Error| LoaderError::ReadError {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    };
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let file = std::fs::File::open(path).map_err(read_error)?;
+        let mut code = String::new();
+        GzDecoder::new(file).read_to_string(&mut code).map_err(read_error)?;
+        return Ok(code);
+    }
+    std::fs::read_to_string(path).map_err(read_error)
+}
+
+/// Modification time of `path` in unix seconds.
+fn file_mtime(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    let secs = modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    i64::try_from(secs).ok()
+}
 
 /// Extract full Python traceback from PyErr.
 fn format_py_error(py: Python<'_>, err: &PyErr) -> String {
@@ -133,6 +292,59 @@ fn format_py_error(py: Python<'_>, err: &PyErr) -> String {
     err.to_string()
 }
 
+/// [`LoaderError::Execution`] for an exception raised by `path`.
+///
+/// Exceptions raised in `path` itself are reported by line; the full
+/// traceback is kept when they come from somewhere else (helper modules,
+/// imports) and goes to the debug log either way.
+fn python_error(py: Python<'_>, err: &PyErr, path: &Path) -> LoaderError {
+    let traceback = format_py_error(py, err);
+    debug!("Loader: {} raised:\n{}", path.display(), traceback);
+    let (line, raised_here) = error_line(py, err, path);
+    LoaderError::Execution {
+        path: path.to_path_buf(),
+        line,
+        message: err.to_string(),
+        traceback: (!raised_here).then_some(traceback),
+    }
+}
+
+/// Line in `path` where `err` was raised: the innermost traceback frame in
+/// that file, or the reported line of a syntax error.
+///
+/// # Returns
+/// (line, whether the innermost frame is in `path`)
+fn error_line(py: Python<'_>, err: &PyErr, path: &Path) -> (Option<usize>, bool) {
+    let file = path.to_string_lossy();
+    let in_file = |obj: &Bound<'_, PyAny>, attr: &str| {
+        obj.getattr(attr)
+            .and_then(|f| f.extract::<String>())
+            .is_ok_and(|f| f == file)
+    };
+
+    // Syntax errors are raised by compile(), before any frame of the file runs
+    if err.is_instance_of::<pyo3::exceptions::PySyntaxError>(py) {
+        let value = err.value(py).as_any();
+        if !in_file(value, "filename") {
+            return (None, false);
+        }
+        return (value.getattr("lineno").and_then(|l| l.extract()).ok(), true);
+    }
+
+    let mut line = None;
+    let mut innermost_here = false;
+    let mut tb = err.traceback(py).map(|t| t.into_any());
+    while let Some(frame) = tb.take().filter(|t| !t.is_none()) {
+        let code = frame.getattr("tb_frame").and_then(|f| f.getattr("f_code"));
+        innermost_here = code.is_ok_and(|c| in_file(&c, "co_filename"));
+        if innermost_here {
+            line = frame.getattr("tb_lineno").and_then(|l| l.extract()).ok();
+        }
+        tb = frame.getattr("tb_next").ok();
+    }
+    (line, innermost_here)
+}
+
 /// Package.py loader.
 ///
 /// Executes `package.py` files and extracts Package definitions.
@@ -145,11 +357,14 @@ fn format_py_error(py: Python<'_>, err: &PyErr) -> String {
 #[pyclass]
 #[derive(Debug, Clone)]
 pub struct Loader {
-    /// Cache of loaded packages by path.
-    cache: HashMap<PathBuf, Package>,
+    /// Cache of loaded packages by path (several if `get_packages`).
+    cache: HashMap<PathBuf, Vec<Package>>,
 
     /// Whether to use caching.
     use_cache: bool,
+
+    /// Directories prepended to `sys.path` before executing package.py.
+    sys_paths: Vec<PathBuf>,
 }
 
 #[pymethods]
@@ -164,6 +379,7 @@ impl Loader {
         Self {
             cache: HashMap::new(),
             use_cache: use_cache.unwrap_or(true),
+            sys_paths: default_sys_paths(),
         }
     }
 
@@ -171,6 +387,7 @@ impl Loader {
     ///
     /// # Arguments
     /// * `path` - Path to package.py file
+    /// * `args` - Optional positional arguments for get_package()
     /// * `kwargs` - Optional keyword arguments for get_package()
     ///
     /// # Returns
@@ -181,13 +398,34 @@ impl Loader {
     /// loader = Loader()
     /// pkg = loader.load("repo/maya/2026.1.0/package.py")
     /// pkg = loader.load("repo/maya/2026.1.0/package.py", platform="linux")
+    /// pkg = loader.load("repo/maya/2026.1.0/package.py", "gpu")
     /// ```
-    #[pyo3(signature = (path, **kwargs))]
+    #[pyo3(signature = (path, *args, **kwargs))]
     pub fn load(
         &mut self,
         path: &str,
+        args: Vec<String>,
         kwargs: Option<&Bound<'_, PyDict>>,
     ) -> PyResult<Package> {
+        self.load_all(path, args, kwargs).map(first_package)
+    }
+
+    /// Load every package a file defines (`get_packages()` or `get_package()`).
+    ///
+    /// # Arguments
+    /// * `path` - Path to package.py file
+    /// * `args` - Optional positional arguments
+    /// * `kwargs` - Optional keyword arguments
+    ///
+    /// # Returns
+    /// Loaded packages (at least one) or error.
+    #[pyo3(signature = (path, *args, **kwargs))]
+    pub fn load_all(
+        &mut self,
+        path: &str,
+        args: Vec<String>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<Package>> {
         let kwargs_map: HashMap<String, String> = kwargs
             .map(|d| {
                 d.iter()
@@ -200,7 +438,7 @@ impl Loader {
             })
             .unwrap_or_default();
 
-        self.load_impl(Path::new(path), &[], &kwargs_map)
+        self.load_impl(Path::new(path), &args, &kwargs_map)
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
 
@@ -226,15 +464,65 @@ impl Loader {
             self.cache.len()
         )
     }
+
+    /// Builder: add directories to prepend to `sys.path`.
+    /// Returns self for method chaining.
+    #[pyo3(name = "with_sys_paths")]
+    fn py_with_sys_paths(mut slf: PyRefMut<'_, Self>, paths: Vec<PathBuf>) -> PyRefMut<'_, Self> {
+        slf.add_sys_paths(paths);
+        slf
+    }
+
+    /// Directories prepended to `sys.path` before executing package.py.
+    #[getter]
+    fn sys_paths(&self) -> Vec<PathBuf> {
+        self.sys_paths.clone()
+    }
 }
 
 // Pure Rust API
 impl Loader {
+    /// Builder: add directories to prepend to `sys.path` (after any from
+    /// `$PKG_SYS_PATH`), so package.py files can import shared helpers.
+    pub fn with_sys_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.add_sys_paths(paths);
+        self
+    }
+
+    fn add_sys_paths(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            if !self.sys_paths.contains(&path) {
+                self.sys_paths.push(path);
+            }
+        }
+    }
+
     /// Load a package (Rust API).
+    ///
+    /// For a file with `get_packages()` this is the first package; see
+    /// [`load_path_all`](Self::load_path_all).
     pub fn load_path(&mut self, path: &Path) -> Result<Package, LoaderError> {
+        self.load_path_all(path).map(first_package)
+    }
+
+    /// Load every package a file defines.
+    pub fn load_path_all(&mut self, path: &Path) -> Result<Vec<Package>, LoaderError> {
         self.load_impl(path, &[], &HashMap::new())
     }
 
+    /// Load, forwarding `args`/`kwargs` to `get_package(*args, **kwargs)`.
+    ///
+    /// Lets one package.py produce different packages (e.g. pick a
+    /// variant) at load time. Bypasses the cache unless both are empty.
+    pub fn load_path_with_args(
+        &mut self,
+        path: &Path,
+        args: Vec<String>,
+        kwargs: HashMap<String, String>,
+    ) -> Result<Package, LoaderError> {
+        self.load_with_args(path, &args, &kwargs)
+    }
+
     /// Load with full arguments.
     pub fn load_with_args(
         &mut self,
@@ -242,7 +530,7 @@ impl Loader {
         args: &[String],
         kwargs: &HashMap<String, String>,
     ) -> Result<Package, LoaderError> {
-        self.load_impl(path, args, kwargs)
+        self.load_impl(path, args, kwargs).map(first_package)
     }
 
     /// Internal load implementation.
@@ -251,9 +539,12 @@ impl Loader {
         path: &Path,
         args: &[String],
         kwargs: &HashMap<String, String>,
-    ) -> Result<Package, LoaderError> {
+    ) -> Result<Vec<Package>, LoaderError> {
+        // Cache is keyed by path, so only plain loads can use it
+        let use_cache = self.use_cache && args.is_empty() && kwargs.is_empty();
+
         // Check cache
-        if self.use_cache {
+        if use_cache {
             if let Some(cached) = self.cache.get(path) {
                 return Ok(cached.clone());
             }
@@ -266,66 +557,84 @@ impl Loader {
             });
         }
 
-        // Read file
-        let code = std::fs::read_to_string(path).map_err(|e| LoaderError::ReadError {
-            path: path.to_path_buf(),
-            reason: e.to_string(),
-        })?;
+        // Read file (package.py.gz is decompressed in memory)
+        let code = read_source(path)?;
+
+        // Static definitions need no Python; otherwise execute and get packages
+        let mut pkgs = if path.file_name().is_some_and(|n| n == toml::PACKAGE_TOML) {
+            if !args.is_empty() || !kwargs.is_empty() {
+                warn!("Loader: {} takes no arguments, ignoring them", path.display());
+            }
+            vec![toml::parse(&code, path)?]
+        } else {
+            self.execute_package_py(&code, path, args, kwargs)?
+        };
+        stamp_git_versions(&mut pkgs, path);
 
-        // Execute and get package
-        let pkg = self.execute_package_py(&code, path, args, kwargs)?;
+        // Unstamped packages count as released when their file last changed
+        if let Some(mtime) = file_mtime(path) {
+            for pkg in pkgs.iter_mut().filter(|p| p.released.is_none()) {
+                pkg.released = Some(mtime);
+            }
+        }
 
         // Cache result
-        if self.use_cache {
-            self.cache.insert(path.to_path_buf(), pkg.clone());
+        if use_cache {
+            self.cache.insert(path.to_path_buf(), pkgs.clone());
         }
 
-        Ok(pkg)
+        Ok(pkgs)
     }
 
-    /// Execute package.py code and return Package.
+    /// Execute package.py code and return its packages (at least one).
     fn execute_package_py(
         &self,
         code: &str,
         path: &Path,
         args: &[String],
         kwargs: &HashMap<String, String>,
-    ) -> Result<Package, LoaderError> {
+    ) -> Result<Vec<Package>, LoaderError> {
         debug!("Loader: executing {}", path.display());
         trace!("Loader: code length={} args={:?} kwargs={:?}", code.len(), args, kwargs);
 
-        Python::attach(|py| {
+        let mut pkgs = Python::attach(|py| {
             // Create execution globals with injected classes
             trace!("Loader: creating Python globals");
             let globals = self.create_globals(py, path)?;
 
-            // Execute the code using CString
-            let code_cstr = CString::new(code.as_bytes()).map_err(|e| {
-                LoaderError::ExecutionError {
-                    path: path.to_path_buf(),
-                    reason: format!("Invalid code (null byte): {}", e),
-                }
-            })?;
-            if let Err(e) = py.run(code_cstr.as_c_str(), Some(&globals), None) {
-                let traceback = format_py_error(py, &e);
-                return Err(LoaderError::ExecutionError {
-                    path: path.to_path_buf(),
-                    reason: format!("Python error:\n{}", traceback),
-                });
+            // Compile under the real path so tracebacks point into the file
+            let run = || -> PyResult<()> {
+                let builtins = py.import("builtins")?;
+                let compiled = builtins
+                    .getattr("compile")?
+                    .call1((code, path.to_string_lossy().as_ref(), "exec"))?;
+                builtins.getattr("exec")?.call1((compiled, &globals))?;
+                Ok(())
+            };
+            if let Err(e) = run() {
+                return Err(python_error(py, &e, path));
             }
 
-            // Get get_package function
-            let get_package = globals.get_item("get_package").map_err(|_e| {
-                LoaderError::MissingFunction {
-                    path: path.to_path_buf(),
-                    function: "get_package".to_string(),
+            // get_packages() (list) takes precedence over get_package()
+            let get_packages = globals.get_item("get_packages").ok().flatten();
+            let get_package = globals.get_item("get_package").ok().flatten();
+            let (func, func_name) = match (get_packages, get_package) {
+                (Some(many), Some(_)) => {
+                    warn!(
+                        "Loader: {} defines both get_packages and get_package, using get_packages",
+                        path.display()
+                    );
+                    (many, "get_packages")
                 }
-            })?;
-
-            let get_package = get_package.ok_or_else(|| LoaderError::MissingFunction {
-                path: path.to_path_buf(),
-                function: "get_package".to_string(),
-            })?;
+                (Some(many), None) => (many, "get_packages"),
+                (None, Some(one)) => (one, "get_package"),
+                (None, None) => {
+                    return Err(LoaderError::MissingFunction {
+                        path: path.to_path_buf(),
+                        function: "get_package".to_string(),
+                    });
+                }
+            };
 
             // Build arguments
             let py_args = PyTuple::new(py, args.iter().map(|s| s.as_str()))
@@ -338,21 +647,20 @@ impl Loader {
                 py_kwargs.set_item(k, v).ok();
             }
 
-            // Call get_package
-            let result = match get_package.call(py_args, Some(&py_kwargs)) {
-                Ok(r) => r,
-                Err(e) => {
-                    let traceback = format_py_error(py, &e);
-                    return Err(LoaderError::ExecutionError {
-                        path: path.to_path_buf(),
-                        reason: format!("get_package() error:\n{}", traceback),
-                    });
-                }
-            };
+            // Call get_package / get_packages
+            let result = func
+                .call(py_args, Some(&py_kwargs))
+                .map_err(|e| python_error(py, &e, path))?;
 
-            // Convert result to Package
-            self.extract_package(py, &result, path)
-        })
+            // Convert result to Package(s)
+            if func_name == "get_packages" {
+                self.extract_packages(py, &result, path)
+            } else {
+                Ok(vec![self.extract_package(py, &result, path)?])
+            }
+        })?;
+
+        Ok(pkgs)
     }
 
     /// Create Python globals with injected classes.
@@ -425,6 +733,13 @@ impl Loader {
         let sys = py.import("sys").ok();
         let os = py.import("os").ok();
 
+        if let Some(m) = &sys {
+            self.prepend_sys_paths(m).map_err(|e| LoaderError::ExecutionError {
+                path: path.to_path_buf(),
+                reason: format!("Cannot extend sys.path: {}", e),
+            })?;
+        }
+
         if let Some(m) = pathlib {
             if let Ok(path_class) = m.getattr("Path") {
                 globals.set_item("Path", path_class).ok();
@@ -441,6 +756,19 @@ impl Loader {
         Ok(globals)
     }
 
+    /// Prepend configured dirs to `sys.path`, skipping ones already there.
+    fn prepend_sys_paths(&self, sys: &Bound<'_, PyModule>) -> PyResult<()> {
+        let sys_path = sys.getattr("path")?;
+        // Reverse so the first configured dir ends up first
+        for dir in self.sys_paths.iter().rev() {
+            let dir = dir.to_string_lossy().to_string();
+            if !sys_path.contains(&dir)? {
+                sys_path.call_method1("insert", (0, dir))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Extract Package from Python object.
     fn extract_package<'py>(
         &self,
@@ -482,17 +810,98 @@ impl Loader {
         })
     }
 
-    /// Load package from string (for testing).
+    /// Extract the list returned by `get_packages()`.
+    fn extract_packages<'py>(
+        &self,
+        py: Python<'py>,
+        obj: &Bound<'py, PyAny>,
+        path: &Path,
+    ) -> Result<Vec<Package>, LoaderError> {
+        let invalid = |reason: String| LoaderError::InvalidReturn {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let items = obj.try_iter().map_err(|_| {
+            invalid(format!(
+                "get_packages() must return a list, got: {}",
+                obj.get_type().name().map(|n| n.to_string()).unwrap_or_else(|_| "unknown".to_string())
+            ))
+        })?;
+
+        let mut packages = Vec::new();
+        for item in items {
+            let item = item.map_err(|e| invalid(format!("get_packages() result: {}", e)))?;
+            packages.push(self.extract_package(py, &item, path)?);
+        }
+        if packages.is_empty() {
+            return Err(invalid("get_packages() returned no packages".to_string()));
+        }
+        Ok(packages)
+    }
+
+    /// Load package from in-memory source.
+    ///
+    /// Used for tests and for packages that don't live on disk (zip archives).
+    /// `virtual_path` is exposed as `__file__` and used in error messages.
     pub fn load_from_string(
         &mut self,
         code: &str,
         virtual_path: &str,
     ) -> Result<Package, LoaderError> {
+        self.load_all_from_string(code, virtual_path).map(first_package)
+    }
+
+    /// Like [`load_from_string`](Self::load_from_string), returning every
+    /// package the source defines. A `virtual_path` ending in
+    /// `package.toml` is parsed as a static definition.
+    pub fn load_all_from_string(
+        &mut self,
+        code: &str,
+        virtual_path: &str,
+    ) -> Result<Vec<Package>, LoaderError> {
         let path = Path::new(virtual_path);
-        self.execute_package_py(code, path, &[], &HashMap::new())
+        let mut pkgs = if virtual_path.ends_with(toml::PACKAGE_TOML) {
+            vec![toml::parse(code, path)?]
+        } else {
+            self.execute_package_py(code, path, &[], &HashMap::new())?
+        };
+        stamp_git_versions(&mut pkgs, path);
+        Ok(pkgs)
     }
 }
 
+/// Replace [`GIT_DESCRIBE_VERSION`] with the version from git in the
+/// directory of the definition file (package.py or package.toml).
+fn stamp_git_versions(pkgs: &mut [Package], path: &Path) {
+    for pkg in pkgs.iter_mut().filter(|p| p.version == GIT_DESCRIBE_VERSION) {
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let version = resolve_git_version(dir);
+        debug!("Loader: {} version from git: {}", pkg.base, version);
+        pkg.set_version(version);
+    }
+}
+
+/// Split `--arg` style values into `get_package` arguments: `key=value`
+/// becomes a kwarg, anything else a positional arg (in order).
+pub fn parse_load_args(items: &[String]) -> (Vec<String>, HashMap<String, String>) {
+    let mut args = Vec::new();
+    let mut kwargs = HashMap::new();
+    for item in items {
+        match item.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                kwargs.insert(key.to_string(), value.to_string());
+            }
+            _ => args.push(item.clone()),
+        }
+    }
+    (args, kwargs)
+}
+
+/// First of the (never empty) packages a load returns.
+fn first_package(mut pkgs: Vec<Package>) -> Package {
+    pkgs.swap_remove(0)
+}
+
 impl Default for Loader {
     fn default() -> Self {
         Self::new(Some(true))
@@ -516,6 +925,255 @@ mod tests {
         assert!(!loader.use_cache);
     }
 
+    #[test]
+    fn describe_semver() {
+        assert_eq!(describe_to_semver("v1.2.3").unwrap(), "1.2.3");
+        assert_eq!(describe_to_semver("2026.1").unwrap(), "2026.1.0");
+        assert_eq!(describe_to_semver("v1.2-4-gabc1234").unwrap(), "1.2.0+4.gabc1234");
+        assert_eq!(describe_to_semver("1.2.3-dirty").unwrap(), "1.2.3+dirty");
+        assert_eq!(
+            describe_to_semver("v1.2.3-0-gabc1234-dirty").unwrap(),
+            "1.2.3+dirty"
+        );
+        assert_eq!(describe_to_semver("release-candidate"), None);
+        assert_eq!(describe_to_semver("abc1234"), None);
+    }
+
+    #[test]
+    fn git_version_from_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=pkg", "-c", "user.email=pkg@localhost"])
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        };
+        // Skip if git isn't installed
+        if !git(&["init", "-q"]) {
+            return;
+        }
+
+        // No tags yet: fallback
+        assert_eq!(resolve_git_version(dir.path()), "0.0.0");
+
+        std::fs::write(dir.path().join("package.py"), "").unwrap();
+        assert!(git(&["add", "package.py"]));
+        assert!(git(&["commit", "-q", "-m", "init"]));
+        assert!(git(&["tag", "v1.4.0"]));
+        assert_eq!(resolve_git_version(dir.path()), "1.4.0");
+
+        // package.toml gets the same treatment as package.py
+        let toml_path = dir.path().join(toml::PACKAGE_TOML);
+        std::fs::write(&toml_path, "[package]\nbase = \"tool\"\nversion = \"git-describe\"\n")
+            .unwrap();
+        let pkg = Loader::new(Some(false)).load_path(&toml_path).unwrap();
+        assert_eq!(pkg.version, "1.4.0");
+        assert_eq!(pkg.name, "tool-1.4.0");
+    }
+
+    #[test]
+    fn loader_forwards_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.py");
+        std::fs::write(
+            &path,
+            "def get_package(*args, **kwargs):\n    \
+             p = pkg.Package(\"maya\", kwargs.get(\"version\", \"2026.0.0\"))\n    \
+             p.tags = list(args)\n    \
+             return p\n",
+        )
+        .unwrap();
+
+        let mut loader = Loader::new(Some(true));
+        let plain = loader.load_path(&path).unwrap();
+        assert_eq!(plain.version, "2026.0.0");
+        assert!(plain.tags.is_empty());
+
+        let kwargs = HashMap::from([("version".to_string(), "2025.1.0".to_string())]);
+        let pinned = loader
+            .load_path_with_args(&path, vec!["gpu".to_string()], kwargs)
+            .unwrap();
+        assert_eq!(pinned.version, "2025.1.0");
+        assert_eq!(pinned.tags, vec!["gpu"]);
+
+        // Parameterized loads don't poison the cache
+        assert_eq!(loader.load_path(&path).unwrap().version, "2026.0.0");
+    }
+
+    #[test]
+    fn loader_deprecated_reason() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |version: &str, body: &str| {
+            let path = dir.path().join(format!("{}.py", version));
+            std::fs::write(
+                &path,
+                format!(
+                    "def get_package():\n    \
+                     p = pkg.Package(\"ocio\", \"{}\")\n{}    \
+                     return p\n",
+                    version, body
+                ),
+            )
+            .unwrap();
+            path
+        };
+        let old = write("2.1.0", "");
+        let new = write("2.3.0", "    p.deprecated = \"EOL\"\n");
+
+        let mut loader = Loader::new(Some(false));
+        let old = loader.load_path(&old).unwrap();
+        let new = loader.load_path(&new).unwrap();
+        assert!(!old.deprecated);
+        assert!(new.deprecated);
+        assert_eq!(new.deprecated_reason.as_deref(), Some("EOL"));
+
+        // Both match: the newer one is flagged, so the older one wins
+        let solver = crate::solver::Solver::new(vec![old, new]).unwrap();
+        let solution = solver.solve_requirements_impl(&["ocio@2".to_string()]).unwrap();
+        assert_eq!(solution, vec!["ocio-2.1.0".to_string()]);
+
+        // Falsy values undeprecate and drop the reason
+        let cleared = write("2.4.0", "    p.deprecated = \"EOL\"\n    p.deprecated = False\n");
+        let blank = write("2.5.0", "    p.deprecated = \"\"\n");
+        for path in [cleared, blank] {
+            let pkg = loader.load_path(&path).unwrap();
+            assert!(!pkg.deprecated);
+            assert_eq!(pkg.deprecated_reason, None);
+        }
+    }
+
+    #[test]
+    fn loader_args_select_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.py");
+        std::fs::write(
+            &path,
+            "def get_package(*args, **kwargs):\n    \
+             return pkg.Package(\"maya_\" + kwargs.get(\"variant\", \"cpu\"), \"2026.0.0\")\n",
+        )
+        .unwrap();
+
+        let (args, kwargs) = parse_load_args(&["variant=gpu".to_string(), "fast".to_string()]);
+        assert_eq!(args, vec!["fast"]);
+
+        let mut loader = Loader::new(Some(false));
+        assert_eq!(loader.load_path(&path).unwrap().base, "maya_cpu");
+        assert_eq!(loader.load_path_with_args(&path, args, kwargs).unwrap().base, "maya_gpu");
+    }
+
+    #[test]
+    fn loader_sys_paths() {
+        let helpers = tempfile::tempdir().unwrap();
+        std::fs::write(
+            helpers.path().join("pkg_test_studio_helpers.py"),
+            "TOOL_VERSION = \"3.1.0\"\n",
+        )
+        .unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let path = repo.path().join("package.py");
+        std::fs::write(
+            &path,
+            "import pkg_test_studio_helpers\n\n\
+             def get_package():\n    \
+             return pkg.Package(\"tool\", pkg_test_studio_helpers.TOOL_VERSION)\n",
+        )
+        .unwrap();
+
+        let helper_dir = helpers.path().to_path_buf();
+        let mut loader = Loader::new(Some(false))
+            .with_sys_paths(vec![helper_dir.clone()])
+            .with_sys_paths(vec![helper_dir.clone()]);
+        assert_eq!(loader.sys_paths.iter().filter(|p| **p == helper_dir).count(), 1);
+
+        assert_eq!(loader.load_path(&path).unwrap().version, "3.1.0");
+        assert_eq!(loader.load_path(&path).unwrap().version, "3.1.0");
+
+        // Loading twice doesn't duplicate the sys.path entry
+        let entries = Python::attach(|py| -> PyResult<usize> {
+            let sys_path: Vec<String> = py.import("sys")?.getattr("path")?.extract()?;
+            let dir = helper_dir.to_string_lossy();
+            Ok(sys_path.iter().filter(|p| **p == dir).count())
+        })
+        .unwrap();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn loader_error_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.py");
+        std::fs::write(
+            &path,
+            "def get_package():\n    \
+             p = pkg.Package(\"tool\", \"1.0.0\")\n    \
+             raise ValueError(\"bad root\")\n    \
+             return p\n",
+        )
+        .unwrap();
+
+        let mut loader = Loader::new(Some(false));
+        match loader.load_path(&path) {
+            Err(LoaderError::Execution { line, message, .. }) => {
+                assert_eq!(line, Some(3));
+                assert_eq!(message, "ValueError: bad root");
+            }
+            other => panic!("expected execution error, got {:?}", other),
+        }
+
+        // Module-level syntax errors report their line too
+        std::fs::write(&path, "import os\n\ndef get_package(:\n    pass\n").unwrap();
+        let err = loader.load_path(&path).unwrap_err();
+        assert!(matches!(err, LoaderError::Execution { line: Some(3), traceback: None, .. }));
+        assert!(err.to_string().starts_with(&format!("{}:3: SyntaxError", path.display())));
+
+        // Raised in a helper: the call site line plus the full traceback
+        std::fs::write(
+            &path,
+            "exec(compile(\"def boom():\\n    raise RuntimeError('deep')\\n\", \"helpers.py\", \"exec\"))\n\n\
+             def get_package():\n    \
+             boom()\n",
+        )
+        .unwrap();
+        let err = loader.load_path(&path).unwrap_err();
+        assert!(matches!(err, LoaderError::Execution { line: Some(4), traceback: Some(_), .. }), "{:?}", err);
+        let text = err.to_string();
+        assert!(text.starts_with(&format!("{}:4: RuntimeError: deep", path.display())), "{}", text);
+        assert!(text.contains("helpers.py"), "{}", text);
+    }
+
+    #[test]
+    fn loader_get_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.py");
+        std::fs::write(
+            &path,
+            "def get_packages():\n    \
+             return [pkg.Package(\"ocio\", v) for v in (\"2.1.0\", \"2.2.0\")]\n\n\
+             def get_package():\n    \
+             return pkg.Package(\"ocio\", \"1.0.0\")\n",
+        )
+        .unwrap();
+
+        // get_packages wins over get_package
+        let mut loader = Loader::new(Some(true));
+        let pkgs = loader.load_path_all(&path).unwrap();
+        let names: Vec<&str> = pkgs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["ocio-2.1.0", "ocio-2.2.0"]);
+        assert_eq!(loader.load_path(&path).unwrap().name, "ocio-2.1.0");
+
+        // The list must be a non-empty list of packages
+        let empty = loader.load_all_from_string("def get_packages():\n    return []\n", "empty.py");
+        assert!(matches!(empty, Err(LoaderError::InvalidReturn { .. })));
+        let single = loader.load_all_from_string(
+            "def get_packages():\n    return pkg.Package(\"a\", \"1.0.0\")\n",
+            "single.py",
+        );
+        assert!(matches!(single, Err(LoaderError::InvalidReturn { .. })));
+    }
+
     // Note: Tests that require actual Python execution need
     // Python to be available at runtime. These are better suited
     // for integration tests.
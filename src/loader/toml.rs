@@ -0,0 +1,228 @@
+//! Static package definitions from `package.toml`.
+//!
+//! For packages that need no logic, a `package.toml` replaces package.py
+//! and is parsed without starting Python. It produces the same [`Package`]
+//! as the equivalent package.py.
+//!
+//! # File Format
+//!
+//! ```toml
+//! [package]
+//! base = "ocio"
+//! version = "2.1.0"
+//! reqs = ["python@3"]
+//! tags = ["color"]
+//!
+//! [[env]]                 # name defaults to "default"
+//! evars = [
+//!     { name = "OCIO_ROOT", value = "/opt/ocio/2.1.0" },
+//!     { name = "PATH", value = "{OCIO_ROOT}/bin", action = "append" },
+//! ]
+//!
+//! [[app]]
+//! name = "ociocheck"
+//! path = "{OCIO_ROOT}/bin/ociocheck"
+//! args = ["--verbose"]
+//! ```
+//!
+//! Evar `action` defaults to `set`; apps default to the `default` env.
+//! `version = "git-describe"` takes the version from git, as in package.py.
+
+use crate::app::App;
+use crate::env::Env;
+use crate::error::LoaderError;
+use crate::evar::{Action, Evar};
+use crate::package::Package;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of TOML package definitions.
+pub const PACKAGE_TOML: &str = "package.toml";
+
+/// Whole `package.toml` document.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackageToml {
+    package: PackageDef,
+    #[serde(default)]
+    env: Vec<EnvDef>,
+    #[serde(default)]
+    app: Vec<AppDef>,
+}
+
+/// `[package]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackageDef {
+    base: String,
+    version: String,
+    #[serde(default)]
+    reqs: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    tests: BTreeMap<String, String>,
+}
+
+/// `[[env]]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnvDef {
+    #[serde(default = "default_env")]
+    name: String,
+    #[serde(default)]
+    evars: Vec<EvarDef>,
+}
+
+/// One entry of `evars`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EvarDef {
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default = "default_action")]
+    action: String,
+}
+
+/// `[[app]]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AppDef {
+    name: String,
+    path: Option<String>,
+    env: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<String>,
+}
+
+fn default_env() -> String {
+    "default".to_string()
+}
+
+fn default_action() -> String {
+    "set".to_string()
+}
+
+/// Parse `package.toml` source into a package.
+///
+/// # Errors
+/// [`LoaderError::InvalidToml`] on malformed TOML, unknown keys or an
+/// unknown evar action.
+pub fn parse(source: &str, path: &Path) -> Result<Package, LoaderError> {
+    let invalid = |reason: String| LoaderError::InvalidToml {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let def: PackageToml = ::toml::from_str(source).map_err(|e| invalid(e.to_string()))?;
+
+    let mut pkg = Package::new(def.package.base, def.package.version);
+    for req in def.package.reqs {
+        pkg.add_req(req);
+    }
+    for tag in def.package.tags {
+        pkg.add_tag(tag);
+    }
+    pkg.provides = def.package.provides;
+    pkg.tests = def.package.tests;
+
+    for env_def in def.env {
+        let mut env = Env::new(env_def.name);
+        for evar in env_def.evars {
+            let action = Action::from_str(&evar.action).map_err(|e| invalid(e.to_string()))?;
+            env.add(Evar::new(evar.name, evar.value, action));
+        }
+        pkg.add_env(env);
+    }
+
+    for app_def in def.app {
+        let app = App::new(
+            app_def.name,
+            app_def.path,
+            Some(app_def.env.unwrap_or_else(default_env)),
+            Some(app_def.args),
+            app_def.cwd,
+            None,
+            None,
+            false,
+        );
+        pkg.add_app(app);
+    }
+
+    Ok(pkg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::Loader;
+
+    #[test]
+    fn toml_matches_package_py() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join(PACKAGE_TOML);
+        std::fs::write(
+            &toml_path,
+            r#"
+[package]
+base = "ocio"
+version = "2.1.0"
+reqs = ["python@3"]
+tags = ["color"]
+
+[[env]]
+evars = [
+    { name = "OCIO_ROOT", value = "/opt/ocio" },
+    { name = "PATH", value = "{OCIO_ROOT}/bin", action = "append" },
+]
+
+[[app]]
+name = "ociocheck"
+path = "{OCIO_ROOT}/bin/ociocheck"
+args = ["--verbose"]
+"#,
+        )
+        .unwrap();
+
+        let py_path = dir.path().join("package.py");
+        std::fs::write(
+            &py_path,
+            "def get_package():\n    \
+             p = Package(\"ocio\", \"2.1.0\")\n    \
+             p.add_req(\"python@3\")\n    \
+             p.add_tag(\"color\")\n    \
+             env = Env(\"default\")\n    \
+             env.add(Evar(\"OCIO_ROOT\", \"/opt/ocio\", \"set\"))\n    \
+             env.add(Evar(\"PATH\", \"{OCIO_ROOT}/bin\", \"append\"))\n    \
+             p.add_env(env)\n    \
+             p.add_app(App(\"ociocheck\", \"{OCIO_ROOT}/bin/ociocheck\", \"default\", [\"--verbose\"]))\n    \
+             return p\n",
+        )
+        .unwrap();
+
+        let mut loader = Loader::new(Some(false));
+        let mut from_toml = loader.load_path(&toml_path).unwrap();
+        let mut from_py = loader.load_path(&py_path).unwrap();
+        from_toml.package_source = None;
+        from_py.package_source = None;
+        assert_eq!(from_toml, from_py);
+    }
+
+    #[test]
+    fn toml_errors() {
+        let path = Path::new(PACKAGE_TOML);
+        let missing = parse("[package]\nbase = \"ocio\"\n", path);
+        assert!(matches!(missing, Err(LoaderError::InvalidToml { .. })));
+        let unknown = parse("[package]\nbase = \"a\"\nversion = \"1.0.0\"\nrequires = []\n", path);
+        assert!(matches!(unknown, Err(LoaderError::InvalidToml { .. })));
+        let action = parse(
+            "[package]\nbase = \"a\"\nversion = \"1.0.0\"\n[[env]]\nevars = [{ name = \"X\", action = \"merge\" }]\n",
+            path,
+        );
+        assert!(matches!(action, Err(LoaderError::InvalidToml { .. })));
+    }
+}
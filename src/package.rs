@@ -111,12 +111,14 @@
 //! ```
 
 use crate::app::App;
-use crate::env::Env;
-use crate::error::PackageError;
+use crate::dep::DepSpec;
+use crate::env::{Env, EnvComparison};
+use crate::error::{PackageError, SolverError};
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Status of package dependency resolution.
 #[pyclass(eq, eq_int)]
@@ -207,6 +209,12 @@ pub struct Package {
     #[pyo3(get, set)]
     pub reqs: Vec<String>,
 
+    /// Optional requirement groups, pulled in with `name[group]`
+    /// (e.g., `{"gpu": ["cuda@12"]}` for `maya[gpu]`).
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extras: BTreeMap<String, Vec<String>>,
+
     /// Resolved dependencies (full Package objects).
     /// Populated by the solver after successful resolution.
     /// 
@@ -216,6 +224,14 @@ pub struct Package {
     #[pyo3(get)]
     pub deps: Vec<Package>,
 
+    /// Virtual names this package also answers to, as `base@version`
+    /// (or bare `base` for this package's own version), e.g. `ocio@2.3.0`
+    /// on `opencolorio` after a rename. Requirements on the virtual name
+    /// resolve to this package.
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provides: Vec<String>,
+
     /// Package tags for categorization and filtering.
     /// Common tags: "dcc", "render", "adobe", "autodesk", "vfx", etc.
     #[pyo3(get, set)]
@@ -240,6 +256,52 @@ pub struct Package {
     #[pyo3(get, set)]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub package_source: Option<String>,
+
+    /// Index of the storage location (layer) this package was scanned
+    /// from; lower layers override higher ones. Set by storage.
+    #[pyo3(get)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_layer: Option<usize>,
+
+    /// Deprecated versions are avoided by the solver unless nothing else
+    /// satisfies the constraint (e.g. an exact version request).
+    /// From Python, `pkg.deprecated = "reason"` also sets the reason.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub deprecated: bool,
+
+    /// Optional explanation shown next to deprecated packages.
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_reason: Option<String>,
+
+    /// Hidden packages are left out of default listings (`pkg ls`) but
+    /// still resolve as dependencies.
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub hidden: bool,
+
+    /// Must be built from source; there is no prebuilt payload.
+    /// Needs a build until `install_path` exists.
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub source_only: bool,
+
+    /// Where a source-only package's build output is installed.
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_path: Option<String>,
+
+    /// Test commands by name, run by `pkg test` in the package's solved env.
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tests: BTreeMap<String, String>,
+
+    /// Release time in unix seconds. Defaults to the package.py mtime;
+    /// `Solver::solve_at` ignores packages released after its cutoff.
+    #[pyo3(get, set)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released: Option<i64>,
 }
 
 #[pymethods]
@@ -265,12 +327,22 @@ impl Package {
             envs: Vec::new(),
             apps: Vec::new(),
             reqs: Vec::new(),
+            extras: BTreeMap::new(),
             deps: Vec::new(),
+            provides: Vec::new(),
             tags: Vec::new(),
             icon: None,
             solve_status: SolveStatus::NotSolved,
             solve_error: None,
             package_source: None,
+            origin_layer: None,
+            deprecated: false,
+            deprecated_reason: None,
+            hidden: false,
+            source_only: false,
+            install_path: None,
+            tests: BTreeMap::new(),
+            released: None,
         }
     }
 
@@ -283,6 +355,25 @@ impl Package {
         self.name = format!("{}-{}", self.base, self.version);
     }
 
+    /// Mark the package deprecated.
+    ///
+    /// Accepts a bool, or a string which flags the package and records
+    /// it as `deprecated_reason`. Falsy values (`False`, `""`) undeprecate
+    /// it and clear the reason.
+    #[setter]
+    pub fn set_deprecated(&mut self, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        if let Ok(reason) = value.extract::<String>() {
+            self.deprecated = !reason.is_empty();
+            self.deprecated_reason = Some(reason).filter(|r| !r.is_empty());
+        } else {
+            self.deprecated = value.extract()?;
+        }
+        if !self.deprecated {
+            self.deprecated_reason = None;
+        }
+        Ok(())
+    }
+
     /// Add an environment to the package.
     pub fn add_env(&mut self, env: Env) {
         self.envs.push(env);
@@ -295,12 +386,31 @@ impl Package {
 
     /// Add a requirement (dependency constraint).
     ///
+    /// Skipped if an equivalent requirement is already present
+    /// (compared via [`DepSpec::canonical`]).
+    ///
     /// # Arguments
     /// * `req` - Requirement string (e.g., "redshift@>=3.5,<4.0")
     pub fn add_req(&mut self, req: String) {
+        if let Ok(spec) = DepSpec::parse_impl(&req) {
+            let canonical = spec.canonical();
+            let dup = self.reqs.iter().any(|r| {
+                DepSpec::parse_impl(r).is_ok_and(|existing| existing.canonical() == canonical)
+            });
+            if dup {
+                return;
+            }
+        }
         self.reqs.push(req);
     }
 
+    /// Requirements plus those of the given extra groups.
+    ///
+    /// Raises if an extra is not defined in `extras`.
+    pub fn reqs_for(&self, extras: Vec<String>) -> PyResult<Vec<String>> {
+        Ok(self.reqs_with_extras(&extras)?)
+    }
+
     /// Add a tag to the package.
     pub fn add_tag(&mut self, tag: String) {
         if !self.tags.contains(&tag) {
@@ -443,7 +553,8 @@ impl Package {
     /// Get effective environment for an app.
     ///
     /// Looks up the app by name, finds its env_name, and returns
-    /// the corresponding solved environment.
+    /// the corresponding solved environment with the app's
+    /// `env_overrides` layered on top.
     ///
     /// # Arguments
     /// * `app_name` - Name of the app (uses default app if None)
@@ -463,7 +574,12 @@ impl Package {
         let env_name = app.env_name.as_deref().unwrap_or("default");
 
         // _env with deps=true already returns solved env
-        Ok(self._env(env_name, true).or_else(|| self.default_env()))
+        let Some(env) = self._env(env_name, true).or_else(|| self.default_env()) else {
+            return Ok(None);
+        };
+        app.layer_env(env)
+            .map(Some)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
 
     /// Parse version as SemVer.
@@ -513,13 +629,24 @@ impl Package {
 
         // Reqs and deps (deps as names for serialization)
         dict.set_item("reqs", PyList::new(py, &self.reqs)?)?;
+        dict.set_item("extras", &self.extras)?;
         let dep_names: Vec<&str> = self.deps.iter().map(|d| d.name.as_str()).collect();
         dict.set_item("deps", PyList::new(py, &dep_names)?)?;
+        dict.set_item("provides", PyList::new(py, &self.provides)?)?;
 
         // Tags and icon
         dict.set_item("tags", PyList::new(py, &self.tags)?)?;
         dict.set_item("icon", &self.icon)?;
 
+        // Deprecation
+        dict.set_item("deprecated", self.deprecated)?;
+        dict.set_item("deprecated_reason", &self.deprecated_reason)?;
+        dict.set_item("hidden", self.hidden)?;
+        dict.set_item("source_only", self.source_only)?;
+        dict.set_item("install_path", &self.install_path)?;
+        dict.set_item("tests", &self.tests)?;
+        dict.set_item("released", self.released)?;
+
         Ok(dict.into())
     }
 
@@ -559,6 +686,13 @@ impl Package {
             let reqs: Vec<String> = reqs_obj.extract()?;
             pkg.reqs = reqs;
         }
+        if let Some(obj) = dict.get_item("extras")? {
+            pkg.extras = obj.extract()?;
+        }
+
+        if let Some(obj) = dict.get_item("provides")? {
+            pkg.provides = obj.extract()?;
+        }
 
         // Deps - skip, they're populated by solve()
         // (from_dict doesn't restore full Package deps)
@@ -574,6 +708,31 @@ impl Package {
             pkg.icon = icon_obj.extract().ok();
         }
 
+        // Deprecation
+        if let Some(obj) = dict.get_item("deprecated")? {
+            pkg.deprecated = obj.extract()?;
+        }
+        if let Some(obj) = dict.get_item("deprecated_reason")? {
+            pkg.deprecated_reason = obj.extract().ok();
+        }
+        if let Some(obj) = dict.get_item("hidden")? {
+            pkg.hidden = obj.extract()?;
+        }
+
+        // Build
+        if let Some(obj) = dict.get_item("source_only")? {
+            pkg.source_only = obj.extract()?;
+        }
+        if let Some(obj) = dict.get_item("install_path")? {
+            pkg.install_path = obj.extract().ok();
+        }
+        if let Some(obj) = dict.get_item("tests")? {
+            pkg.tests = obj.extract()?;
+        }
+        if let Some(obj) = dict.get_item("released")? {
+            pkg.released = obj.extract()?;
+        }
+
         Ok(pkg)
     }
 
@@ -672,15 +831,29 @@ impl Package {
         // Solve requirements
         match solver.solve_reqs(self.reqs.clone()) {
             Ok(solution) => {
-                // Clone packages into deps - intentional ownership transfer
-                // Makes Package self-contained, independent from Storage
-                self.deps = solution
-                    .iter()
-                    .filter(|name| *name != &self.name)
-                    .filter_map(|name| available.iter().find(|p| &p.name == name).cloned())
-                    .collect();
-                self.solve_status = SolveStatus::Solved;
-                self.solve_error = None;
+                self.set_solution(&solution, available);
+                Ok(())
+            }
+            Err(e) => {
+                self.solve_status = SolveStatus::Failed;
+                self.solve_error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Resolve versions with constraint-only caps like `ocio@<3`.
+    ///
+    /// Same as [`solve_version_impl`](Self::solve_version_impl), but via
+    /// [`Solver::solve_requirements_capped_impl`](crate::solver::Solver::solve_requirements_capped_impl).
+    pub fn solve_capped_impl(&mut self, available: &[Package], caps: &[String]) -> Result<(), SolverError> {
+        use crate::solver::Solver;
+
+        let solution = Solver::from_packages(available)
+            .and_then(|solver| solver.solve_requirements_capped_impl(&self.reqs, caps));
+        match solution {
+            Ok(solution) => {
+                self.set_solution(&solution, available);
                 Ok(())
             }
             Err(e) => {
@@ -691,6 +864,19 @@ impl Package {
         }
     }
 
+    /// Fill deps from resolved names and mark solved.
+    fn set_solution(&mut self, solution: &[String], available: &[Package]) {
+        // Clone packages into deps - intentional ownership transfer
+        // Makes Package self-contained, independent from Storage
+        self.deps = solution
+            .iter()
+            .filter(|name| *name != &self.name)
+            .filter_map(|name| available.iter().find(|p| &p.name == name).cloned())
+            .collect();
+        self.solve_status = SolveStatus::Solved;
+        self.solve_error = None;
+    }
+
     /// Recursively solve all deps (Rust API with slice).
     /// 
     /// Cloning strategy: We clone packages intentionally to make each Package
@@ -777,6 +963,28 @@ impl Package {
 
 // Pure Rust API
 impl Package {
+    /// Requirements plus those of the given extra groups.
+    ///
+    /// Extra requirements are added with [`add_req`](Self::add_req), so
+    /// ones equivalent to a base requirement are not repeated.
+    ///
+    /// # Errors
+    /// [`PackageError::ExtraNotFound`] if an extra is not defined.
+    pub fn reqs_with_extras(&self, extras: &[String]) -> Result<Vec<String>, PackageError> {
+        let mut merged = Package::new(self.base.clone(), self.version.clone());
+        merged.reqs = self.reqs.clone();
+        for extra in extras {
+            let reqs = self.extras.get(extra).ok_or_else(|| PackageError::ExtraNotFound {
+                package: self.name.clone(),
+                extra: extra.clone(),
+            })?;
+            for req in reqs {
+                merged.add_req(req.clone());
+            }
+        }
+        Ok(merged.reqs)
+    }
+
     /// Parse a package name into base and version.
     ///
     /// # Arguments
@@ -866,15 +1074,64 @@ impl Package {
         Ok(self.version_cmp(other)? == std::cmp::Ordering::Greater)
     }
 
+    /// Package root directory (parent of `package_source`), if known.
+    pub fn root_dir(&self) -> Option<std::path::PathBuf> {
+        let source = std::path::Path::new(self.package_source.as_deref()?);
+        source.parent().map(|p| p.to_path_buf())
+    }
+
+    /// Absolute path of the package [`icon`](Self::icon).
+    ///
+    /// Relative icons resolve against [`root_dir`](Self::root_dir).
+    ///
+    /// # Returns
+    /// The path only if the file exists; None otherwise or if a relative
+    /// icon has no known package source.
+    pub fn resolved_icon(&self) -> Option<std::path::PathBuf> {
+        resolve_icon(self.icon.as_deref()?, self.root_dir())
+    }
+
+    /// Absolute path of an app's `icon` property, resolved like
+    /// [`resolved_icon`](Self::resolved_icon). Only this package's own apps.
+    pub fn resolved_app_icon(&self, app_name: &str) -> Option<std::path::PathBuf> {
+        let app = self.apps.iter().find(|a| a.name == app_name)?;
+        resolve_icon(&app.icon()?, self.root_dir())
+    }
+
+    /// Copy of `env` with `{PKG_ROOT}` expanded to this package's root.
+    ///
+    /// Must run before envs of different packages are merged, since each
+    /// package has its own root. Left unexpanded if the source is unknown.
+    fn expand_pkg_root(&self, env: &Env) -> Env {
+        let mut env = env.clone();
+        if let Some(root) = self.root_dir() {
+            let root = root.to_string_lossy();
+            for evar in &mut env.evars {
+                evar.value = crate::token::expand_pkg_root(&evar.value, &root);
+            }
+        }
+        env
+    }
+
     /// Get env by name (internal Rust API).
     ///
+    /// `{PKG_ROOT}` resolves to each package's own directory.
     /// Tokens are always expanded. When deps=true, merges envs from dependencies first.
     /// For toolsets (packages without own envs), returns merged env from dependencies.
+    /// The installed prelude ([`Env::set_prelude`]) is merged underneath everything.
     pub fn _env(&self, name: &str, deps: bool) -> Option<Env> {
-        use crate::env::Env;
+        let prelude = Env::prelude();
+        self.env_with_prelude(name, deps, prelude.as_ref())
+    }
+
+    /// Like [`_env`](Self::_env) with an explicit prelude.
+    ///
+    /// The prelude is merged first (lowest priority): its variables are
+    /// present in the result unless the package or its deps set them.
+    pub fn env_with_prelude(&self, name: &str, deps: bool, prelude: Option<&Env>) -> Option<Env> {
         use log::debug;
         
-        let own = self.envs.iter().find(|e| e.name == name).cloned();
+        let own = self.envs.iter().find(|e| e.name == name).map(|e| self.expand_pkg_root(e));
         
         // Collect deps envs if requested
         // NOTE: After solve(), deps is a FLAT list of all resolved packages (direct + transitive).
@@ -904,7 +1161,7 @@ impl Package {
             let mut merged: Option<Env> = None;
             // Transitive first (will end up last in PATH due to insert prepend)
             for dep in transitive.iter().rev() {
-                if let Some(dep_env) = dep._env(name, false) {
+                if let Some(dep_env) = dep.env_with_prelude(name, false, None) {
                     merged = Some(match merged {
                         Some(m) => m.merge(&dep_env),
                         None => dep_env,
@@ -913,7 +1170,7 @@ impl Package {
             }
             // Direct reqs last in reverse order (first req will be first in PATH)
             for dep in direct.iter().rev() {
-                if let Some(dep_env) = dep._env(name, false) {
+                if let Some(dep_env) = dep.env_with_prelude(name, false, None) {
                     merged = Some(match merged {
                         Some(m) => m.merge(&dep_env),
                         None => dep_env,
@@ -933,9 +1190,19 @@ impl Package {
             (None, Some(d)) => d.compress(), // Toolset case: must compress deps!
             (None, None) => return None,
         };
+
+        // Site prelude underneath everything
+        let result = match prelude {
+            Some(p) => {
+                let mut merged = p.merge(&result).compress();
+                merged.name = result.name;
+                merged
+            }
+            None => result,
+        };
         
         // ALWAYS expand tokens
-        match result.solve_impl(10, true) {
+        match result.solve_impl(crate::env::DEFAULT_MAX_DEPTH, true) {
             Ok(solved) => {
                 debug!("Package::_env solved {} evars for {}", solved.evars.len(), name);
                 Some(solved)
@@ -966,6 +1233,74 @@ impl Package {
         None
     }
 
+    /// Describe how to launch an app as a JSON manifest for a launcher service.
+    ///
+    /// Solves the package against `available` unless it is already solved,
+    /// takes the app's [`effective_env`](Self::effective_env) without
+    /// [`Env::default_blacklist`] variables and expands `{TOKEN}`s in the
+    /// exe path, args and cwd against it. Unknown tokens are left in place.
+    ///
+    /// # Arguments
+    /// * `available` - Packages to solve dependencies against
+    /// * `app_name` - Name of the app (uses default app if None)
+    ///
+    /// # Returns
+    /// `{"exe", "args", "cwd", "env"}`; `exe`/`cwd` are null without an app
+    /// path, `env` maps names to values (unset variables omitted).
+    ///
+    /// # Errors
+    /// If the solve fails or the app's env can't be resolved.
+    pub fn launch_manifest(&self, available: &[Package], app_name: Option<&str>) -> PyResult<serde_json::Value> {
+        let mut solved: Package;
+        let pkg = if self.is_solved() {
+            self
+        } else {
+            solved = self.clone();
+            solved.solve_version_impl(available)?;
+            solved.solve_deps_impl(available)?;
+            &solved
+        };
+
+        let app = match app_name {
+            Some(name) => pkg._app(name, true),
+            None => pkg.default_app(),
+        };
+        let env = pkg.effective_env(app_name)?.or_else(|| pkg.default_env()).map(|env| {
+            let (env, skipped) = env.without_names(&Env::default_blacklist());
+            for name in &skipped {
+                log::warn!("Package {}: not passing blacklisted variable {}", pkg.name, name);
+            }
+            env
+        });
+
+        let vars: BTreeMap<String, String> = env.map(|e| e.to_map().into_iter().collect()).unwrap_or_default();
+        let lookup: std::collections::HashMap<String, String> =
+            vars.iter().map(|(k, v)| (k.to_lowercase(), v.clone())).collect();
+        let expand = |value: &str| {
+            crate::token::expand_tokens(value, |name| lookup.get(&name.to_lowercase()).cloned())
+        };
+
+        let (exe, args, cwd) = match app {
+            Some(app) => {
+                let resolved = App {
+                    path: app.path.as_deref().map(expand),
+                    cwd: app.cwd.as_deref().map(expand),
+                    ..app
+                };
+                let args: Vec<String> = resolved.build_args(None).iter().map(|a| expand(a)).collect();
+                (resolved.path.clone(), args, resolved.effective_cwd())
+            }
+            None => (None, Vec::new(), None),
+        };
+
+        Ok(serde_json::json!({
+            "exe": exe,
+            "args": args,
+            "cwd": cwd,
+            "env": vars,
+        }))
+    }
+
     /// Create a merged environment from all package envs.
     ///
     /// Merges all envs in order, then compresses the result.
@@ -1045,6 +1380,150 @@ impl Package {
     }
 }
 
+/// Result of [`Package::semantic_diff`]: changes from self to other.
+///
+/// Requirements are matched by base and compared by canonical form, so
+/// `ocio@2` vs `ocio@>=2.0.0,<3.0.0` is not a change. Envs are compared
+/// with [`Env::compare`]. All lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageDiff {
+    /// Requirements only in other
+    pub reqs_added: Vec<String>,
+    /// Requirements only in self
+    pub reqs_removed: Vec<String>,
+    /// Same base, different constraint: (base, self req, other req)
+    pub reqs_changed: Vec<(String, String, String)>,
+    /// Env names only in other
+    pub envs_added: Vec<String>,
+    /// Env names only in self
+    pub envs_removed: Vec<String>,
+    /// Variable-level changes for envs present in both
+    pub envs_changed: Vec<(String, EnvComparison)>,
+    /// App names only in other
+    pub apps_added: Vec<String>,
+    /// App names only in self
+    pub apps_removed: Vec<String>,
+    /// Apps present in both with different definitions
+    pub apps_changed: Vec<String>,
+    /// Tags only in other
+    pub tags_added: Vec<String>,
+    /// Tags only in self
+    pub tags_removed: Vec<String>,
+}
+
+impl PackageDiff {
+    /// True if the packages are semantically equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.reqs_added.is_empty()
+            && self.reqs_removed.is_empty()
+            && self.reqs_changed.is_empty()
+            && self.envs_added.is_empty()
+            && self.envs_removed.is_empty()
+            && self.envs_changed.is_empty()
+            && self.apps_added.is_empty()
+            && self.apps_removed.is_empty()
+            && self.apps_changed.is_empty()
+            && self.tags_added.is_empty()
+            && self.tags_removed.is_empty()
+    }
+}
+
+/// Names in `left` missing from `right`, sorted.
+fn missing_from<'a>(left: impl IntoIterator<Item = &'a String>, right: &[&String]) -> Vec<String> {
+    let mut out: Vec<String> = left
+        .into_iter()
+        .filter(|n| !right.contains(n))
+        .cloned()
+        .collect();
+    out.sort();
+    out
+}
+
+/// Requirements keyed by base, valued by canonical form (raw if unparseable).
+fn reqs_by_base(reqs: &[String]) -> std::collections::BTreeMap<String, (String, String)> {
+    reqs.iter()
+        .map(|r| match DepSpec::parse_impl(r) {
+            Ok(spec) => (spec.base.clone(), (spec.canonical(), r.clone())),
+            Err(_) => (r.clone(), (r.clone(), r.clone())),
+        })
+        .collect()
+}
+
+// Semantic diff
+impl Package {
+    /// Compare requirements, envs, apps and tags with another package.
+    ///
+    /// Typically used on two versions of the same base (`pkg diff`).
+    pub fn semantic_diff(&self, other: &Package) -> PackageDiff {
+        let mut diff = PackageDiff::default();
+
+        // Requirements
+        let left = reqs_by_base(&self.reqs);
+        let right = reqs_by_base(&other.reqs);
+        for (base, (canonical, raw)) in &left {
+            match right.get(base) {
+                None => diff.reqs_removed.push(raw.clone()),
+                Some((theirs, their_raw)) if theirs != canonical => {
+                    diff.reqs_changed.push((base.clone(), raw.clone(), their_raw.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (base, (_, raw)) in &right {
+            if !left.contains_key(base) {
+                diff.reqs_added.push(raw.clone());
+            }
+        }
+        diff.reqs_added.sort();
+        diff.reqs_removed.sort();
+
+        // Envs
+        let self_envs: Vec<&String> = self.envs.iter().map(|e| &e.name).collect();
+        let other_envs: Vec<&String> = other.envs.iter().map(|e| &e.name).collect();
+        diff.envs_added = missing_from(other_envs.iter().copied(), &self_envs);
+        diff.envs_removed = missing_from(self_envs.iter().copied(), &other_envs);
+        for env in &self.envs {
+            if let Some(theirs) = other.envs.iter().find(|e| e.name == env.name) {
+                let cmp = env.compare(theirs);
+                if !cmp.is_empty() {
+                    diff.envs_changed.push((env.name.clone(), cmp));
+                }
+            }
+        }
+        diff.envs_changed.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // Apps
+        let self_apps: Vec<&String> = self.apps.iter().map(|a| &a.name).collect();
+        let other_apps: Vec<&String> = other.apps.iter().map(|a| &a.name).collect();
+        diff.apps_added = missing_from(other_apps.iter().copied(), &self_apps);
+        diff.apps_removed = missing_from(self_apps.iter().copied(), &other_apps);
+        for app in &self.apps {
+            if let Some(theirs) = other.apps.iter().find(|a| a.name == app.name) {
+                if theirs != app {
+                    diff.apps_changed.push(app.name.clone());
+                }
+            }
+        }
+        diff.apps_changed.sort();
+
+        // Tags
+        let self_tags: Vec<&String> = self.tags.iter().collect();
+        let other_tags: Vec<&String> = other.tags.iter().collect();
+        diff.tags_added = missing_from(other_tags.iter().copied(), &self_tags);
+        diff.tags_removed = missing_from(self_tags.iter().copied(), &other_tags);
+
+        diff
+    }
+}
+
+/// Absolute path of `icon`, joining relative ones to `root`; None unless it exists.
+fn resolve_icon(icon: &str, root: Option<std::path::PathBuf>) -> Option<std::path::PathBuf> {
+    let path = std::path::Path::new(icon);
+    let path = if path.is_absolute() { path.to_path_buf() } else { root?.join(path) };
+    let path = std::path::absolute(path).ok()?;
+    path.is_file().then_some(path)
+}
+
 impl Default for Package {
     fn default() -> Self {
         Self::new("unnamed".to_string(), "0.0.0".to_string())
@@ -1100,6 +1579,47 @@ mod tests {
         assert!(pkg.has_req("redshift"));
         assert!(pkg.has_req("ocio"));
         assert!(!pkg.has_req("unknown"));
+
+        // Equivalent spellings are deduplicated
+        pkg.add_req("ocio@>=2.0.0, <3.0.0".to_string());
+        pkg.add_req("redshift@<4,>=3.5".to_string());
+        assert_eq!(pkg.reqs, vec!["redshift@>=3.5,<4.0", "ocio@2"]);
+    }
+
+    #[test]
+    fn package_semantic_diff() {
+        let mut old = Package::new("maya".to_string(), "2026.0.0".to_string());
+        old.add_req("ocio@2".to_string());
+        old.add_req("redshift@>=3.5,<4.0".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("ROOT", "/opt/maya"));
+        env.add(Evar::set("MAYA_VERSION", "2026.0"));
+        old.add_env(env);
+
+        let mut new = old.clone();
+        new.version = "2026.1.0".to_string();
+        new.reqs = vec!["ocio@>=2.0.0,<3.0.0".to_string(), "redshift@>=3.6,<4.0".to_string()];
+        new.envs[0].evars[1] = Evar::set("MAYA_VERSION", "2026.1");
+
+        assert!(old.semantic_diff(&old).is_empty());
+
+        let diff = old.semantic_diff(&new);
+        // ocio spelling changed but the range didn't
+        assert_eq!(
+            diff.reqs_changed,
+            vec![(
+                "redshift".to_string(),
+                "redshift@>=3.5,<4.0".to_string(),
+                "redshift@>=3.6,<4.0".to_string()
+            )]
+        );
+        assert!(diff.reqs_added.is_empty() && diff.reqs_removed.is_empty());
+        assert_eq!(diff.envs_changed.len(), 1);
+        let (name, cmp) = &diff.envs_changed[0];
+        assert_eq!(name, "default");
+        assert_eq!(cmp.differing.len(), 1);
+        assert_eq!(cmp.differing[0].0, "MAYA_VERSION");
+        assert!(diff.apps_changed.is_empty() && diff.tags_added.is_empty());
     }
 
     #[test]
@@ -1121,6 +1641,79 @@ mod tests {
         assert!(pkg.default_app().is_some());
     }
 
+    #[test]
+    fn package_effective_env_overrides() {
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("MAYA_ROOT", "/opt/maya"));
+        env.add(Evar::set("MAYA_RENDER_NODE", "0"));
+        pkg.add_env(env);
+
+        pkg.add_app(App::named("maya").with_env("default"));
+        let mut render = App::named("render")
+            .with_env("default")
+            .with_override(Evar::set("MAYA_RENDER_NODE", "1"));
+        render.add_override(Evar::set("RENDER_LOG", "{MAYA_ROOT}/logs"));
+        pkg.add_app(render);
+
+        let gui = pkg.effective_env(Some("maya")).unwrap().unwrap();
+        let render = pkg.effective_env(Some("render")).unwrap().unwrap();
+        assert_ne!(gui, render);
+
+        assert_eq!(gui.get("MAYA_RENDER_NODE").unwrap().value, "0");
+        assert!(gui.get("RENDER_LOG").is_none());
+        assert_eq!(render.get("MAYA_RENDER_NODE").unwrap().value, "1");
+        assert_eq!(render.get("RENDER_LOG").unwrap().value, "/opt/maya/logs");
+        assert_eq!(render.get("MAYA_ROOT").unwrap().value, "/opt/maya");
+    }
+
+    #[test]
+    fn package_launch_manifest() {
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("MAYA_ROOT", "/opt/maya"));
+        env.add(Evar::append("PATH", "{MAYA_ROOT}/bin"));
+        pkg.add_env(env);
+        pkg.add_app(
+            App::named("maya")
+                .with_path("{MAYA_ROOT}/bin/maya")
+                .with_arg("-proj")
+                .with_arg("{MAYA_ROOT}/projects"),
+        );
+
+        let manifest = pkg.launch_manifest(&[], None).unwrap();
+        assert_eq!(manifest["exe"], "/opt/maya/bin/maya");
+        assert_eq!(manifest["args"], serde_json::json!(["-proj", "/opt/maya/projects"]));
+        assert_eq!(manifest["cwd"], "/opt/maya/bin");
+        assert_eq!(manifest["env"]["MAYA_ROOT"], "/opt/maya");
+        assert!(manifest["env"]["PATH"].as_str().unwrap().contains("/opt/maya/bin"));
+
+        let missing = pkg.launch_manifest(&[], Some("nuke")).unwrap();
+        assert!(missing["exe"].is_null());
+    }
+
+    #[test]
+    fn package_launch_manifest_solves() {
+        let mut ocio = Package::new("ocio".to_string(), "2.3.0".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("OCIO", "/opt/ocio/config.ocio"));
+        env.add(Evar::set("LD_PRELOAD", "/tmp/hook.so"));
+        ocio.add_env(env);
+
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        pkg.add_req("ocio".to_string());
+        pkg.add_app(App::named("maya").with_path("/opt/maya/bin/maya"));
+        assert!(!pkg.is_solved());
+
+        let manifest = pkg.launch_manifest(&[ocio], None).unwrap();
+        assert_eq!(manifest["env"]["OCIO"], "/opt/ocio/config.ocio");
+        assert!(manifest["env"].get("LD_PRELOAD").is_none());
+
+        // Unsatisfiable reqs are an error, not an empty env
+        pkg.add_req("nuke".to_string());
+        assert!(pkg.launch_manifest(&[], None).is_err());
+    }
+
     #[test]
     fn package_version_compare() {
         let pkg1 = Package::new("maya".to_string(), "2025.0.0".to_string());
@@ -1182,6 +1775,52 @@ mod tests {
         assert!(pkg.deps.iter().any(|d| d.name.starts_with("redshift-")));
     }
 
+    #[test]
+    fn package_solve_deterministic() {
+        use crate::solver::Solver;
+
+        let make = |base: &str, version: &str, reqs: &[&str]| {
+            let mut pkg = Package::new(base.to_string(), version.to_string());
+            for req in reqs {
+                pkg.add_req(req.to_string());
+            }
+            let mut env = Env::new("default".to_string());
+            env.add(Evar::append("PATH", format!("/opt/{base}/{version}/bin")));
+            pkg.add_env(env);
+            pkg
+        };
+        let available = vec![
+            make("maya", "2026.0.0", &["ocio@>=2", "redshift", "usd", "arnold"]),
+            make("redshift", "3.5.0", &["ocio@>=2.1", "usd@<25"]),
+            make("arnold", "7.3.0", &["ocio", "oiio"]),
+            make("oiio", "2.5.0", &["ocio@<2.3"]),
+            make("usd", "24.11.0", &["ocio"]),
+            make("usd", "25.2.0", &["ocio"]),
+            make("ocio", "1.1.0", &[]),
+            make("ocio", "2.1.0", &[]),
+            make("ocio", "2.2.0", &[]),
+            make("ocio", "2.3.0", &[]),
+        ];
+
+        // Fresh solver and package each run: new HashMap seeds every time
+        let run = || {
+            let solver = Solver::from_packages(&available).unwrap();
+            let solution = solver.solve_impl("maya-2026.0.0").unwrap();
+            let mut maya = available[0].clone();
+            maya.solve(available.clone()).unwrap();
+            let deps: Vec<String> = maya.deps.iter().map(|d| d.name.clone()).collect();
+            let path = maya._env("default", true).unwrap().get("PATH").unwrap().value;
+            (solution, deps, path)
+        };
+
+        let first = run();
+        assert!(first.0.contains(&"ocio-2.2.0".to_string()));
+        assert!(first.0.contains(&"usd-24.11.0".to_string()));
+        for _ in 0..50 {
+            assert_eq!(run(), first);
+        }
+    }
+
     #[test]
     fn package_solve_empty_reqs() {
         let mut pkg = Package::new("simple".to_string(), "1.0.0".to_string());
@@ -1240,4 +1879,93 @@ mod tests {
         assert_eq!(evars[0].name, "PKG_HOUDINI_ROOT");
         assert_eq!(evars[0].value, "C:/Program Files/Houdini");
     }
+
+    #[test]
+    fn env_pkg_root_token() {
+        let dir = std::path::Path::new("repo").join("maya").join("2026.0.0");
+
+        let mut dep = Package::new("ocio".to_string(), "2.0.0".to_string());
+        dep.package_source = Some("/repo/ocio/2.0.0/package.py".to_string());
+        let mut dep_env = Env::new("default".to_string());
+        dep_env.add(Evar::append("PATH", "{PKG_ROOT}/bin"));
+        dep.add_env(dep_env);
+
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        pkg.package_source = Some(dir.join("package.py").to_string_lossy().to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::append("PATH", "{PKG_ROOT}/bin"));
+        pkg.add_env(env);
+        pkg.add_req("ocio".to_string());
+        pkg.deps.push(dep);
+
+        assert_eq!(pkg.root_dir(), Some(dir.clone()));
+
+        let solved = pkg._env("default", true).unwrap();
+        let path = solved.get("PATH").unwrap();
+        let parts: Vec<&str> = path.value().split(crate::evar::path_sep().as_str()).collect();
+        assert!(parts.contains(&format!("{}/bin", dir.to_string_lossy()).as_str()));
+        assert!(parts.contains(&"/repo/ocio/2.0.0/bin"));
+        assert!(!path.value().contains("PKG_ROOT"));
+    }
+
+    #[test]
+    fn package_resolved_icon() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("icons")).unwrap();
+        std::fs::write(dir.path().join("icons").join("maya.png"), b"png").unwrap();
+
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        pkg.icon = Some("icons/maya.png".to_string());
+        pkg.add_app(App::named("maya").with_property("icon", "icons/maya.png"));
+        pkg.add_app(App::named("mayapy").with_property("icon", "icons/missing.png"));
+
+        // Relative icon without a known source can't be resolved
+        assert!(pkg.resolved_icon().is_none());
+
+        pkg.package_source = Some(dir.path().join("package.py").to_string_lossy().to_string());
+        let expected = dir.path().join("icons").join("maya.png");
+        assert_eq!(pkg.resolved_icon(), Some(expected.clone()));
+        assert!(pkg.resolved_icon().unwrap().is_absolute());
+        assert_eq!(pkg.resolved_app_icon("maya"), Some(expected.clone()));
+        assert!(pkg.resolved_app_icon("mayapy").is_none());
+        assert!(pkg.resolved_app_icon("nuke").is_none());
+
+        // Absolute icons are used as-is
+        pkg.icon = Some(expected.to_string_lossy().to_string());
+        pkg.package_source = None;
+        assert_eq!(pkg.resolved_icon(), Some(expected));
+    }
+
+    #[test]
+    fn env_pkg_root_without_source() {
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("MAYA_BIN", "{PKG_ROOT}/bin"));
+        pkg.add_env(env);
+
+        assert!(pkg.root_dir().is_none());
+        let solved = pkg._env("default", false).unwrap();
+        assert_eq!(solved.get("MAYA_BIN").unwrap().value(), "{PKG_ROOT}/bin");
+    }
+
+    #[test]
+    fn env_prelude() {
+        let mut prelude = Env::new("prelude".to_string());
+        prelude.add(Evar::set("STUDIO_ROOT", "/studio"));
+        prelude.add(Evar::set("OCIO", "/studio/default.ocio"));
+
+        let mut pkg = Package::new("maya".to_string(), "2026.0.0".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("OCIO", "/maya/aces.ocio"));
+        pkg.add_env(env);
+
+        let solved = pkg.env_with_prelude("default", true, Some(&prelude)).unwrap();
+        assert_eq!(solved.get("STUDIO_ROOT").unwrap().value, "/studio");
+        assert_eq!(solved.get("OCIO").unwrap().value, "/maya/aces.ocio");
+        assert_eq!(solved.name, "default");
+
+        // No package env: prelude alone doesn't create one
+        let empty = Package::new("empty".to_string(), "1.0.0".to_string());
+        assert!(empty.env_with_prelude("default", true, Some(&prelude)).is_none());
+    }
 }
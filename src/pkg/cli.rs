@@ -4,6 +4,9 @@ use clap::{Parser, Subcommand};
 use clap_complete::Shell as CompletionShell;
 use std::path::PathBuf;
 
+/// Values accepted by `pkg env --format` (and the shell's `env -f`).
+pub const ENV_FORMATS: [&str; 7] = ["shell", "json", "export", "set", "fish", "powershell", "dotenv"];
+
 /// pkg - Software package management
 #[derive(Parser)]
 #[command(name = "pkg")]
@@ -41,10 +44,22 @@ pub struct Cli {
     #[arg(short = 'x', long = "exclude", global = true)]
     pub exclude: Vec<String>,
 
+    /// Only report what --exclude would remove, keep all packages
+    #[arg(long = "dry-run-exclude", global = true)]
+    pub dry_run_exclude: bool,
+
+    /// Helper module dir importable from package.py (can repeat, adds to $PKG_SYS_PATH)
+    #[arg(long = "sys-path", global = true)]
+    pub sys_paths: Vec<PathBuf>,
+
     /// Include user packages (~/.pkg-rs/packages)
     #[arg(short = 'u', long = "user-packages", global = true, default_value = "false")]
     pub user_packages: bool,
 
+    /// Keep packages tagged for other platforms/architectures (platform-*, arch-*)
+    #[arg(long = "all-platforms", global = true)]
+    pub all_platforms: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -75,6 +90,9 @@ pub enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Include hidden packages
+        #[arg(short = 'a', long, visible_alias = "include-hidden")]
+        all: bool,
     },
 
     /// Show package details
@@ -84,12 +102,29 @@ pub enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Reload with an argument for get_package: key=value or positional (can repeat)
+        #[arg(long = "arg")]
+        args: Vec<String>,
+    },
+
+    /// Compare two package versions semantically
+    Diff {
+        /// Old package (name-version)
+        a: String,
+        /// New package (name-version)
+        b: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Compare the solved default environments instead of definitions
+        #[arg(long)]
+        env: bool,
     },
 
     /// Setup environment and optionally run command
     Env {
         /// Package name(s)
-        #[arg(required = true)]
+        #[arg(required_unless_present = "lock")]
         packages: Vec<String>,
         /// Command to run (after --)
         #[arg(last = true)]
@@ -97,8 +132,8 @@ pub enum Commands {
         /// Environment name (default: "default")
         #[arg(long)]
         env_name: Option<String>,
-        /// Output format: shell, json, export, set
-        #[arg(short, long, default_value = "shell")]
+        /// Output format: shell, json, export, set, fish, powershell, dotenv
+        #[arg(short, long, default_value = "shell", value_parser = ENV_FORMATS)]
         format: String,
         /// Expand {TOKEN} references in values (default: true)
         #[arg(short, long, default_value = "true", action = clap::ArgAction::Set)]
@@ -112,6 +147,117 @@ pub enum Commands {
         /// Add PKG_* stamp variables for each resolved package
         #[arg(short, long)]
         stamp: bool,
+        /// Only the package's own environment (skip dependency resolution)
+        #[arg(long)]
+        no_deps: bool,
+        /// Bound a dependency's version without requiring it, e.g. ocio@<3 (can repeat)
+        #[arg(long = "cap")]
+        caps: Vec<String>,
+        /// Write the resolved context as a rez-compatible .rxt file
+        #[arg(long)]
+        rxt: Option<PathBuf>,
+        /// Reproduce a lockfile from `pkg lock` (replaces package arguments)
+        #[arg(long, conflicts_with = "no_deps")]
+        lock: Option<PathBuf>,
+        /// Which matching versions to pick: newest, oldest (test range floors)
+        #[arg(long, default_value = "newest", value_parser = ["newest", "oldest"])]
+        prefer: String,
+        /// Give up resolving after this many solver steps
+        #[arg(long)]
+        max_steps: Option<usize>,
+        /// Resolve as of a date: ignore packages released after it
+        /// (ISO 8601, UTC unless an offset is given, or @<unix seconds>)
+        #[arg(long)]
+        before: Option<String>,
+    },
+
+    /// Compare resolved environment against a saved baseline
+    #[command(name = "env-diff")]
+    EnvDiff {
+        /// Package name(s)
+        #[arg(required = true)]
+        packages: Vec<String>,
+        /// Baseline JSON file
+        #[arg(short, long)]
+        baseline: PathBuf,
+        /// Write current environment as the new baseline
+        #[arg(long)]
+        save: bool,
+        /// Environment name (default: "default")
+        #[arg(long)]
+        env_name: Option<String>,
+    },
+
+    /// Print exact resolved versions (base-version per line)
+    Freeze {
+        /// Requirement(s) to resolve
+        #[arg(required = true)]
+        targets: Vec<String>,
+        /// Write to file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Solve requirements and write a JSON lockfile of exact versions
+    Lock {
+        /// Requirement(s) to resolve
+        #[arg(required = true)]
+        targets: Vec<String>,
+        /// Write to file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Write a relocatable bundle: solved env.json plus activation scripts
+    Bundle {
+        /// Requirement(s) to resolve
+        #[arg(required = true)]
+        packages: Vec<String>,
+        /// Output directory
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Also copy package directories into the bundle
+        #[arg(long)]
+        copy: bool,
+    },
+
+    /// Print JSON launch manifest (exe, args, cwd, env) for an app
+    Manifest {
+        /// Package name
+        package: String,
+        /// App name (default: package's default app)
+        #[arg(long)]
+        app: Option<String>,
+    },
+
+    /// Launch a package's app with its args, cwd and resolved environment
+    Run {
+        /// Package name
+        package: String,
+        /// App name (default: package's default app)
+        app: Option<String>,
+        /// Extra arguments after the app's own (after --)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// Run a package's declared test commands in its resolved environment
+    Test {
+        /// Package name
+        package: String,
+        /// Test names (default: all)
+        tests: Vec<String>,
+    },
+
+    /// Print the resolved package and absolute executable path of its app
+    Which {
+        /// Package name or requirement (maya, maya@2026)
+        package: String,
+        /// App name (default: package's default app)
+        app: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Show dependency graph
@@ -127,6 +273,9 @@ pub enum Commands {
         /// Show reverse dependencies
         #[arg(short = 'R', long)]
         reverse: bool,
+        /// Print resolved packages in this order instead of a graph (topo: dependencies first)
+        #[arg(long, value_parser = ["topo"])]
+        order: Option<String>,
     },
 
     /// Scan locations for packages
@@ -135,6 +284,22 @@ pub enum Commands {
         paths: Vec<PathBuf>,
     },
 
+    /// Inspect or reset the package cache
+    Cache {
+        /// Action: stats, clear
+        #[arg(value_parser = ["stats", "clear"])]
+        action: String,
+    },
+
+    /// Move package.py dirs to canonical base/version layout
+    Normalize {
+        /// Repository location to check
+        location: PathBuf,
+        /// Apply moves (default: report only)
+        #[arg(long)]
+        write: bool,
+    },
+
     /// Generate test repository with random packages
     #[command(name = "gen-repo", after_help = 
         "PRESETS:\n  \
@@ -191,6 +356,13 @@ pub enum Commands {
     #[command(visible_alias = "sh")]
     Shell,
 
+    /// Print shell function `pkg-activate` for eval-based activation
+    #[command(name = "shell-init")]
+    ShellInit {
+        /// Shell type: bash, zsh, fish, powershell
+        shell: String,
+    },
+
     /// Generate shell completions
     Completions {
         /// Shell type
@@ -200,4 +372,15 @@ pub enum Commands {
     /// Launch graphical interface
     #[command(name = "gui")]
     Gui,
+
+    /// Serve read-only JSON query endpoints over HTTP
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
 }
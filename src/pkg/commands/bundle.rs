@@ -0,0 +1,309 @@
+//! Bundle command.
+//!
+//! Solves requests and writes a self-contained environment for machines
+//! without the repository mounted (render farm nodes):
+//!
+//! ```text
+//! pkg bundle maya redshift -o /farm/bundles/shot010
+//! pkg bundle maya -o bundle --copy      # also copy package dirs
+//! ```
+//!
+//! The output dir holds `env.json` (the solved env) and `activate.sh`,
+//! `activate.ps1` and `activate.cmd`. With `--copy` each resolved package
+//! dir is copied to `packages/<base>/<version>` and env values pointing
+//! into it are rewritten relative to the bundle, so the whole dir can be
+//! moved: the scripts set `PKG_BUNDLE` to their own location.
+//!
+//! Secret values never land in the bundle: `env.json` shows them masked
+//! and the scripts leave them to the target machine.
+
+use super::env::resolve_package;
+use pkg_lib::evar::SECRET_MASK;
+use pkg_lib::{Env, Package, Storage};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Placeholder for the bundle dir in env values, replaced per script.
+const BUNDLE_MARKER: &str = "@PKG_BUNDLE@";
+
+/// Write `env.json` and activation scripts for `packages` into `output`.
+pub fn cmd_bundle(storage: &Storage, packages: Vec<String>, output: &Path, copy: bool) -> ExitCode {
+    match write_bundle(storage, &packages, output, copy) {
+        Ok(names) => {
+            eprintln!("Bundled {} into {}", names.join(", "), output.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Solve `packages` and write the bundle files.
+///
+/// # Returns
+/// Names of the bundled packages.
+fn write_bundle(storage: &Storage, packages: &[String], output: &Path, copy: bool) -> Result<Vec<String>, String> {
+    let pkg = resolve_package(storage, packages)?;
+
+    let env = pkg.default_env().unwrap_or_else(|| Env::new("default".to_string()));
+    let (mut env, skipped) = env.without_names(&Env::default_blacklist());
+    for name in &skipped {
+        eprintln!("Skipping blacklisted variable {}", name);
+    }
+    for evar in env.evars.iter().filter(|e| e.secret) {
+        eprintln!("Not bundling secret variable {}; provide it on the target machine", evar.name);
+    }
+
+    let mut resolved: Vec<&Package> = pkg.deps.iter().collect();
+    if pkg.base != "_adhoc" {
+        resolved.insert(0, &pkg);
+    }
+
+    std::fs::create_dir_all(output).map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    if copy {
+        for dep in &resolved {
+            match copy_package(dep, output) {
+                Ok(Some((root, rel))) => relocate(&mut env, &root, &rel),
+                Ok(None) => eprintln!("Not copying {}: no package directory", dep.name),
+                Err(e) => return Err(format!("Failed to copy {}: {}", dep.name, e)),
+            }
+        }
+    }
+
+    let files = [
+        ("env.json", env_json(&env)),
+        ("activate.sh", activate_sh(&env)),
+        ("activate.ps1", activate_ps1(&env)),
+        ("activate.cmd", activate_cmd(&env)),
+    ];
+    for (name, content) in &files {
+        let path = output.join(name);
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    Ok(resolved.iter().map(|p| p.name.clone()).collect())
+}
+
+/// Copy a package's dir to `<output>/packages/<base>/<version>`.
+///
+/// # Returns
+/// The original root and the bundle-relative path, or None for packages
+/// without a filesystem dir (zipped, toolsets, built in memory).
+fn copy_package(pkg: &Package, output: &Path) -> std::io::Result<Option<(PathBuf, String)>> {
+    let Some(root) = pkg.root_dir().filter(|d| d.is_dir()) else {
+        return Ok(None);
+    };
+    let rel = format!("packages/{}/{}", pkg.base, pkg.version);
+    copy_dir(&root, &output.join(&rel))?;
+    Ok(Some((root, rel)))
+}
+
+fn copy_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Point values under `root` at the copy inside the bundle.
+fn relocate(env: &mut Env, root: &Path, rel: &str) {
+    let root = root.to_string_lossy();
+    let copied = format!("{}/{}", BUNDLE_MARKER, rel);
+    for evar in &mut env.evars {
+        evar.value = replace_root(&evar.value, &root, &copied);
+    }
+}
+
+/// Replace `root` in `value` only as whole leading path components: it
+/// must start the value or a list entry and end at a path or list
+/// separator. `\` and `/` compare equal.
+fn replace_root(value: &str, root: &str, with: &str) -> String {
+    let is_list_sep = |c: char| c == ':' || c == ';';
+    // Same byte length as the originals, so positions carry over
+    let normalized = value.replace('\\', "/");
+    let root_norm = root.replace('\\', "/");
+    let root_norm = root_norm.trim_end_matches('/');
+    if root_norm.is_empty() {
+        return value.to_string();
+    }
+
+    let mut out = String::with_capacity(value.len());
+    let mut copied_to = 0;
+    let mut search_from = 0;
+    while let Some(found) = normalized[search_from..].find(root_norm) {
+        let start = search_from + found;
+        let end = start + root_norm.len();
+        let starts_entry = normalized[..start].chars().next_back().is_none_or(is_list_sep);
+        let ends_component = normalized[end..].chars().next().is_none_or(|c| c == '/' || is_list_sep(c));
+        if starts_entry && ends_component {
+            out.push_str(&value[copied_to..start]);
+            out.push_str(with);
+            copied_to = end;
+        }
+        search_from = end;
+    }
+    out.push_str(&value[copied_to..]);
+    out
+}
+
+/// Env for the activation scripts: secrets are left out.
+fn without_secrets(env: &Env) -> Env {
+    let mut env = env.clone();
+    env.evars.retain(|e| !e.secret);
+    env
+}
+
+/// Copy of `env` with the bundle marker replaced by `reference`.
+///
+/// Only for output that isn't quoted afterwards: scripts quote values, so
+/// they replace the marker in the finished text instead.
+fn with_bundle_ref(env: &Env, reference: &str) -> Env {
+    let mut env = env.clone();
+    for evar in &mut env.evars {
+        evar.value = evar.value.replace(BUNDLE_MARKER, reference);
+    }
+    env
+}
+
+/// Solved env as JSON, bundle paths as `{PKG_BUNDLE}` tokens and secret
+/// values masked.
+fn env_json(env: &Env) -> String {
+    let mut env = with_bundle_ref(env, "{PKG_BUNDLE}");
+    for evar in env.evars.iter_mut().filter(|e| e.secret) {
+        evar.value = SECRET_MASK.to_string();
+    }
+    serde_json::to_string_pretty(&env).unwrap_or_default()
+}
+
+fn activate_sh(env: &Env) -> String {
+    format!(
+        "# Environment bundle generated by pkg v{}\n\
+         # Use with: source activate.sh, then deactivate\n\
+         export PKG_BUNDLE=\"$(cd \"$(dirname \"${{BASH_SOURCE[0]:-$0}}\")\" && pwd)\"\n\n{}\n",
+        pkg_lib::VERSION,
+        // Values are single-quoted: step out of the quotes to expand the dir
+        without_secrets(env)
+            .to_activation_sh()
+            .replace(BUNDLE_MARKER, "'\"${PKG_BUNDLE}\"'")
+    )
+}
+
+fn activate_ps1(env: &Env) -> String {
+    format!(
+        "# Environment bundle generated by pkg v{}\n\
+         # Use with: . .\\activate.ps1, then deactivate\n\
+         $env:PKG_BUNDLE = $PSScriptRoot\n\n{}\n",
+        pkg_lib::VERSION,
+        without_secrets(env).to_activation_ps1().replace(BUNDLE_MARKER, "$env:PKG_BUNDLE")
+    )
+}
+
+fn activate_cmd(env: &Env) -> String {
+    format!(
+        "@echo off\r\n\
+         REM Environment bundle generated by pkg v{}\r\n\
+         for %%I in (\"%~dp0.\") do SET \"PKG_BUNDLE=%%~fI\"\r\n{}\r\n",
+        pkg_lib::VERSION,
+        without_secrets(env).to_cmd(None).replace(BUNDLE_MARKER, "%PKG_BUNDLE%")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pkg_lib::Evar;
+
+    fn write_package(repo: &Path, base: &str, version: &str, evar: &str, reqs: &[&str]) {
+        let dir = repo.join(base).join(version);
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        let reqs: String = reqs.iter().map(|r| format!("    p.add_req(\"{}\")\n", r)).collect();
+        std::fs::write(
+            dir.join("package.py"),
+            format!(
+                "def get_package():\n    \
+                 p = Package(\"{base}\", \"{version}\")\n{reqs}    \
+                 env = Env(\"default\")\n    \
+                 env.add(Evar(\"{evar}\", \"{{PKG_ROOT}}/bin\", \"set\"))\n    \
+                 p.add_env(env)\n    \
+                 return p\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn bundle_two_packages() {
+        let repo = tempfile::tempdir().unwrap();
+        write_package(repo.path(), "maya", "2026.1.0", "MAYA_BIN", &["ocio"]);
+        write_package(repo.path(), "ocio", "2.3.0", "OCIO_BIN", &[]);
+        let storage = Storage::scan_impl(Some(&[repo.path().to_path_buf()])).unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let names = write_bundle(&storage, &["maya".to_string()], out.path(), false).unwrap();
+        assert_eq!(names, ["maya-2026.1.0", "ocio-2.3.0"]);
+        for script in ["activate.sh", "activate.ps1", "activate.cmd"] {
+            let content = std::fs::read_to_string(out.path().join(script)).unwrap();
+            assert!(content.contains("MAYA_BIN"), "{}", script);
+            assert!(content.contains("OCIO_BIN"), "{}", script);
+        }
+        let env: Env = serde_json::from_str(&std::fs::read_to_string(out.path().join("env.json")).unwrap()).unwrap();
+        assert!(env.get("OCIO_BIN").unwrap().value.ends_with("bin"));
+
+        // --copy rewrites package paths relative to the bundle
+        let copied = tempfile::tempdir().unwrap();
+        write_bundle(&storage, &["maya".to_string()], copied.path(), true).unwrap();
+        assert!(copied.path().join("packages/ocio/2.3.0/package.py").exists());
+        let sh = std::fs::read_to_string(copied.path().join("activate.sh")).unwrap();
+        assert!(sh.contains("export OCIO_BIN=''\"${PKG_BUNDLE}\"'/packages/ocio/2.3.0"), "{}", sh);
+    }
+
+    #[test]
+    fn bundle_marker_per_shell() {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("TOOL", format!("{}/packages/tool/1.0.0/bin", BUNDLE_MARKER)));
+        assert!(activate_sh(&env).contains("export TOOL=''\"${PKG_BUNDLE}\"'/packages/tool/1.0.0/bin'"));
+        assert!(activate_ps1(&env).contains("$env:TOOL = \"$env:PKG_BUNDLE/packages/tool/1.0.0/bin\""));
+        assert!(activate_cmd(&env).contains("SET TOOL=%PKG_BUNDLE%/packages/tool/1.0.0/bin"));
+        assert!(env_json(&env).contains("{PKG_BUNDLE}/packages/tool/1.0.0/bin"));
+    }
+
+    #[test]
+    fn bundle_secrets_left_out() {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("TOOL", "/opt/tool"));
+        env.add(Evar::set("LICENSE_TOKEN", "s3cr3t").with_secret(true));
+        for script in [env_json(&env), activate_sh(&env), activate_ps1(&env), activate_cmd(&env)] {
+            assert!(script.contains("TOOL"));
+            assert!(!script.contains("s3cr3t"), "{}", script);
+        }
+        assert!(env_json(&env).contains(SECRET_MASK));
+        assert!(!activate_sh(&env).contains("LICENSE_TOKEN"));
+    }
+
+    #[test]
+    fn relocate_whole_components() {
+        let with = "@PKG_BUNDLE@/packages/ocio/2.3.0";
+        let root = "/repo/ocio/2.3.0";
+        assert_eq!(replace_root("/repo/ocio/2.3.0/bin", root, with), format!("{}/bin", with));
+        assert_eq!(replace_root("/repo/ocio/2.3.0", root, with), with);
+        assert_eq!(replace_root("/repo/ocio/2.3.0-rc/bin", root, with), "/repo/ocio/2.3.0-rc/bin");
+        assert_eq!(replace_root("/mnt/repo/ocio/2.3.0/bin", root, with), "/mnt/repo/ocio/2.3.0/bin");
+        assert_eq!(
+            replace_root("/a:/repo/ocio/2.3.0/lib:/repo/ocio/2.3.0-rc", root, with),
+            format!("/a:{}/lib:/repo/ocio/2.3.0-rc", with)
+        );
+        assert_eq!(
+            replace_root("C:/repo/ocio/2.3.0/bin;C:\\x", "C:\\repo\\ocio\\2.3.0", with),
+            format!("{}/bin;C:\\x", with)
+        );
+    }
+}
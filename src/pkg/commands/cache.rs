@@ -0,0 +1,43 @@
+//! Cache command.
+//!
+//! Inspects or resets the on-disk package cache:
+//!
+//! ```text
+//! pkg cache stats     # entries, size, location
+//! pkg cache clear     # delete the cache file
+//! ```
+
+use pkg_lib::cache::Cache;
+use std::process::ExitCode;
+
+/// Run `stats` or `clear` on the package cache.
+pub fn cmd_cache(action: &str) -> ExitCode {
+    let mut cache = Cache::load();
+    let location = Cache::cache_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "(unavailable)".to_string());
+
+    match action {
+        "clear" => match cache.clear() {
+            Ok(true) => {
+                println!("Cache cleared: {}", location);
+                ExitCode::SUCCESS
+            }
+            Ok(false) => {
+                println!("No cache file at {}", location);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to clear cache {}: {}", location, e);
+                ExitCode::FAILURE
+            }
+        },
+        _ => {
+            let stats = cache.stats();
+            println!("Entries:  {}", stats.entries);
+            println!("Size:     {:.1} KB", stats.bytes as f64 / 1024.0);
+            println!("Location: {}", location);
+            ExitCode::SUCCESS
+        }
+    }
+}
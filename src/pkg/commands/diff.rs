@@ -0,0 +1,178 @@
+//! Semantic package diff command.
+//!
+//! Compares two package definitions by meaning rather than text, or with
+//! `--env` the default environments they solve to:
+//!
+//! ```text
+//! pkg diff maya-2026.0.0 maya-2026.1.0
+//! pkg diff maya houdini --env --json
+//! ```
+
+use super::env::resolve_package;
+use pkg_lib::{Env, Storage};
+use std::process::ExitCode;
+
+/// Report requirement, env, app and tag changes from `a` to `b`.
+///
+/// With `env`, report the solved environment delta instead.
+/// Exits with success whether or not differences were found.
+pub fn cmd_diff(storage: &Storage, a: &str, b: &str, json: bool, env: bool) -> ExitCode {
+    if env {
+        return diff_solved_envs(storage, a, b, json);
+    }
+
+    let (Some(old), Some(new)) = (storage.resolve(a), storage.resolve(b)) else {
+        let missing = if storage.resolve(a).is_none() { a } else { b };
+        eprintln!("Package not found: {}", missing);
+        return ExitCode::FAILURE;
+    };
+
+    let diff = old.semantic_diff(&new);
+
+    if json {
+        match serde_json::to_string_pretty(&diff) {
+            Ok(j) => println!("{}", j),
+            Err(e) => {
+                eprintln!("Failed to serialize diff: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    println!("{} -> {}", old.name, new.name);
+    if diff.is_empty() {
+        println!("No semantic differences");
+        return ExitCode::SUCCESS;
+    }
+
+    if !diff.reqs_added.is_empty() || !diff.reqs_removed.is_empty() || !diff.reqs_changed.is_empty() {
+        println!("Requirements:");
+        for req in &diff.reqs_removed {
+            println!("  - {}", req);
+        }
+        for req in &diff.reqs_added {
+            println!("  + {}", req);
+        }
+        for (_, old_req, new_req) in &diff.reqs_changed {
+            println!("  ~ {} -> {}", old_req, new_req);
+        }
+    }
+
+    if !diff.envs_added.is_empty() || !diff.envs_removed.is_empty() || !diff.envs_changed.is_empty() {
+        println!("Environments:");
+        for name in &diff.envs_removed {
+            println!("  - {}", name);
+        }
+        for name in &diff.envs_added {
+            println!("  + {}", name);
+        }
+        for (name, cmp) in &diff.envs_changed {
+            println!("  ~ {}", name);
+            for var in &cmp.only_self {
+                println!("      - {}", var);
+            }
+            for var in &cmp.only_other {
+                println!("      + {}", var);
+            }
+            for (var, old_val, new_val) in &cmp.differing {
+                println!("      ~ {}: {} -> {}", var, old_val, new_val);
+            }
+        }
+    }
+
+    if !diff.apps_added.is_empty() || !diff.apps_removed.is_empty() || !diff.apps_changed.is_empty() {
+        println!("Applications:");
+        for name in &diff.apps_removed {
+            println!("  - {}", name);
+        }
+        for name in &diff.apps_added {
+            println!("  + {}", name);
+        }
+        for name in &diff.apps_changed {
+            println!("  ~ {}", name);
+        }
+    }
+
+    if !diff.tags_added.is_empty() || !diff.tags_removed.is_empty() {
+        println!("Tags:");
+        for tag in &diff.tags_removed {
+            println!("  - {}", tag);
+        }
+        for tag in &diff.tags_added {
+            println!("  + {}", tag);
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Solve both packages and diff their default envs variable by variable.
+fn diff_solved_envs(storage: &Storage, a: &str, b: &str, json: bool) -> ExitCode {
+    let solved_env = |request: &str| -> Result<(String, Env), String> {
+        let pkg = resolve_package(storage, &[request.to_string()])?;
+        // _env with deps=true is already merged, compressed and solved
+        let env = pkg.default_env().unwrap_or_else(|| Env::new("default".to_string()));
+        Ok((pkg.name, env))
+    };
+    let ((old_name, old), (new_name, new)) = match (solved_env(a), solved_env(b)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let delta = env_delta(&old, &new);
+    if json {
+        match serde_json::to_string_pretty(&delta) {
+            Ok(j) => println!("{}", j),
+            Err(e) => {
+                eprintln!("Failed to serialize diff: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    println!("{} -> {}", old_name, new_name);
+    let cmp = old.compare(&new);
+    if cmp.is_empty() {
+        println!("No environment differences");
+        return ExitCode::SUCCESS;
+    }
+    for var in &cmp.only_self {
+        println!("  - {}", var);
+    }
+    for var in &cmp.only_other {
+        let value = new.get(var).map(|e| e.value).unwrap_or_default();
+        println!("  + {}={}", var, value);
+    }
+    for (var, old_val, new_val) in &cmp.differing {
+        println!("  ~ {}: {} -> {}", var, old_val, new_val);
+    }
+    ExitCode::SUCCESS
+}
+
+/// `{added: {var: value}, removed: [var], changed: {var: {old, new}}}`.
+fn env_delta(old: &Env, new: &Env) -> serde_json::Value {
+    let cmp = old.compare(new);
+    let added: serde_json::Map<String, serde_json::Value> = cmp
+        .only_other
+        .iter()
+        .map(|var| {
+            let value = new.get(var).map(|e| e.value).unwrap_or_default();
+            (var.clone(), value.into())
+        })
+        .collect();
+    let changed: serde_json::Map<String, serde_json::Value> = cmp
+        .differing
+        .into_iter()
+        .map(|(var, old_val, new_val)| (var, serde_json::json!({"old": old_val, "new": new_val})))
+        .collect();
+    serde_json::json!({
+        "added": added,
+        "removed": cmp.only_self,
+        "changed": changed,
+    })
+}
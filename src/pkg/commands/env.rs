@@ -1,7 +1,9 @@
 //! Environment command.
 
-use pkg_lib::{Package, Storage};
-use std::path::PathBuf;
+use pkg_lib::dep::split_extras;
+use pkg_lib::env::{is_shell_name, ps1_escape, sh_quote};
+use pkg_lib::{DepSpec, Lockfile, Package, Solver, Storage, VersionStrategy};
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitCode};
 
 /// Setup environment for package(s) and optionally run command.
@@ -19,45 +21,65 @@ pub fn cmd_env(
     output: Option<PathBuf>,
     dry_run: bool,
     stamp: bool,
+    no_deps: bool,
+    caps: Vec<String>,
+    rxt: Option<PathBuf>,
+    lock: Option<PathBuf>,
+    prefer: &str,
+    max_steps: Option<usize>,
+    before: Option<String>,
     verbose: bool,
 ) -> ExitCode {
-    if packages.is_empty() {
-        eprintln!("No packages specified");
-        return ExitCode::FAILURE;
-    }
+    let strategy: VersionStrategy = match prefer.parse() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
 
-    // Build effective package (single or ad-hoc toolset)
-    let mut pkg = if packages.len() == 1 {
-        let name = &packages[0];
-        match storage.resolve(name) {
-            Some(p) => p.clone(),
+    // --before: resolve against what was released by then
+    let dated;
+    let storage = match &before {
+        Some(when) => match parse_timestamp(when) {
+            Some(cutoff) => {
+                dated = storage.released_before(cutoff);
+                &dated
+            }
             None => {
-                eprintln!("Package not found: {}", name);
+                eprintln!(
+                    "Invalid --before time (expected ISO 8601 like 2026-03-10T14:00:00Z, or @<unix seconds>): {}",
+                    when
+                );
                 return ExitCode::FAILURE;
             }
-        }
-    } else {
-        // Multiple packages - create ad-hoc toolset
-        let mut adhoc = Package::new("_adhoc".to_string(), "0.0.0".to_string());
-        for name in &packages {
-            adhoc.add_req(name.clone());
-        }
-        adhoc
+        },
+        None => storage,
     };
 
-    // Solve dependencies
-    if !pkg.reqs.is_empty() {
-        if let Err(e) = pkg.solve(storage.packages()) {
-            eprintln!("Failed to solve dependencies: {}", e);
-            return ExitCode::FAILURE;
-        }
-    }
+    // --lock: requests and caps come from the (re-checked) lockfile
+    let (packages, caps) = match &lock {
+        Some(path) => match locked_requests(storage, path, &packages, caps) {
+            Ok(found) => found,
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => (packages, caps),
+    };
 
-    let env_name_ref = env_name.as_deref().unwrap_or("default");
-    let env = pkg._env(env_name_ref, true).or_else(|| pkg.default_env());
-    let Some(mut env) = env else {
-        eprintln!("Environment not found: {}", env_name_ref);
+    if packages.is_empty() {
+        eprintln!("No packages specified");
         return ExitCode::FAILURE;
+    }
+
+    let (pkg, mut env) = match package_env(storage, &packages, env_name.as_deref(), no_deps, &caps, strategy, max_steps) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
     };
 
     // Add PKG_* stamp variables for each resolved package
@@ -76,7 +98,7 @@ pub fn cmd_env(
 
     // Expand {TOKEN} references if requested
     if expand {
-        match env.solve_impl(10, true) {
+        match env.solve_impl(pkg_lib::env::DEFAULT_MAX_DEPTH, true) {
             Ok(solved) => env = solved,
             Err(e) => {
                 eprintln!("Failed to solve environment: {}", e);
@@ -85,6 +107,28 @@ pub fn cmd_env(
         }
     }
 
+    // Never emit blacklisted variables (LD_PRELOAD, ...) in any mode
+    let (filtered, skipped) = env.without_names(&pkg_lib::Env::default_blacklist());
+    for name in &skipped {
+        eprintln!("Skipping blacklisted variable {}", name);
+    }
+    env = filtered;
+
+    // Write rez-compatible context if --rxt specified
+    if let Some(path) = rxt {
+        let mut resolved = Vec::with_capacity(pkg.deps.len() + 1);
+        if pkg.base != "_adhoc" {
+            resolved.push(pkg.clone());
+        }
+        resolved.extend(pkg.deps.iter().cloned());
+        let snapshot = pkg_lib::rxt::build_snapshot(storage, &packages, &resolved, &env);
+        if let Err(e) = pkg_lib::rxt::write(&path, &snapshot) {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+        eprintln!("Context written to: {}", path.display());
+    }
+
     // Run mode: execute command with environment
     if !command.is_empty() {
         return run_with_env(&pkg, &env, &command, dry_run, verbose);
@@ -107,6 +151,218 @@ pub fn cmd_env(
     ExitCode::SUCCESS
 }
 
+/// Requests and caps that reproduce a lockfile.
+///
+/// The lock is first re-checked with [`Solver::solve_locked`]. Each locked
+/// requirement is then requested at its pinned version and every pin becomes
+/// an exact cap, so the regular solve lands on the locked set. `caps` from
+/// the command line are kept.
+fn locked_requests(
+    storage: &Storage,
+    path: &Path,
+    packages: &[String],
+    mut caps: Vec<String>,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    if !packages.is_empty() {
+        return Err("--lock takes its packages from the lockfile".to_string());
+    }
+    let lock = Lockfile::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let solver = Solver::from_packages(&storage.all_packages())
+        .map_err(|e| format!("Failed to build solver: {}", e))?;
+    solver.solve_locked(&lock).map_err(|e| format!("Lockfile {}: {}", path.display(), e))?;
+
+    let mut requests = Vec::with_capacity(lock.requirements.len());
+    for req in &lock.requirements {
+        let spec = DepSpec::parse_impl(req).map_err(|e| e.to_string())?;
+        let version = &lock.pins[&spec.base];
+        let extras = if spec.extras.is_empty() {
+            String::new()
+        } else {
+            format!("[{}]", spec.extras.join(","))
+        };
+        requests.push(format!("{}{}-{}", spec.base, extras, version));
+    }
+    caps.extend(lock.pins.iter().map(|(base, version)| format!("{}@{}", base, version)));
+    Ok((requests, caps))
+}
+
+/// Effective package and its named env (falls back to the default env).
+///
+/// With `no_deps` nothing is solved: a single package contributes only its
+/// own env, an ad-hoc toolset merges each member's own env. `caps` bound
+/// dependency versions without requiring them (ignored with `no_deps`).
+fn package_env(
+    storage: &Storage,
+    packages: &[String],
+    env_name: Option<&str>,
+    no_deps: bool,
+    caps: &[String],
+    strategy: VersionStrategy,
+    max_steps: Option<usize>,
+) -> Result<(Package, pkg_lib::Env), String> {
+    let pkg = if no_deps {
+        lookup_package(storage, packages)?
+    } else {
+        resolve_package_capped(storage, packages, caps, strategy, max_steps)?
+    };
+
+    let env_name = env_name.unwrap_or("default");
+    let with_deps = !no_deps || packages.len() > 1;
+    let env = pkg._env(env_name, with_deps).or_else(|| pkg.default_env());
+    match env {
+        Some(env) => Ok((pkg, env)),
+        None => Err(format!("Environment not found: {}", env_name)),
+    }
+}
+
+/// Like [`resolve_package`] but without solving.
+///
+/// A single package is returned as-is; several become an ad-hoc toolset
+/// whose deps are exactly the requested packages. Extras are ignored.
+fn lookup_package(storage: &Storage, packages: &[String]) -> Result<Package, String> {
+    let mut found = Vec::new();
+    for request in packages {
+        let (name, _) = split_request(request)?;
+        match storage.resolve(&name) {
+            Some(p) => found.push(p),
+            None => return Err(format!("Package not found: {}", name)),
+        }
+    }
+    if found.len() == 1 {
+        return Ok(found.remove(0));
+    }
+
+    let mut adhoc = Package::new("_adhoc".to_string(), "0.0.0".to_string());
+    for p in &found {
+        adhoc.add_req(p.base.clone());
+    }
+    adhoc.deps = found;
+    Ok(adhoc)
+}
+
+/// Build effective package (single or ad-hoc toolset) and solve its dependencies.
+///
+/// Names may request extras (`maya[gpu]`); their requirement groups are
+/// merged in before solving. In an ad-hoc toolset the groups are taken
+/// from the version `storage.resolve` picks for the name.
+pub(super) fn resolve_package(storage: &Storage, packages: &[String]) -> Result<Package, String> {
+    resolve_package_capped(storage, packages, &[], VersionStrategy::Newest, None)
+}
+
+/// Like [`resolve_package`], holding dependencies within `caps` (`ocio@<3`).
+///
+/// Caps are constraint-only: a capped base is bounded if something pulls
+/// it in, but isn't added to the resolve otherwise. With
+/// [`VersionStrategy::Oldest`] or a `max_steps` budget the versions are
+/// picked up front and the regular solve is capped to exactly those.
+fn resolve_package_capped(
+    storage: &Storage,
+    packages: &[String],
+    caps: &[String],
+    strategy: VersionStrategy,
+    max_steps: Option<usize>,
+) -> Result<Package, String> {
+    let mut pkg = if packages.len() == 1 {
+        let (name, extras) = split_request(&packages[0])?;
+        let mut pkg = match storage.resolve(&name) {
+            Some(p) => p.clone(),
+            None => return Err(format!("Package not found: {}", name)),
+        };
+        pkg.reqs = pkg.reqs_with_extras(&extras).map_err(|e| e.to_string())?;
+        pkg
+    } else {
+        // Multiple packages - create ad-hoc toolset
+        let mut adhoc = Package::new("_adhoc".to_string(), "0.0.0".to_string());
+        for request in packages {
+            let (name, extras) = split_request(request)?;
+            adhoc.add_req(name.clone());
+            if extras.is_empty() {
+                continue;
+            }
+            let Some(target) = storage.resolve(&name) else {
+                return Err(format!("Package not found: {}", name));
+            };
+            let reqs = target.reqs_with_extras(&extras).map_err(|e| e.to_string())?;
+            for req in reqs.into_iter().skip(target.reqs.len()) {
+                adhoc.add_req(req);
+            }
+        }
+        adhoc
+    };
+
+    let picked;
+    let presolve = strategy == VersionStrategy::Oldest || max_steps.is_some();
+    let caps = if presolve && !pkg.reqs.is_empty() {
+        picked = picked_caps(storage, &pkg.reqs, caps, strategy, max_steps)?;
+        picked.as_slice()
+    } else {
+        caps
+    };
+
+    // Solve dependencies
+    if !pkg.reqs.is_empty() {
+        if caps.is_empty() {
+            pkg.solve(storage.packages())
+                .map_err(|e| format!("Failed to solve dependencies: {}", e))?;
+        } else {
+            let available = storage.packages();
+            pkg.solve_capped_impl(&available, caps)
+                .map_err(|e| format!("Failed to solve dependencies: {}", e))?;
+            pkg.solve_deps_impl(&available)
+                .map_err(|e| format!("Failed to solve dependencies: {}", e))?;
+        }
+    }
+
+    Ok(pkg)
+}
+
+/// Exact `base@version` caps for resolving `reqs` per `strategy` within
+/// `max_steps`.
+fn picked_caps(
+    storage: &Storage,
+    reqs: &[String],
+    caps: &[String],
+    strategy: VersionStrategy,
+    max_steps: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let solver = Solver::from_packages(&storage.packages())
+        .map_err(|e| format!("Failed to build solver: {}", e))?;
+    let solution = solver
+        .solve_requirements_capped_with_impl(reqs, caps, strategy, max_steps)
+        .map_err(|e| format!("Failed to solve dependencies: {}", e))?;
+    solution
+        .iter()
+        .map(|name| {
+            let (base, version) = Package::parse_name(name).map_err(|e| e.to_string())?;
+            Ok(format!("{}@{}", base, version))
+        })
+        .collect()
+}
+
+/// Split `maya[gpu]@2026` into the storage query `maya@2026` and its extras.
+fn split_request(request: &str) -> Result<(String, Vec<String>), String> {
+    let split = split_extras(request.trim()).map_err(|e| e.to_string())?;
+    Ok(split.unwrap_or_else(|| (request.to_string(), Vec::new())))
+}
+
+/// Print what applying `env` would change in the current environment.
+///
+/// `+` new variable, `~` changed, `-` unset. Secret values are masked.
+fn print_os_diff(env: &pkg_lib::Env) {
+    println!("Environment changes:");
+    for (name, old, new) in env.diff_os() {
+        let evars = env.get_all(&name);
+        let mask = |value: &str| {
+            if evars.iter().any(|e| e.secret) { pkg_lib::evar::SECRET_MASK.to_string() } else { value.to_string() }
+        };
+        match old {
+            _ if evars.last().is_some_and(|e| e.is_unset()) => println!("  - {}", name),
+            None => println!("  + {}={}", name, mask(&new)),
+            Some(old) => println!("  ~ {}: {} -> {}", name, mask(&old), mask(&new)),
+        }
+    }
+}
+
 /// Run command with environment applied.
 fn run_with_env(
     pkg: &Package,
@@ -115,6 +371,10 @@ fn run_with_env(
     dry_run: bool,
     verbose: bool,
 ) -> ExitCode {
+    // Arguments may reference {TOKEN}s from the launch env (process + package env)
+    let mut launch_vars: std::collections::HashMap<String, String> = std::env::vars().collect();
+    launch_vars.extend(env.to_map());
+
     let (exe_path, args) = if command.is_empty() {
         // No command: use package's default app
         let app = pkg._app(&pkg.base, true).or_else(|| pkg.default_app());
@@ -126,19 +386,29 @@ fn run_with_env(
             eprintln!("No executable path for app: {}", app.name);
             return ExitCode::FAILURE;
         };
-        (path.clone(), app.build_args(None))
+        (path.clone(), app.expand_args(&app.build_args(None), &launch_vars))
     } else {
-        (command[0].clone(), command[1..].to_vec())
+        // Braces may be the command's own syntax: only expand known vars
+        let args = pkg_lib::App::expand_known_args(&command[1..], &launch_vars);
+        (command[0].clone(), Ok(args))
+    };
+    let args = match args {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
     };
 
-    if dry_run || verbose {
+    if verbose {
         println!("Environment:");
         for evar in env.evars_sorted() {
-            println!("  {}={}", evar.name, evar.value);
+            println!("  {}={}", evar.name, evar.display_value());
         }
     }
 
     if dry_run {
+        print_os_diff(env);
         println!("\nWould run: {} {:?}", exe_path, args);
         return ExitCode::SUCCESS;
     }
@@ -176,37 +446,71 @@ fn run_with_env(
 }
 
 /// Generate env output for display.
-fn generate_env_output(env: &pkg_lib::Env, format: &str) -> String {
+pub(crate) fn generate_env_output(env: &pkg_lib::Env, format: &str) -> String {
     let mut out = String::new();
     match format {
         "json" => {
             out = env.to_json().unwrap_or_default();
         }
+        "dotenv" => {
+            out = env.to_dotenv();
+            out.push('\n');
+        }
         "export" => {
-            for evar in env.evars_sorted() {
-                out.push_str(&format!("export {}=\"{}\"\n", evar.name, evar.value));
-            }
+            // Eval'd by `pkg-activate`: values single-quoted, bad names skipped
+            let sorted = pkg_lib::Env::from_evars(env.name.clone(), env.evars_sorted().into_iter().cloned());
+            out = sorted.to_sh();
+            out.push('\n');
         }
         "set" => {
             for evar in env.evars_sorted() {
-                out.push_str(&format!("set {}={}\n", evar.name, evar.value));
+                if evar.is_unset() {
+                    out.push_str(&format!("set {}=\n", evar.name));
+                } else {
+                    out.push_str(&format!("set {}={}\n", evar.name, evar.value));
+                }
             }
         }
+        "fish" => {
+            let sorted = pkg_lib::Env::from_evars(env.name.clone(), env.evars_sorted().into_iter().cloned());
+            out = sorted.to_fish();
+            out.push('\n');
+        }
+        "powershell" => {
+            let sorted = pkg_lib::Env::from_evars(env.name.clone(), env.evars_sorted().into_iter().cloned());
+            out = sorted.to_ps1(Some(false));
+            out.push('\n');
+        }
         _ => {
+            // Human-readable: mask secrets (export formats keep real values)
             for evar in env.evars_sorted() {
-                out.push_str(&format!("{}={}\n", evar.name, evar.value));
+                out.push_str(&format!("{}={}\n", evar.name, evar.display_value()));
             }
         }
     }
     out
 }
 
+/// Generate a `.fish` script.
+fn generate_fish_script(env: &pkg_lib::Env) -> String {
+    let sorted = pkg_lib::Env::from_evars(env.name.clone(), env.evars_sorted().into_iter().cloned());
+    format!(
+        "# Environment for {}\n# Generated by pkg v{}\n\n{}\n",
+        env.name,
+        pkg_lib::VERSION,
+        sorted.to_fish()
+    )
+}
+
 /// Generate platform-specific script based on file extension.
 fn generate_env_script(env: &pkg_lib::Env, path: &std::path::Path) -> String {
     let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
     let mut out = String::new();
     
     match ext {
+        "fish" => {
+            out = generate_fish_script(env);
+        }
         "cmd" | "bat" => {
             out.push_str("@echo off\n");
             out.push_str(&format!("REM Environment for {}\n", env.name));
@@ -217,9 +521,12 @@ fn generate_env_script(env: &pkg_lib::Env, path: &std::path::Path) -> String {
                     "append" => {
                         out.push_str(&format!("set {}=%{}%;{}\n", evar.name, evar.name, evar.value));
                     }
-                    "insert" => {
+                    "insert" | "prepend" => {
                         out.push_str(&format!("set {}={};%{}%\n", evar.name, evar.value, evar.name));
                     }
+                    "unset" => {
+                        out.push_str(&format!("set {}=\n", evar.name));
+                    }
                     _ => {
                         out.push_str(&format!("set {}={}\n", evar.name, evar.value));
                     }
@@ -229,17 +536,20 @@ fn generate_env_script(env: &pkg_lib::Env, path: &std::path::Path) -> String {
         "ps1" => {
             out.push_str(&format!("# Environment for {}\n", env.name));
             out.push_str(&format!("# Generated by pkg v{}\n\n", pkg_lib::VERSION));
-            for evar in env.evars_sorted() {
+            for evar in env.evars_sorted().into_iter().filter(|e| is_shell_name(&e.name)) {
                 let action = evar.action();
                 match action {
                     "append" => {
-                        out.push_str(&format!("$env:{} = \"$env:{};{}\"\n", evar.name, evar.name, evar.value));
+                        out.push_str(&format!("$env:{} = \"$env:{};{}\"\n", evar.name, evar.name, ps1_escape(&evar.value)));
+                    }
+                    "insert" | "prepend" => {
+                        out.push_str(&format!("$env:{} = \"{};$env:{}\"\n", evar.name, ps1_escape(&evar.value), evar.name));
                     }
-                    "insert" => {
-                        out.push_str(&format!("$env:{} = \"{};$env:{}\"\n", evar.name, evar.value, evar.name));
+                    "unset" => {
+                        out.push_str(&format!("Remove-Item Env:{} -ErrorAction SilentlyContinue\n", evar.name));
                     }
                     _ => {
-                        out.push_str(&format!("$env:{} = \"{}\"\n", evar.name, evar.value));
+                        out.push_str(&format!("$env:{} = \"{}\"\n", evar.name, ps1_escape(&evar.value)));
                     }
                 }
             }
@@ -248,17 +558,21 @@ fn generate_env_script(env: &pkg_lib::Env, path: &std::path::Path) -> String {
             out.push_str("#!/bin/bash\n");
             out.push_str(&format!("# Environment for {}\n", env.name));
             out.push_str(&format!("# Generated by pkg v{}\n\n", pkg_lib::VERSION));
-            for evar in env.evars_sorted() {
+            for evar in env.evars_sorted().into_iter().filter(|e| is_shell_name(&e.name)) {
                 let action = evar.action();
+                let value = sh_quote(&evar.value);
                 match action {
                     "append" => {
-                        out.push_str(&format!("export {}=\"${}:{}\"\n", evar.name, evar.name, evar.value));
+                        out.push_str(&format!("export {}=\"${}:\"{}\n", evar.name, evar.name, value));
                     }
-                    "insert" => {
-                        out.push_str(&format!("export {}=\"{}:${}\"\n", evar.name, evar.value, evar.name));
+                    "insert" | "prepend" => {
+                        out.push_str(&format!("export {}={}\":${}\"\n", evar.name, value, evar.name));
+                    }
+                    "unset" => {
+                        out.push_str(&format!("unset {}\n", evar.name));
                     }
                     _ => {
-                        out.push_str(&format!("export {}=\"{}\"\n", evar.name, evar.value));
+                        out.push_str(&format!("export {}={}\n", evar.name, value));
                     }
                 }
             }
@@ -266,3 +580,244 @@ fn generate_env_script(env: &pkg_lib::Env, path: &std::path::Path) -> String {
     }
     out
 }
+
+/// Unix seconds for an ISO 8601 date or date-time (`2026`, `2026-03-10`,
+/// `2026-03-10T14:00:00Z`, `2026-03-10T16:00+02:00`; UTC unless an offset
+/// is given) or an explicit `@<unix seconds>`.
+fn parse_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Some(secs) = value.strip_prefix('@') {
+        return secs.parse().ok();
+    }
+    let (date, time) = match value.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    // Reduced precision (`2026`, `2026-03`) means the start of the period
+    let mut fields = date.split('-');
+    let year = parse_digits(fields.next()?, 4)?;
+    let month = fields.next().map_or(Some(1), |m| parse_digits(m, 2))?;
+    let day = fields.next().map_or(Some(1), |d| parse_digits(d, 2))?;
+    if fields.next().is_some() || !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+
+    let mut secs = days_from_civil(year, month, day) * 86_400;
+    if let Some(time) = time {
+        let (clock, offset) = split_offset(time)?;
+        let fields: Vec<i64> = clock.split(':').map(|p| parse_digits(p, 2)).collect::<Option<_>>()?;
+        let (hour, minute, second) = match fields[..] {
+            [hour, minute] => (hour, minute, 0),
+            [hour, minute, second] => (hour, minute, second),
+            _ => return None,
+        };
+        if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..=60).contains(&second) {
+            return None;
+        }
+        secs += hour * 3600 + minute * 60 + second - offset;
+    }
+    Some(secs)
+}
+
+/// Split a trailing `Z` or `+HH:MM` / `+HHMM` / `+HH` offset off a time.
+///
+/// # Returns
+/// (clock part, offset in seconds east of UTC; 0 without one)
+fn split_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(clock) = time.strip_suffix('Z') {
+        return Some((clock, 0));
+    }
+    let Some(at) = time.find(['+', '-']) else {
+        return Some((time, 0));
+    };
+    let (clock, offset) = time.split_at(at);
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let offset = &offset[1..];
+    let (hours, minutes) = match offset.split_once(':') {
+        Some(parts) => parts,
+        None if offset.len() == 4 => offset.split_at(2),
+        None => (offset, "00"),
+    };
+    let (hours, minutes) = (parse_digits(hours, 2)?, parse_digits(minutes, 2)?);
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some((clock, sign * (hours * 3600 + minutes * 60)))
+}
+
+/// Exactly `len` ASCII digits as a number.
+fn parse_digits(value: &str, len: usize) -> Option<i64> {
+    if value.len() != len || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Number of days in `month` (1-12) of a proleptic Gregorian `year`.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn storage() -> Storage {
+        let mut storage = Storage::empty();
+        let mut maya = Package::new("maya".to_string(), "2026.1.0".to_string());
+        maya.add_req("ocio".to_string());
+        maya.extras = BTreeMap::from([("gpu".to_string(), vec!["cuda@12".to_string()])]);
+        storage.add(maya);
+        storage.add(Package::new("ocio".to_string(), "2.3.0".to_string()));
+        storage.add(Package::new("cuda".to_string(), "12.4.0".to_string()));
+        storage
+    }
+
+    fn dep_bases(pkg: &Package) -> Vec<String> {
+        let mut bases: Vec<String> = pkg.deps.iter().map(|d| d.base.clone()).collect();
+        bases.sort();
+        bases
+    }
+
+    #[test]
+    fn resolve_package_extras() {
+        let storage = storage();
+
+        let plain = resolve_package(&storage, &["maya".to_string()]).unwrap();
+        assert_eq!(dep_bases(&plain), ["ocio"]);
+
+        let gpu = resolve_package(&storage, &["maya[gpu]".to_string()]).unwrap();
+        assert_eq!(gpu.reqs, ["ocio", "cuda@12"]);
+        assert_eq!(dep_bases(&gpu), ["cuda", "ocio"]);
+
+        // Ad-hoc toolset picks up the group too
+        let adhoc = resolve_package(&storage, &["maya[gpu]".to_string(), "ocio".to_string()]).unwrap();
+        assert!(dep_bases(&adhoc).contains(&"cuda".to_string()));
+
+        assert!(resolve_package(&storage, &["maya[vr]".to_string()]).is_err());
+    }
+
+    #[test]
+    fn resolve_package_caps() {
+        let mut storage = storage();
+        storage.add(Package::new("ocio".to_string(), "3.0.0".to_string()));
+        let ocio = |pkg: &Package| pkg.deps.iter().find(|d| d.base == "ocio").map(|d| d.version.clone());
+
+        let latest = resolve_package(&storage, &["maya".to_string()]).unwrap();
+        assert_eq!(ocio(&latest).as_deref(), Some("3.0.0"));
+
+        let caps = ["ocio@<3".to_string(), "cuda@<12".to_string()];
+        let capped = resolve_package_capped(&storage, &["maya".to_string()], &caps, VersionStrategy::Newest, None).unwrap();
+        assert_eq!(ocio(&capped).as_deref(), Some("2.3.0"));
+        // Cap alone doesn't pull cuda in
+        assert!(capped.deps.iter().all(|d| d.base != "cuda"));
+
+        // Oldest-first picks the floor, still within caps
+        storage.add(Package::new("ocio".to_string(), "2.0.0".to_string()));
+        let oldest = resolve_package_capped(&storage, &["maya".to_string()], &[], VersionStrategy::Oldest, None).unwrap();
+        assert_eq!(ocio(&oldest).as_deref(), Some("2.0.0"));
+
+        // A generous budget changes nothing, a tiny one aborts
+        let budgeted = resolve_package_capped(&storage, &["maya".to_string()], &[], VersionStrategy::Newest, Some(100)).unwrap();
+        assert_eq!(ocio(&budgeted).as_deref(), Some("3.0.0"));
+        let err = resolve_package_capped(&storage, &["maya".to_string()], &[], VersionStrategy::Newest, Some(1)).unwrap_err();
+        assert!(err.contains("budget"), "{}", err);
+    }
+
+    #[test]
+    fn package_env_no_deps() {
+        let mut storage = Storage::empty();
+        let mut maya = Package::new("maya".to_string(), "2026.1.0".to_string());
+        maya.add_req("ocio".to_string());
+        let mut env = pkg_lib::Env::new("default".to_string());
+        env.add(pkg_lib::Evar::set("MAYA_ROOT", "/opt/maya"));
+        maya.add_env(env);
+        storage.add(maya);
+
+        let mut ocio = Package::new("ocio".to_string(), "2.3.0".to_string());
+        let mut env = pkg_lib::Env::new("default".to_string());
+        env.add(pkg_lib::Evar::set("OCIO", "/opt/ocio/config.ocio"));
+        ocio.add_env(env);
+        storage.add(ocio);
+
+        let packages = ["maya".to_string()];
+        let (_, full) = package_env(&storage, &packages, None, false, &[], VersionStrategy::Newest, None).unwrap();
+        assert!(full.get("OCIO").is_some());
+
+        let (pkg, own) = package_env(&storage, &packages, None, true, &[], VersionStrategy::Newest, None).unwrap();
+        assert!(pkg.deps.is_empty());
+        assert!(own.get("MAYA_ROOT").is_some());
+        assert!(own.get("OCIO").is_none());
+    }
+
+    #[test]
+    fn parse_timestamp_iso() {
+        assert_eq!(parse_timestamp("2026-01-01"), Some(1_767_225_600));
+        assert_eq!(parse_timestamp("2026"), Some(1_767_225_600));
+        assert_eq!(parse_timestamp("2024-02-29T12:30:15Z"), Some(1_709_209_815));
+        assert_eq!(parse_timestamp("2024-02-29 12:30"), Some(1_709_209_800));
+        assert_eq!(parse_timestamp("2024-02-29T14:30:15+02:00"), Some(1_709_209_815));
+        assert_eq!(parse_timestamp("2024-02-29T10:00:15-0230"), Some(1_709_209_815));
+        assert_eq!(parse_timestamp("@1709209815"), Some(1_709_209_815));
+        // Bare numbers are years or nothing, never unix seconds
+        assert_eq!(parse_timestamp("1709209815"), None);
+        assert_eq!(parse_timestamp("2024-13-01"), None);
+        assert_eq!(parse_timestamp("2026-02-29"), None);
+        assert_eq!(parse_timestamp("2026-04-31"), None);
+        assert_eq!(parse_timestamp("2024-02-29T12:30+25:00"), None);
+        assert_eq!(parse_timestamp("last tuesday"), None);
+    }
+
+    #[test]
+    fn resolve_package_before() {
+        let mut storage = storage();
+        let mut newer = Package::new("ocio".to_string(), "2.4.0".to_string());
+        newer.released = Some(parse_timestamp("2026-03-10").unwrap());
+        storage.add(newer);
+
+        let now = resolve_package(&storage, &["maya".to_string()]).unwrap();
+        assert!(now.deps.iter().any(|d| d.name == "ocio-2.4.0"));
+
+        let dated = storage.released_before(parse_timestamp("2026-03-09").unwrap());
+        let then = resolve_package(&dated, &["maya".to_string()]).unwrap();
+        assert!(then.deps.iter().any(|d| d.name == "ocio-2.3.0"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn export_output_is_eval_safe() {
+        let value = "$(touch pwned) `touch pwned` \"q\" it's \\";
+        let mut env = pkg_lib::Env::new("default".to_string());
+        env.add(pkg_lib::Evar::set("PKG_TEST_VALUE", value));
+        env.add(pkg_lib::Evar::set("BAD;touch pwned", "x"));
+        let script = generate_env_output(&env, "export");
+        assert!(!script.contains("BAD"), "{}", script);
+
+        // Run it the way `pkg-activate` does
+        let dir = tempfile::tempdir().unwrap();
+        let out = Command::new("sh")
+            .args(["-c", "eval \"$1\"; printf %s \"$PKG_TEST_VALUE\"", "sh", &script])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout), value);
+        assert!(!dir.path().join("pwned").exists());
+    }
+}
@@ -0,0 +1,162 @@
+//! Environment drift command.
+//!
+//! Resolves the environment for package(s) and compares it against a
+//! baseline saved earlier with `--save`:
+//!
+//! ```text
+//! pkg env-diff maya -b maya-env.json --save   # record baseline
+//! pkg env-diff maya -b maya-env.json          # report drift
+//! ```
+//!
+//! Secret values are never written or printed: the baseline stores a
+//! hash of them, and drift shows them masked.
+
+use super::env::resolve_package;
+use pkg_lib::evar::SECRET_MASK;
+use pkg_lib::{Env, Storage};
+use sha1::{Digest, Sha1};
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Prefix of hashed secret values in baselines.
+const SECRET_HASH_PREFIX: &str = "sha1:";
+
+/// Compare resolved environment against a baseline JSON file.
+///
+/// Exits with failure if any variable drifted.
+pub fn cmd_env_diff(
+    storage: &Storage,
+    packages: Vec<String>,
+    env_name: Option<String>,
+    baseline: &Path,
+    save: bool,
+) -> ExitCode {
+    if packages.is_empty() {
+        eprintln!("No packages specified");
+        return ExitCode::FAILURE;
+    }
+
+    let pkg = match resolve_package(storage, &packages) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let env_name_ref = env_name.as_deref().unwrap_or("default");
+    let Some(env) = pkg._env(env_name_ref, true).or_else(|| pkg.default_env()) else {
+        eprintln!("Environment not found: {}", env_name_ref);
+        return ExitCode::FAILURE;
+    };
+
+    // Expand tokens without OS fallback so baselines are machine-independent
+    let env = match env.solve_impl(pkg_lib::env::DEFAULT_MAX_DEPTH, false) {
+        Ok(solved) => baseline_env(&solved),
+        Err(e) => {
+            eprintln!("Failed to solve environment: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if save {
+        let json = match serde_json::to_string_pretty(&env) {
+            Ok(j) => j,
+            Err(e) => {
+                eprintln!("Failed to serialize environment: {}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(e) = std::fs::write(baseline, json) {
+            eprintln!("Failed to write {}: {}", baseline.display(), e);
+            return ExitCode::FAILURE;
+        }
+        eprintln!("Baseline written to: {}", baseline.display());
+        return ExitCode::SUCCESS;
+    }
+
+    let saved: Env = match std::fs::read_to_string(baseline)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(env) => env,
+        Err(e) => {
+            eprintln!("Failed to read baseline {}: {}", baseline.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let report = drift_report(&saved, &env);
+    if report.is_empty() {
+        println!("No drift");
+        return ExitCode::SUCCESS;
+    }
+    for line in report {
+        println!("{}", line);
+    }
+
+    ExitCode::FAILURE
+}
+
+/// Solved env as stored in a baseline: compressed, secret values
+/// replaced by a hash so changes are still detected.
+fn baseline_env(env: &Env) -> Env {
+    let mut env = env.compress();
+    for evar in env.evars.iter_mut().filter(|e| e.secret) {
+        evar.value = format!("{}{:x}", SECRET_HASH_PREFIX, Sha1::digest(evar.value.as_bytes()));
+    }
+    env
+}
+
+/// Drift lines between a baseline and the current env (empty if none).
+/// Secret values are shown masked.
+fn drift_report(saved: &Env, current: &Env) -> Vec<String> {
+    let cmp = saved.compare(current);
+    let mut lines = Vec::new();
+    for name in &cmp.only_self {
+        lines.push(format!("- {}", name));
+    }
+    for name in &cmp.only_other {
+        lines.push(format!("+ {}", name));
+    }
+    for (name, old, new) in &cmp.differing {
+        let secret = [saved, current]
+            .iter()
+            .any(|env| env.get(name).is_some_and(|e| e.secret));
+        let shown = |value: &str| if secret { SECRET_MASK.to_string() } else { value.to_string() };
+        lines.push(format!("~ {}", name));
+        lines.push(format!("    baseline: {}", shown(old)));
+        lines.push(format!("    current:  {}", shown(new)));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pkg_lib::Evar;
+
+    fn env_with_token(token: &str) -> Env {
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("ROOT", "/opt/maya"));
+        let mut secret = Evar::set("TOKEN", token);
+        secret.secret = true;
+        env.add(secret);
+        env
+    }
+
+    #[test]
+    fn env_diff_keeps_secrets_out() {
+        let saved = baseline_env(&env_with_token("s3cr3t-old"));
+        let json = serde_json::to_string_pretty(&saved).unwrap();
+        assert!(!json.contains("s3cr3t-old"), "{}", json);
+        assert!(json.contains(SECRET_HASH_PREFIX));
+
+        // Same secret: no drift; changed secret: reported, but masked
+        assert!(drift_report(&saved, &baseline_env(&env_with_token("s3cr3t-old"))).is_empty());
+        let report = drift_report(&saved, &baseline_env(&env_with_token("s3cr3t-new"))).join("\n");
+        assert!(report.contains("~ TOKEN"), "{}", report);
+        assert!(report.contains(SECRET_MASK));
+        assert!(!report.contains("s3cr3t") && !report.contains(SECRET_HASH_PREFIX), "{}", report);
+    }
+}
@@ -0,0 +1,48 @@
+//! Freeze command.
+//!
+//! Solves requirements and prints one exact `base-version` line per resolved
+//! package, like `pip freeze`. The output can be fed back as requirements.
+
+use pkg_lib::{Solver, Storage};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Print the exact resolution of the given requirements.
+pub fn cmd_freeze(storage: &Storage, targets: Vec<String>, output: Option<PathBuf>) -> ExitCode {
+    if targets.is_empty() {
+        eprintln!("No packages specified");
+        return ExitCode::FAILURE;
+    }
+
+    let solver = match Solver::from_packages(&storage.all_packages()) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to build solver: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let frozen = match solver.freeze_impl(&targets) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Failed to solve dependencies: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut text = frozen.join("\n");
+    text.push('\n');
+
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, &text) {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+            eprintln!("Written to: {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+
+    ExitCode::SUCCESS
+}
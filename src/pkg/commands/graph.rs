@@ -1,17 +1,25 @@
 //! Graph visualization command.
 
-use pkg_lib::{Package, Storage};
+use pkg_lib::{Package, Solver, Storage};
 use std::collections::HashSet;
 use std::process::ExitCode;
 
 /// Show dependency graph in DOT or Mermaid format.
+///
+/// With `order = Some("topo")` prints the resolved packages instead, one per
+/// line, dependencies before dependents.
 pub fn cmd_graph(
     storage: &Storage,
     packages: Vec<String>,
     format: &str,
     max_depth: usize,
     reverse: bool,
+    order: Option<&str>,
 ) -> ExitCode {
+    if order == Some("topo") {
+        return print_topo(storage, &packages);
+    }
+
     let mut edges: Vec<(String, String)> = Vec::new();
     let mut visited: HashSet<String> = HashSet::new();
     let mut roots: Vec<String> = Vec::new();
@@ -51,6 +59,43 @@ pub fn cmd_graph(
     ExitCode::SUCCESS
 }
 
+/// Print each package's resolve in install order, skipping repeats.
+fn print_topo(storage: &Storage, packages: &[String]) -> ExitCode {
+    if packages.is_empty() {
+        eprintln!("--order topo needs at least one package");
+        return ExitCode::FAILURE;
+    }
+
+    let solver = match Solver::from_packages(&storage.all_packages()) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to build solver: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut printed = HashSet::new();
+    for name in packages {
+        let Some(pkg) = storage.resolve(name) else {
+            eprintln!("Package not found: {}", name);
+            return ExitCode::FAILURE;
+        };
+        match solver.solve_ordered_impl(&pkg.name) {
+            Ok(ordered) => {
+                for name in ordered.into_iter().filter(|n| printed.insert(n.clone())) {
+                    println!("{}", name);
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to solve dependencies: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
 /// Collect forward dependencies recursively.
 fn collect_deps(
     storage: &Storage,
@@ -1,17 +1,41 @@
 //! Show package info command.
 
-use pkg_lib::Storage;
+use pkg_lib::loader::parse_load_args;
+use pkg_lib::{DepSpec, Loader, Solver, Storage};
+use std::collections::HashSet;
+use std::path::Path;
 use std::process::ExitCode;
 
 /// Show detailed package information.
-pub fn cmd_info(storage: &Storage, package: &str, json: bool) -> ExitCode {
+///
+/// With `load_args`, the package's package.py is loaded again with them
+/// passed to `get_package(*args, **kwargs)`.
+pub fn cmd_info(storage: &Storage, package: &str, json: bool, load_args: &[String]) -> ExitCode {
     let pkg = storage.resolve(package);
 
-    let Some(pkg) = pkg else {
+    let Some(mut pkg) = pkg else {
         eprintln!("Package not found: {}", package);
         return ExitCode::FAILURE;
     };
 
+    if !load_args.is_empty() {
+        let Some(source) = pkg.package_source.clone() else {
+            eprintln!("{} has no package.py to load with arguments", pkg.name);
+            return ExitCode::FAILURE;
+        };
+        let (args, kwargs) = parse_load_args(load_args);
+        pkg = match Loader::new(Some(false)).load_path_with_args(Path::new(&source), args, kwargs) {
+            Ok(mut p) => {
+                p.package_source = Some(source.clone());
+                p
+            }
+            Err(e) => {
+                eprintln!("Failed to load {}: {}", source, e);
+                return ExitCode::FAILURE;
+            }
+        };
+    }
+
     if json {
         println!("{}", pkg.to_json_pretty().unwrap_or_default());
     } else {
@@ -19,6 +43,28 @@ pub fn cmd_info(storage: &Storage, package: &str, json: bool) -> ExitCode {
         println!("  Base: {}", pkg.base);
         println!("  Version: {}", pkg.version);
 
+        // Only interesting for override setups
+        let origins = storage.version_origins(&pkg.base);
+        let distinct: HashSet<_> = origins.iter().map(|(_, loc)| loc).collect();
+        if distinct.len() > 1 {
+            println!("  Origins:");
+            for (name, loc) in &origins {
+                println!("    - {}: {}", name, loc.display());
+            }
+        }
+
+        // Constraint given: every version it matches, not just the pick
+        if DepSpec::parse_impl(package).is_ok_and(|spec| !spec.is_any()) {
+            print_matching(storage, package);
+        }
+
+        if pkg.deprecated {
+            match &pkg.deprecated_reason {
+                Some(reason) => println!("  Deprecated: {}", reason),
+                None => println!("  Deprecated: yes"),
+            }
+        }
+
         if !pkg.reqs.is_empty() {
             println!("  Requirements:");
             for req in &pkg.reqs {
@@ -44,3 +90,18 @@ pub fn cmd_info(storage: &Storage, package: &str, json: bool) -> ExitCode {
 
     ExitCode::SUCCESS
 }
+
+/// Print all versions `req` matches, marking deprecated ones.
+fn print_matching(storage: &Storage, req: &str) {
+    let matching = Solver::from_packages(&storage.packages()).and_then(|s| s.available_for_impl(req));
+    match matching {
+        Ok(names) => {
+            println!("  Matching versions:");
+            for name in names {
+                let deprecated = storage.get(&name).is_some_and(|p| p.deprecated);
+                println!("    - {}{}", name, if deprecated { " (deprecated)" } else { "" });
+            }
+        }
+        Err(e) => eprintln!("Failed to list matching versions: {}", e),
+    }
+}
@@ -41,14 +41,18 @@ fn matches_glob_impl(pattern: &[u8], text: &[u8]) -> bool {
 }
 
 /// List packages with optional filtering.
+///
+/// Hidden packages are shown only with `all`.
 pub fn cmd_list(
     storage: &Storage,
     patterns: Vec<String>,
     tags: Vec<String>,
     latest: bool,
     json: bool,
+    all: bool,
 ) -> ExitCode {
-    let all_packages = storage.packages();
+    // Tag filter: all specified tags must be present; hidden skipped unless --all
+    let all_packages = storage.list(Some(tags), all);
     let mut packages: Vec<&Package> = all_packages.iter().collect();
 
     // Filter by glob patterns (OR logic: any pattern matches)
@@ -60,11 +64,6 @@ pub fn cmd_list(
         });
     }
 
-    // Filter by tags (all specified tags must be present)
-    if !tags.is_empty() {
-        packages.retain(|p| tags.iter().all(|t| p.tags.contains(t)));
-    }
-
     // Sort by name
     packages.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -83,7 +82,8 @@ pub fn cmd_list(
         } else {
             println!("Available packages ({}):", packages.len());
             for pkg in packages {
-                println!("  {} ({})", pkg.name, pkg.base);
+                let mark = if pkg.deprecated { " [deprecated]" } else { "" };
+                println!("  {} ({}){}", pkg.name, pkg.base, mark);
             }
         }
     }
@@ -0,0 +1,51 @@
+//! Lock command.
+//!
+//! Solves requirements and writes a JSON lockfile (base → exact version)
+//! that `pkg env --lock` can re-check and reproduce later.
+
+use pkg_lib::{Solver, Storage};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Solve `targets` and print or write the lockfile.
+pub fn cmd_lock(storage: &Storage, targets: Vec<String>, output: Option<PathBuf>) -> ExitCode {
+    if targets.is_empty() {
+        eprintln!("No packages specified");
+        return ExitCode::FAILURE;
+    }
+
+    let solver = match Solver::from_packages(&storage.all_packages()) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to build solver: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let lock = match solver.solve_to_lock(&targets) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Failed to solve dependencies: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output {
+        Some(path) => {
+            if let Err(e) = lock.write(&path) {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+            eprintln!("Written to: {}", path.display());
+        }
+        None => match serde_json::to_string_pretty(&lock) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize lockfile: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+
+    ExitCode::SUCCESS
+}
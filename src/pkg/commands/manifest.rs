@@ -0,0 +1,49 @@
+//! Launch manifest command.
+//!
+//! Resolves a package and prints a JSON description of how to run one of
+//! its apps, for launcher services:
+//!
+//! ```text
+//! pkg manifest maya              # default app
+//! pkg manifest maya --app mayapy
+//! ```
+
+use super::env::resolve_package;
+use pkg_lib::Storage;
+use std::process::ExitCode;
+
+/// Print `{exe, args, cwd, env}` for a package's app as JSON.
+pub fn cmd_manifest(storage: &Storage, package: String, app: Option<String>) -> ExitCode {
+    let pkg = match resolve_package(storage, std::slice::from_ref(&package)) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(name) = &app {
+        if pkg._app(name, true).is_none() {
+            eprintln!("App not found: {}", name);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let manifest = match pkg.launch_manifest(&storage.all_packages(), app.as_deref()) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("Failed to resolve env: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match serde_json::to_string_pretty(&manifest) {
+        Ok(json) => {
+            println!("{}", json);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to serialize manifest: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
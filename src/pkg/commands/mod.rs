@@ -2,16 +2,44 @@
 
 mod list;
 mod info;
+mod diff;
 mod env;
+mod env_diff;
+mod freeze;
+mod lock;
+mod bundle;
+mod manifest;
+mod run;
+mod test;
+mod which;
 mod graph;
+mod normalize;
 mod scan;
+mod cache;
 mod generate;
 mod gen_pkg;
+mod shell_init;
+#[cfg(feature = "serve")]
+mod serve;
 
 pub use list::{cmd_list, matches_glob};
 pub use info::cmd_info;
-pub use env::cmd_env;
+pub use diff::cmd_diff;
+pub use env::{cmd_env, generate_env_output};
+pub use env_diff::cmd_env_diff;
+pub use freeze::cmd_freeze;
+pub use lock::cmd_lock;
+pub use bundle::cmd_bundle;
+pub use manifest::cmd_manifest;
+pub use run::cmd_run;
+pub use test::cmd_test;
+pub use which::cmd_which;
 pub use graph::cmd_graph;
+pub use normalize::cmd_normalize;
 pub use scan::cmd_scan;
+pub use cache::cmd_cache;
 pub use generate::cmd_generate_repo;
 pub use gen_pkg::cmd_gen_pkg;
+pub use shell_init::cmd_shell_init;
+#[cfg(feature = "serve")]
+pub use serve::cmd_serve;
@@ -0,0 +1,185 @@
+//! Normalize repository layout command.
+//!
+//! Finds package definitions (package.py, package.py.gz, package.toml)
+//! whose directory doesn't match the declared package (e.g.
+//! `ocio/2.1/package.py` declaring `2.1.0`) and, with `--write`, moves them
+//! to the canonical `base/version/` layout.
+
+use log::warn;
+use pkg_lib::storage::is_package_file;
+use pkg_lib::{Package, Storage};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// A package directory that should be moved to its canonical location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Move {
+    /// Full package name (base-version)
+    name: String,
+    /// Current directory containing the package definition
+    from: PathBuf,
+    /// Canonical directory: `<location>/<base>/<version>`
+    to: PathBuf,
+}
+
+/// Report (or fix with `write`) layout mismatches under a location.
+pub fn cmd_normalize(location: &Path, write: bool) -> ExitCode {
+    let storage = match Storage::scan_impl(Some(&[location.to_path_buf()])) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Scan failed: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let moves = plan_moves(location, storage.packages_iter());
+    if moves.is_empty() {
+        println!("Layout OK: {} package(s) checked", storage.count());
+        return ExitCode::SUCCESS;
+    }
+
+    let mut failed = false;
+    for mv in &moves {
+        println!("{}: {} -> {}", mv.name, mv.from.display(), mv.to.display());
+        if write {
+            if let Err(e) = apply_move(mv) {
+                eprintln!("  failed: {}", e);
+                failed = true;
+            }
+        }
+    }
+
+    if !write {
+        println!("{} mismatch(es). Run with --write to fix.", moves.len());
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Collect packages whose directory differs from `<location>/<base>/<version>`.
+///
+/// Toolsets and packages without a known definition file are skipped, as
+/// are directories defining several packages: there's no single canonical
+/// place to move them to.
+fn plan_moves<'a>(location: &Path, packages: impl Iterator<Item = &'a Package>) -> Vec<Move> {
+    // Packages by source directory; a multi-package file lists several
+    let mut by_dir: BTreeMap<PathBuf, Vec<&Package>> = BTreeMap::new();
+    for pkg in packages {
+        let Some(source) = pkg.package_source.as_deref().map(Path::new) else {
+            continue;
+        };
+        let is_definition = source.file_name().and_then(|n| n.to_str()).is_some_and(is_package_file);
+        if let (true, Some(dir)) = (is_definition, source.parent()) {
+            by_dir.entry(dir.to_path_buf()).or_default().push(pkg);
+        }
+    }
+
+    let mut moves: Vec<Move> = by_dir
+        .into_iter()
+        .filter_map(|(from, pkgs)| {
+            let [pkg] = pkgs.as_slice() else {
+                warn!("Skipping {}: defines {} packages", from.display(), pkgs.len());
+                return None;
+            };
+
+            let (base, version) = match Package::parse_name(&pkg.name) {
+                Ok(parts) => parts,
+                Err(e) => {
+                    warn!("Skipping {}: {}", pkg.name, e);
+                    return None;
+                }
+            };
+            let to = location.join(base).join(version);
+
+            (from != to).then(|| Move {
+                name: pkg.name.clone(),
+                from,
+                to,
+            })
+        })
+        .collect();
+    moves.sort_by(|a, b| a.name.cmp(&b.name));
+    moves
+}
+
+/// Move a package directory, refusing to overwrite an existing one.
+fn apply_move(mv: &Move) -> Result<(), String> {
+    if mv.to.exists() {
+        return Err(format!("{} already exists", mv.to.display()));
+    }
+    if let Some(parent) = mv.to.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::rename(&mv.from, &mv.to).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg_at(base: &str, version: &str, dir: &Path) -> Package {
+        pkg_in(base, version, &dir.join("package.py"))
+    }
+
+    fn pkg_in(base: &str, version: &str, file: &Path) -> Package {
+        let mut pkg = Package::new(base.to_string(), version.to_string());
+        pkg.package_source = Some(file.to_string_lossy().to_string());
+        pkg
+    }
+
+    #[test]
+    fn normalize_detects_and_fixes_mismatch() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let root = repo.path();
+
+        let good_dir = root.join("maya").join("2026.0.0");
+        let bad_dir = root.join("ocio").join("2.1");
+        for dir in [&good_dir, &bad_dir] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join("package.py"), "").unwrap();
+        }
+        let packages = [pkg_at("maya", "2026.0.0", &good_dir), pkg_at("ocio", "2.1.0", &bad_dir)];
+
+        let moves = plan_moves(root, packages.iter());
+        assert_eq!(
+            moves,
+            vec![Move {
+                name: "ocio-2.1.0".to_string(),
+                from: bad_dir.clone(),
+                to: root.join("ocio").join("2.1.0"),
+            }]
+        );
+
+        apply_move(&moves[0]).unwrap();
+        assert!(root.join("ocio").join("2.1.0").join("package.py").is_file());
+        assert!(!bad_dir.exists());
+
+        // Existing target is never overwritten
+        std::fs::create_dir_all(&bad_dir).unwrap();
+        assert!(apply_move(&moves[0]).is_err());
+    }
+
+    #[test]
+    fn normalize_other_definitions_and_multi_package_files() {
+        let root = Path::new("/repo");
+        let toml_dir = root.join("ocio").join("2.1");
+        let gz_dir = root.join("nuke").join("15");
+        let multi_dir = root.join("tools");
+        let packages = [
+            pkg_in("ocio", "2.1.0", &toml_dir.join("package.toml")),
+            pkg_in("nuke", "15.0.0", &gz_dir.join("package.py.gz")),
+            pkg_in("tool-a", "1.0.0", &multi_dir.join("package.py")),
+            pkg_in("tool-b", "1.0.0", &multi_dir.join("package.py")),
+            pkg_in("notes", "1.0.0", &root.join("notes").join("README.md")),
+        ];
+
+        let moves = plan_moves(root, packages.iter());
+        let planned: Vec<(&str, &Path)> = moves.iter().map(|m| (m.name.as_str(), m.from.as_path())).collect();
+        assert_eq!(planned, vec![("nuke-15.0.0", gz_dir.as_path()), ("ocio-2.1.0", toml_dir.as_path())]);
+    }
+}
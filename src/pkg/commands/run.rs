@@ -0,0 +1,64 @@
+//! Run command.
+//!
+//! Resolves a package and launches one of its apps with the solved env.
+//! The app's own `args` come before the extra arguments and it runs in
+//! the app's working directory:
+//!
+//! ```text
+//! pkg run maya                          # default app
+//! pkg run maya mayapy -- -c "print(1)"
+//! ```
+
+use super::env::resolve_package;
+use pkg_lib::{Env, Storage};
+use std::process::ExitCode;
+
+/// Launch `app` (default: the package's default app) and wait for it.
+pub fn cmd_run(storage: &Storage, package: String, app: Option<String>, args: Vec<String>) -> ExitCode {
+    let pkg = match resolve_package(storage, std::slice::from_ref(&package)) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let found = match &app {
+        Some(name) => pkg._app(name, true),
+        None => pkg.default_app(),
+    };
+    let Some(app) = found else {
+        eprintln!("App not found: {}", app.as_deref().unwrap_or(&pkg.base));
+        return ExitCode::FAILURE;
+    };
+
+    // _env with deps=true is already solved; launch layers env_overrides
+    let env_name = app.env_name.as_deref().unwrap_or("default");
+    let env = pkg
+        ._env(env_name, true)
+        .or_else(|| pkg.default_env())
+        .unwrap_or_else(|| Env::new(env_name.to_string()));
+    let (env, skipped) = env.without_names(&Env::default_blacklist());
+    for name in &skipped {
+        eprintln!("Skipping blacklisted variable {}", name);
+    }
+
+    let mut child = match app.launch(&env, &args) {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if app.detached {
+        return ExitCode::SUCCESS;
+    }
+    match child.wait() {
+        Ok(status) if status.success() => ExitCode::SUCCESS,
+        Ok(status) => ExitCode::from(status.code().unwrap_or(1) as u8),
+        Err(e) => {
+            eprintln!("Failed to wait for process: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! HTTP query server command.
+
+use pkg_lib::server::Server;
+use pkg_lib::Storage;
+use std::net::TcpListener;
+use std::process::ExitCode;
+
+/// Serve read-only JSON endpoints over HTTP until interrupted.
+pub fn cmd_serve(storage: Storage, host: &str, port: u16) -> ExitCode {
+    let server = match Server::new(storage) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to build solver index: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let listener = match TcpListener::bind((host, port)) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Failed to bind {}:{}: {}", host, port, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("Serving on http://{}:{}", host, port);
+    println!("  GET /packages");
+    println!("  GET /packages/{{base}}");
+    println!("  GET /resolve?reqs=...");
+
+    match server.serve(listener) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Server error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
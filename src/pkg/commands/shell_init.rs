@@ -0,0 +1,96 @@
+//! Shell integration command.
+//!
+//! Prints a `pkg-activate` function for the calling shell that evaluates
+//! `pkg env` output in-place, so the environment sticks (like `pyenv init`).
+//!
+//! ```text
+//! # ~/.bashrc or ~/.zshrc
+//! eval "$(pkg shell-init bash)"
+//!
+//! # ~/.config/fish/config.fish
+//! pkg shell-init fish | source
+//!
+//! # $PROFILE
+//! pkg shell-init powershell | Out-String | Invoke-Expression
+//! ```
+
+use std::process::ExitCode;
+
+/// Print the activation function for the given shell.
+pub fn cmd_shell_init(shell: &str) -> ExitCode {
+    match activation_script(shell) {
+        Some(script) => {
+            print!("{}", script);
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!(
+                "Unknown shell: {}. Use 'bash', 'zsh', 'fish' or 'powershell'",
+                shell
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Build the `pkg-activate` function source for a shell.
+///
+/// Returns None for unsupported shells.
+fn activation_script(shell: &str) -> Option<String> {
+    let script = match shell.to_lowercase().as_str() {
+        "bash" | "zsh" => r#"# pkg shell integration (bash/zsh)
+pkg-activate() {
+    local __pkg_env
+    __pkg_env="$(command pkg env --format export "$@")" || return $?
+    eval "$__pkg_env"
+}
+"#,
+        "fish" => r#"# pkg shell integration (fish)
+function pkg-activate
+    set -l __pkg_env (command pkg env --format fish $argv | string collect)
+    or return $status
+    eval $__pkg_env
+end
+"#,
+        "powershell" | "pwsh" => r#"# pkg shell integration (PowerShell)
+function pkg-activate {
+    $pkgEnv = & pkg env --format powershell @args | Out-String
+    if ($LASTEXITCODE -ne 0) { return }
+    Invoke-Expression $pkgEnv
+}
+"#,
+        _ => return None,
+    };
+    Some(script.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bash_uses_eval_and_export_format() {
+        let script = activation_script("bash").unwrap();
+        assert!(script.contains("pkg-activate()"));
+        assert!(script.contains("eval"));
+        assert!(script.contains("--format export"));
+        assert_eq!(activation_script("zsh"), Some(script));
+    }
+
+    #[test]
+    fn fish_and_powershell() {
+        let fish = activation_script("fish").unwrap();
+        assert!(fish.contains("function pkg-activate"));
+        assert!(fish.contains("--format fish"));
+        assert!(fish.trim_end().ends_with("end"));
+
+        let ps = activation_script("powershell").unwrap();
+        assert!(ps.contains("Invoke-Expression"));
+        assert!(ps.contains("--format powershell"));
+    }
+
+    #[test]
+    fn unknown_shell() {
+        assert!(activation_script("tcsh").is_none());
+    }
+}
@@ -0,0 +1,127 @@
+//! Test command.
+//!
+//! Runs the commands declared in a package's `tests` map, each through the
+//! platform shell in the package's solved env, from the package dir:
+//!
+//! ```text
+//! pkg test ocio                 # all tests
+//! pkg test ocio import smoke    # selected tests
+//! ```
+
+use super::env::resolve_package;
+use pkg_lib::{Env, Package, Storage};
+use std::process::{Command, ExitCode};
+
+/// Outcome of one declared test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TestResult {
+    name: String,
+    passed: bool,
+    detail: Option<String>,
+}
+
+/// Run `package`'s tests (all if `names` is empty); fails if any test fails.
+pub fn cmd_test(storage: &Storage, package: String, names: Vec<String>) -> ExitCode {
+    let pkg = match resolve_package(storage, std::slice::from_ref(&package)) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(missing) = names.iter().find(|n| !pkg.tests.contains_key(*n)) {
+        eprintln!("Test not found in {}: {}", pkg.name, missing);
+        return ExitCode::FAILURE;
+    }
+    if pkg.tests.is_empty() {
+        println!("{}: no tests declared", pkg.name);
+        return ExitCode::SUCCESS;
+    }
+
+    let env = pkg
+        .default_env()
+        .unwrap_or_else(|| Env::new("default".to_string()));
+    let (env, _) = env.without_names(&Env::default_blacklist());
+
+    let results = run_tests(&pkg, &env, &names);
+    let failed = results.iter().filter(|r| !r.passed).count();
+    for result in &results {
+        let status = if result.passed { "PASS" } else { "FAIL" };
+        match &result.detail {
+            Some(detail) => println!("  {} {} ({})", status, result.name, detail),
+            None => println!("  {} {}", status, result.name),
+        }
+    }
+    println!("{}: {} passed, {} failed", pkg.name, results.len() - failed, failed);
+
+    if failed == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+/// Run the selected tests in name order, collecting pass/fail.
+fn run_tests(pkg: &Package, env: &Env, names: &[String]) -> Vec<TestResult> {
+    pkg.tests
+        .iter()
+        .filter(|(name, _)| names.is_empty() || names.contains(name))
+        .map(|(name, command)| {
+            let (passed, detail) = match shell_command(command, env, pkg).status() {
+                Ok(status) if status.success() => (true, None),
+                Ok(status) => (false, Some(format!("exit code {}", status.code().unwrap_or(-1)))),
+                Err(e) => (false, Some(e.to_string())),
+            };
+            TestResult { name: name.clone(), passed, detail }
+        })
+        .collect()
+}
+
+/// `command` run by the platform shell with `env` applied.
+fn shell_command(command: &str, env: &Env, pkg: &Package) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    for evar in &env.evars {
+        if evar.is_unset() {
+            cmd.env_remove(&evar.name);
+        } else {
+            cmd.env(&evar.name, &evar.value);
+        }
+    }
+    if let Some(root) = pkg.root_dir().filter(|d| d.is_dir()) {
+        cmd.current_dir(root);
+    }
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pkg_lib::Evar;
+
+    #[test]
+    fn run_tests_pass_fail() {
+        let mut pkg = Package::new("ocio".to_string(), "2.1.0".to_string());
+        pkg.tests.insert("env".to_string(), "test \"$OCIO\" = /opt/ocio/config.ocio".to_string());
+        pkg.tests.insert("broken".to_string(), "exit 2".to_string());
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("OCIO", "/opt/ocio/config.ocio"));
+
+        let results = run_tests(&pkg, &env, &[]);
+        assert_eq!(
+            results,
+            vec![
+                TestResult { name: "broken".to_string(), passed: false, detail: Some("exit code 2".to_string()) },
+                TestResult { name: "env".to_string(), passed: true, detail: None },
+            ]
+        );
+
+        let selected = run_tests(&pkg, &env, &["env".to_string()]);
+        assert_eq!(selected.len(), 1);
+        assert!(selected[0].passed);
+    }
+}
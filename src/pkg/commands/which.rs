@@ -0,0 +1,105 @@
+//! Which command.
+//!
+//! Resolves a requirement and prints the concrete package it picks and the
+//! absolute executable of one of its apps, without setting up the env:
+//!
+//! ```text
+//! pkg which maya@2026           # default app
+//! pkg which maya mayapy --json
+//! ```
+
+use super::env::resolve_package;
+use pkg_lib::Storage;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Print the resolved package name and its app's absolute executable path.
+pub fn cmd_which(storage: &Storage, package: String, app: Option<String>, json: bool) -> ExitCode {
+    let pkg = match resolve_package(storage, std::slice::from_ref(&package)) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let found = match &app {
+        Some(name) => pkg._app(name, true),
+        None => pkg.default_app(),
+    };
+    let Some(found) = found else {
+        match &app {
+            Some(name) => eprintln!("App not found in {}: {}", pkg.name, name),
+            None => eprintln!("No apps defined in {}", pkg.name),
+        }
+        return ExitCode::FAILURE;
+    };
+
+    // The manifest expands {TOKEN}s in the path against the app's env
+    let manifest = match pkg.launch_manifest(&storage.all_packages(), Some(&found.name)) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("Failed to resolve env: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(exe) = manifest["exe"].as_str() else {
+        eprintln!("App '{}' in {} has no executable path", found.name, pkg.name);
+        return ExitCode::FAILURE;
+    };
+    let env_path = manifest["env"]["PATH"].as_str().map(String::from);
+    let Some(exe_path) = absolute_exe(exe, env_path) else {
+        eprintln!("Executable for app '{}' not found: {}", found.name, exe);
+        return ExitCode::FAILURE;
+    };
+
+    if json {
+        let out = serde_json::json!({
+            "package": pkg.name,
+            "app": found.name,
+            "exe": exe_path,
+        });
+        println!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
+    } else {
+        println!("{}", pkg.name);
+        println!("{}", exe_path.display());
+    }
+    ExitCode::SUCCESS
+}
+
+/// Absolute form of `exe`; bare names are looked up on `path_var`
+/// (the resolved env's PATH), falling back to the process PATH.
+fn absolute_exe(exe: &str, path_var: Option<String>) -> Option<PathBuf> {
+    let path = Path::new(exe);
+    if path.components().count() > 1 || path.is_absolute() {
+        return std::path::absolute(path).ok();
+    }
+
+    let search = path_var.or_else(|| std::env::var("PATH").ok())?;
+    let names: Vec<String> = if cfg!(windows) && path.extension().is_none() {
+        vec![format!("{}.exe", exe), exe.to_string()]
+    } else {
+        vec![exe.to_string()]
+    };
+    std::env::split_paths(&search)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_exe_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("tool");
+        std::fs::write(&tool, "").unwrap();
+
+        let search = std::env::join_paths([dir.path()]).unwrap().to_string_lossy().to_string();
+        assert_eq!(absolute_exe("tool", Some(search.clone())), Some(tool.clone()));
+        assert_eq!(absolute_exe("missing-tool", Some(search)), None);
+        assert_eq!(absolute_exe(&tool.to_string_lossy(), None), Some(tool));
+        assert!(absolute_exe("bin/tool", None).unwrap().is_absolute());
+    }
+}
@@ -16,8 +16,8 @@ mod shell;
 use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use cli::{Cli, Commands};
-use log::{debug, info, trace};
-use pkg_lib::Storage;
+use log::{debug, info, trace, warn};
+use pkg_lib::{Env, Storage};
 use std::path::PathBuf;
 use std::process::ExitCode;
 
@@ -30,6 +30,14 @@ fn main() -> ExitCode {
     info!("pkg v{} starting", pkg_lib::VERSION);
     trace!("CLI args: repos={:?}, exclude={:?}", cli.repos, cli.exclude);
 
+    // Helper module dirs reach every Loader via $PKG_SYS_PATH
+    if !cli.sys_paths.is_empty() {
+        set_sys_paths(&cli.sys_paths);
+    }
+
+    // Site prelude is read once and shared by every Package env
+    Env::set_prelude(Env::from_prelude());
+
     // Show help if no command
     let Some(command) = cli.command else {
         print_usage();
@@ -43,10 +51,23 @@ fn main() -> ExitCode {
     if let Commands::Completions { shell } = command {
         return cmd_completions(shell);
     }
+    if let Commands::ShellInit { shell } = command {
+        debug!("cmd: shell-init shell={}", shell);
+        return commands::cmd_shell_init(&shell);
+    }
     if let Commands::GenPkg { package_id } = command {
         debug!("cmd: gen-pkg package_id={}", package_id);
         return commands::cmd_gen_pkg(&package_id);
     }
+    if let Commands::Cache { action } = command {
+        debug!("cmd: cache action={}", action);
+        return commands::cmd_cache(&action);
+    }
+
+    // Ctrl-C cancels long scans/solves
+    if !handles_interrupt_itself(&command) {
+        install_interrupt_handler();
+    }
 
     // Build storage with custom repos if provided
     debug!(
@@ -54,9 +75,18 @@ fn main() -> ExitCode {
         cli.repos.len(),
         cli.user_packages
     );
-    let storage = match build_storage(&cli.repos, &cli.exclude, cli.user_packages) {
+    let storage = match build_storage(
+        &cli.repos,
+        &cli.exclude,
+        cli.dry_run_exclude,
+        cli.user_packages,
+        cli.all_platforms,
+    ) {
         Ok(s) => s,
         Err(e) => {
+            if pkg_lib::cancel::global().is_cancelled() {
+                return interrupted();
+            }
             log::error!("Storage error: {}", e);
             eprintln!("Error scanning packages: {}", e);
             return ExitCode::FAILURE;
@@ -81,19 +111,27 @@ fn main() -> ExitCode {
         }
     }
 
-    match command {
+    let code = match command {
         Commands::List {
             patterns,
             tags,
             latest,
             json,
+            all,
         } => {
-            debug!("cmd: ls patterns={:?} tags={:?} latest={}", patterns, tags, latest);
-            commands::cmd_list(&storage, patterns, tags, latest, json)
+            debug!(
+                "cmd: ls patterns={:?} tags={:?} latest={} all={}",
+                patterns, tags, latest, all
+            );
+            commands::cmd_list(&storage, patterns, tags, latest, json, all)
+        }
+        Commands::Info { package, json, args } => {
+            debug!("cmd: info package={} args={:?}", package, args);
+            commands::cmd_info(&storage, &package, json, &args)
         }
-        Commands::Info { package, json } => {
-            debug!("cmd: info package={}", package);
-            commands::cmd_info(&storage, &package, json)
+        Commands::Diff { a, b, json, env } => {
+            debug!("cmd: diff a={} b={} env={}", a, b, env);
+            commands::cmd_diff(&storage, &a, &b, json, env)
         }
         Commands::Env {
             packages,
@@ -104,10 +142,17 @@ fn main() -> ExitCode {
             output,
             dry_run,
             stamp,
+            no_deps,
+            caps,
+            rxt,
+            lock,
+            prefer,
+            max_steps,
+            before,
         } => {
             debug!(
-                "cmd: env packages={:?} command={:?} env_name={:?}",
-                packages, command, env_name
+                "cmd: env packages={:?} command={:?} env_name={:?} before={:?}",
+                packages, command, env_name, before
             );
             commands::cmd_env(
                 &storage,
@@ -119,25 +164,77 @@ fn main() -> ExitCode {
                 output,
                 dry_run,
                 stamp,
+                no_deps,
+                caps,
+                rxt,
+                lock,
+                &prefer,
+                max_steps,
+                before,
                 cli.verbose > 0,
             )
         }
+        Commands::EnvDiff {
+            packages,
+            baseline,
+            save,
+            env_name,
+        } => {
+            debug!(
+                "cmd: env-diff packages={:?} baseline={:?} save={}",
+                packages, baseline, save
+            );
+            commands::cmd_env_diff(&storage, packages, env_name, &baseline, save)
+        }
+        Commands::Freeze { targets, output } => {
+            debug!("cmd: freeze targets={:?}", targets);
+            commands::cmd_freeze(&storage, targets, output)
+        }
+        Commands::Lock { targets, output } => {
+            debug!("cmd: lock targets={:?}", targets);
+            commands::cmd_lock(&storage, targets, output)
+        }
+        Commands::Bundle { packages, output, copy } => {
+            debug!("cmd: bundle packages={:?} output={:?} copy={}", packages, output, copy);
+            commands::cmd_bundle(&storage, packages, &output, copy)
+        }
+        Commands::Manifest { package, app } => {
+            debug!("cmd: manifest package={} app={:?}", package, app);
+            commands::cmd_manifest(&storage, package, app)
+        }
+        Commands::Run { package, app, args } => {
+            debug!("cmd: run package={} app={:?} args={:?}", package, app, args);
+            commands::cmd_run(&storage, package, app, args)
+        }
+        Commands::Test { package, tests } => {
+            debug!("cmd: test package={} tests={:?}", package, tests);
+            commands::cmd_test(&storage, package, tests)
+        }
+        Commands::Which { package, app, json } => {
+            debug!("cmd: which package={} app={:?}", package, app);
+            commands::cmd_which(&storage, package, app, json)
+        }
         Commands::Graph {
             packages,
             format,
             depth,
             reverse,
+            order,
         } => {
             debug!(
-                "cmd: graph packages={:?} format={} depth={} reverse={}",
-                packages, format, depth, reverse
+                "cmd: graph packages={:?} format={} depth={} reverse={} order={:?}",
+                packages, format, depth, reverse, order
             );
-            commands::cmd_graph(&storage, packages, &format, depth, reverse)
+            commands::cmd_graph(&storage, packages, &format, depth, reverse, order.as_deref())
         }
         Commands::Scan { paths } => {
             debug!("cmd: scan paths={:?}", paths);
             commands::cmd_scan(&paths)
         }
+        Commands::Normalize { location, write } => {
+            debug!("cmd: normalize location={:?} write={}", location, write);
+            commands::cmd_normalize(&location, write)
+        }
         Commands::GenerateRepo {
             output,
             small,
@@ -189,12 +286,60 @@ fn main() -> ExitCode {
                 }
             }
         }
+        #[cfg(feature = "serve")]
+        Commands::Serve { port, host } => {
+            debug!("cmd: serve host={} port={}", host, port);
+            commands::cmd_serve(storage, &host, port)
+        }
         Commands::Python { .. } => unreachable!(),
         Commands::Completions { .. } => unreachable!(),
         Commands::GenPkg { .. } => unreachable!(),
+        Commands::ShellInit { .. } => unreachable!(),
+        Commands::Cache { .. } => unreachable!(),
+    };
+
+    if pkg_lib::cancel::global().is_cancelled() {
+        return interrupted();
+    }
+    code
+}
+
+/// Exit code for Ctrl-C (128 + SIGINT), as shells report it.
+const EXIT_INTERRUPTED: u8 = 130;
+
+/// Commands that own Ctrl-C: interactive UIs, servers, and launched
+/// child processes (which receive the signal themselves).
+fn handles_interrupt_itself(command: &Commands) -> bool {
+    match command {
+        Commands::Shell | Commands::Gui => true,
+        Commands::Env { command, .. } => !command.is_empty(),
+        #[cfg(feature = "serve")]
+        Commands::Serve { .. } => true,
+        _ => false,
+    }
+}
+
+/// Install Ctrl-C handler: first press cancels the running scan/solve,
+/// second press exits immediately.
+fn install_interrupt_handler() {
+    let result = ctrlc::set_handler(|| {
+        let token = pkg_lib::cancel::global();
+        if token.is_cancelled() {
+            std::process::exit(EXIT_INTERRUPTED as i32);
+        }
+        token.cancel();
+    });
+    if let Err(e) = result {
+        debug!("Failed to install Ctrl-C handler: {}", e);
     }
 }
 
+/// Report interruption and return the matching exit code.
+fn interrupted() -> ExitCode {
+    eprintln!("Interrupted");
+    ExitCode::from(EXIT_INTERRUPTED)
+}
+
 /// Initialize logging based on verbosity and optional log file.
 fn init_logging(verbosity: u8, log_file: &Option<Option<PathBuf>>) {
     use std::io::Write;
@@ -257,11 +402,29 @@ fn cmd_completions(shell: clap_complete::Shell) -> ExitCode {
     ExitCode::SUCCESS
 }
 
+/// Prepend `dirs` to `$PKG_SYS_PATH`, keeping any studio-wide entries.
+fn set_sys_paths(dirs: &[PathBuf]) {
+    let var = pkg_lib::loader::SYS_PATH_VAR;
+    let mut paths = dirs.to_vec();
+    if let Some(existing) = std::env::var_os(var) {
+        paths.extend(std::env::split_paths(&existing));
+    }
+    match std::env::join_paths(&paths) {
+        Ok(joined) => std::env::set_var(var, joined),
+        Err(e) => warn!("Ignoring --sys-path: {}", e),
+    }
+}
+
 /// Build storage with optional custom repos, exclude patterns, and user packages.
+///
+/// With `dry_run_exclude`, packages matching `exclude` are only reported.
+/// Unless `all_platforms`, packages tagged for another OS/arch are dropped.
 fn build_storage(
     extra_repos: &[PathBuf],
     exclude: &[String],
+    dry_run_exclude: bool,
     user_packages: bool,
+    all_platforms: bool,
 ) -> Result<Storage, String> {
     let mut all_paths = Vec::new();
 
@@ -290,12 +453,24 @@ fn build_storage(
     let mut storage = if all_paths.is_empty() {
         Storage::scan_impl(None).map_err(|e| e.to_string())?
     } else {
-        Storage::scan_impl(Some(&all_paths)).map_err(|e| e.to_string())?
+        Storage::scan_layered(&all_paths).map_err(|e| e.to_string())?
     };
 
     // Apply exclude patterns (filter out matching packages)
     if !exclude.is_empty() {
-        storage.exclude_packages(exclude);
+        if dry_run_exclude {
+            let preview = storage.exclude_preview(exclude);
+            eprintln!("Would exclude {} package(s):", preview.len());
+            for name in &preview {
+                eprintln!("  - {}", name);
+            }
+        } else {
+            storage.exclude_packages(exclude);
+        }
+    }
+
+    if !all_platforms {
+        storage = storage.for_current_platform();
     }
 
     Ok(storage)
@@ -1,6 +1,7 @@
 //! Shell command implementations.
 
-use crate::commands::matches_glob;
+use crate::cli::ENV_FORMATS;
+use crate::commands::{generate_env_output, matches_glob};
 use pkg_lib::{SolveStatus, Storage};
 use std::process::Command;
 
@@ -12,7 +13,7 @@ Commands:
   list, ls [patterns...]      List packages (glob: maya, cinem*)
   info <package>              Show package details
   run [-f] <package> [app]    Launch app (-f: skip solve check)
-  env <package>               Show environment
+  env <package> [app] [-f fmt] Show environment (fmt: fish, export, ...)
   solve <package>             Resolve dependencies
   scan                        Rescan locations
   help, ?                     This help
@@ -26,10 +27,10 @@ pub fn shell_list(storage: &Storage, args: &[&str]) {
     let patterns: Vec<&str> = args.iter().copied().collect();
     
     let packages: Vec<_> = if patterns.is_empty() {
-        storage.packages()
+        storage.list(None, false)
     } else {
         storage
-            .packages()
+            .list(None, false)
             .into_iter()
             .filter(|pkg| {
                 patterns.iter().any(|pat| {
@@ -174,7 +175,7 @@ pub fn shell_run(storage: &Storage, args: &[&str]) {
     let mut cmd = Command::new(exe_path);
 
     if let Some(env) = env {
-        if let Ok(solved) = env.solve_impl(10, true) {
+        if let Ok(solved) = env.solve_impl(pkg_lib::env::DEFAULT_MAX_DEPTH, true) {
             for evar in &solved.evars {
                 cmd.env(&evar.name, &evar.value);
             }
@@ -202,10 +203,27 @@ pub fn shell_run(storage: &Storage, args: &[&str]) {
 
 /// Show environment in shell.
 pub fn shell_env(storage: &Storage, args: &[&str]) {
+    let mut format = None;
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if *arg == "-f" || *arg == "--format" {
+            format = iter.next().copied();
+        } else {
+            positional.push(*arg);
+        }
+    }
+    let args = positional.as_slice();
     if args.is_empty() {
-        eprintln!("Usage: env <package> [app]");
+        eprintln!("Usage: env <package> [app] [-f format]");
         return;
     }
+    if let Some(fmt) = format {
+        if !ENV_FORMATS.contains(&fmt) {
+            eprintln!("Unknown format: {} (expected one of: {})", fmt, ENV_FORMATS.join(", "));
+            return;
+        }
+    }
 
     let pkg = storage.resolve(args[0]);
     let Some(pkg) = pkg else {
@@ -215,12 +233,15 @@ pub fn shell_env(storage: &Storage, args: &[&str]) {
 
     let app_name = args.get(1).copied();
     match pkg.effective_env(app_name) {
-        Ok(Some(env)) => {
-            println!("Environment for {}:", pkg.name);
-            for evar in env.evars_sorted() {
-                println!("  {}={}", evar.name, evar.value);
+        Ok(Some(env)) => match format {
+            Some(fmt) => print!("{}", generate_env_output(&env, fmt)),
+            None => {
+                println!("Environment for {}:", pkg.name);
+                for evar in env.evars_sorted() {
+                    println!("  {}={}", evar.name, evar.display_value());
+                }
             }
-        }
+        },
         Ok(None) => println!("No environment defined."),
         Err(e) => eprintln!("Error: {}", e),
     }
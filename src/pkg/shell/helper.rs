@@ -1,6 +1,7 @@
 //! Shell helper for completion and hints.
 
 use super::SHELL_COMMANDS;
+use crate::cli::ENV_FORMATS;
 use pkg_lib::Storage;
 use rustyline::completion::{Completer, Pair};
 use rustyline::highlight::Highlighter;
@@ -54,8 +55,29 @@ impl Completer for ShellHelper {
             return Ok((0, matches));
         }
 
-        // Complete package name for commands that need it
         let cmd = parts[0];
+
+        // Complete `env -f <format>`
+        let format_flag = |p: &str| p == "-f" || p == "--format";
+        let after_flag = if line.ends_with(' ') {
+            parts.last().is_some_and(|p| format_flag(p))
+        } else {
+            parts.len() >= 2 && format_flag(parts[parts.len() - 2])
+        };
+        if cmd == "env" && after_flag {
+            let prefix = if line.ends_with(' ') { "" } else { parts[parts.len() - 1] };
+            let matches: Vec<Pair> = ENV_FORMATS
+                .iter()
+                .filter(|f| f.starts_with(prefix))
+                .map(|f| Pair {
+                    display: f.to_string(),
+                    replacement: f.to_string(),
+                })
+                .collect();
+            return Ok((pos - prefix.len(), matches));
+        }
+
+        // Complete package name for commands that need it
         if matches!(cmd, "info" | "run" | "env" | "solve") {
             let prefix = if line.ends_with(' ') {
                 ""
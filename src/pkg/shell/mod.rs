@@ -11,6 +11,8 @@ use rustyline::history::DefaultHistory;
 use rustyline::{Config, Editor};
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /// Shell command names for completion.
 pub const SHELL_COMMANDS: &[&str] = &[
@@ -46,8 +48,19 @@ pub fn cmd_shell(mut storage: Storage) -> ExitCode {
         .unwrap_or_else(|| PathBuf::from(SHELL_HISTORY_FILE));
     let _ = rl.load_history(&history_path);
 
+    // Flag package file edits so the user knows to rescan
+    let changed = Arc::new(AtomicBool::new(false));
+    let flag = changed.clone();
+    let _watcher = storage
+        .watch(move |_| flag.store(true, Ordering::Relaxed))
+        .inspect_err(|e| log::debug!("shell: not watching for changes: {}", e))
+        .ok();
+
     // REPL loop
     loop {
+        if changed.swap(false, Ordering::Relaxed) {
+            println!("Package files changed on disk. Type 'scan' to reload.");
+        }
         let prompt = format!("pkg ({})> ", storage.packages().len());
 
         match rl.readline(&prompt) {
@@ -71,9 +84,10 @@ pub fn cmd_shell(mut storage: Storage) -> ExitCode {
                     "env" => shell_env(&storage, args),
                     "solve" => shell_solve(&storage, args),
                     "scan" => {
-                        match Storage::scan() {
+                        match storage.rescan() {
                             Ok(s) => {
                                 storage = s;
+                                changed.store(false, Ordering::Relaxed);
                                 if let Some(helper) = rl.helper_mut() {
                                     helper.update(&storage);
                                 }
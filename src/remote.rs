@@ -0,0 +1,375 @@
+//! Read-only package repositories served over HTTP.
+//!
+//! [`Storage::scan_url`] fetches a JSON manifest listing the packages:
+//!
+//! ```json
+//! {"packages": [
+//!     {"name": "ocio", "version": "2.1.0", "url": "ocio/2.1.0/package.py"}
+//! ]}
+//! ```
+//!
+//! Relative `url`s resolve against the manifest's own URL. Each package.py
+//! is downloaded to `<cache dir>/<name>/<version>/package.py` and loaded
+//! like a filesystem package. Downloads send back the `ETag` /
+//! `Last-Modified` the server last returned, so unchanged files answer
+//! `304 Not Modified` and their parsed packages come from the [`Cache`].
+//! If a download fails, the previously downloaded copy is used with a
+//! warning. Packages whose name or version differ from their manifest entry
+//! are dropped with a warning. Downloaded code is executed, so plain
+//! `http://` URLs are refused unless `$PKG_REMOTE_ALLOW_HTTP` is set.
+//! Only available with the `remote` feature.
+//!
+//! ```ignore
+//! let storage = Storage::scan_url("https://pkg.studio.local/repo/index.json")?;
+//! ```
+
+use crate::cache::Cache;
+use crate::error::StorageError;
+use crate::storage::Storage;
+use log::{debug, info};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// File name downloaded packages are stored under.
+const PACKAGE_FILE: &str = "package.py";
+
+/// Sidecar next to each download holding the HTTP validators.
+const VALIDATORS_FILE: &str = "package.py.http";
+
+/// Environment variable allowing plain `http://` repositories (`1` or `true`).
+pub const ALLOW_HTTP_VAR: &str = "PKG_REMOTE_ALLOW_HTTP";
+
+/// Remote repository manifest (`index.json`).
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    packages: Vec<ManifestEntry>,
+}
+
+/// One package in the manifest.
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    name: String,
+    version: String,
+    url: String,
+}
+
+impl Storage {
+    /// Scan a remote repository from its manifest URL, caching downloads
+    /// under the user cache dir (`~/.cache/pkg-rs/remote/...`).
+    ///
+    /// Plain `http://` is only used when [`ALLOW_HTTP_VAR`] is set.
+    ///
+    /// # Errors
+    /// [`StorageError::RemoteFailed`] if the manifest can't be fetched or
+    /// parsed. Individual package failures are reported in `warnings`.
+    pub fn scan_url(url: &str) -> Result<Self, StorageError> {
+        let allow_http = std::env::var(ALLOW_HTTP_VAR).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        Self::scan_url_into(url, &default_cache_dir(url), allow_http)
+    }
+
+    /// Like [`scan_url`](Self::scan_url), downloading into `cache_dir`.
+    ///
+    /// # Arguments
+    /// * `allow_http` - Accept plain `http://` for the manifest and packages
+    ///
+    /// # Errors
+    /// [`StorageError::RemoteFailed`] if the manifest URL is refused or the
+    /// manifest can't be fetched or parsed.
+    pub fn scan_url_into(url: &str, cache_dir: &Path, allow_http: bool) -> Result<Self, StorageError> {
+        info!("Storage: scanning remote {}", url);
+        let remote_err = |reason: String| StorageError::RemoteFailed {
+            url: url.to_string(),
+            reason,
+        };
+        check_scheme(url, allow_http).map_err(remote_err)?;
+
+        let body = ureq::get(url)
+            .call()
+            .and_then(|mut resp| resp.body_mut().read_to_string())
+            .map_err(|e| remote_err(e.to_string()))?;
+        let manifest: Manifest =
+            serde_json::from_str(&body).map_err(|e| remote_err(format!("invalid manifest: {}", e)))?;
+
+        let mut cache = Cache::load();
+        let mut warnings = Vec::new();
+        let mut files = Vec::new();
+        let mut expected = Vec::new();
+        for entry in &manifest.packages {
+            if !is_safe_component(&entry.name) || !is_safe_component(&entry.version) {
+                warnings.push(format!(
+                    "Skipping manifest entry '{}-{}': invalid name or version",
+                    entry.name, entry.version
+                ));
+                continue;
+            }
+
+            let dest = cache_dir.join(&entry.name).join(&entry.version).join(PACKAGE_FILE);
+            let source = resolve_url(url, &entry.url);
+            if let Err(e) = check_scheme(&source, allow_http) {
+                warnings.push(format!("Skipping {}: {}", source, e));
+                continue;
+            }
+            match download(&source, &dest) {
+                // Fresh content: drop the parsed entry, mtime may not have moved
+                Ok(true) => {
+                    cache.entries.remove(&dest);
+                }
+                Ok(false) => debug!("Storage: {} not modified", source),
+                Err(e) if dest.exists() => {
+                    warnings.push(format!("Failed to fetch {}: {} (using cached copy)", source, e));
+                }
+                Err(e) => {
+                    warnings.push(format!("Failed to fetch {}: {}", source, e));
+                    continue;
+                }
+            }
+            expected.push((dest.to_string_lossy().to_string(), entry));
+            files.push((cache_dir.to_path_buf(), dest));
+        }
+
+        let mut storage = Self::from_files(vec![cache_dir.to_path_buf()], &files, &mut cache)?;
+
+        // A file may only define the package its manifest entry names
+        for (source, entry) in &expected {
+            let mismatched = storage.names_where(|pkg| {
+                pkg.package_source.as_deref() == Some(source.as_str())
+                    && (pkg.base != entry.name || pkg.version != entry.version)
+            });
+            for name in mismatched {
+                warnings.push(format!(
+                    "Dropping {} from {}: manifest lists {}-{}",
+                    name, entry.url, entry.name, entry.version
+                ));
+                storage.remove(&name);
+            }
+        }
+        warnings.append(&mut storage.warnings);
+        storage.warnings = warnings;
+        cache.save();
+
+        info!("Storage: found {} packages at {}", storage.count(), url);
+        Ok(storage)
+    }
+}
+
+/// Fetch `url` into `dest` unless the server says our copy is current.
+///
+/// # Returns
+/// `true` if new content was written, `false` on `304 Not Modified`.
+fn download(url: &str, dest: &Path) -> Result<bool, String> {
+    let validators_path = dest.with_file_name(VALIDATORS_FILE);
+    let mut request = ureq::get(url);
+    if dest.exists() {
+        let validators = std::fs::read_to_string(&validators_path).unwrap_or_default();
+        for line in validators.lines() {
+            match line.split_once(": ") {
+                Some(("etag", v)) => request = request.header("If-None-Match", v),
+                Some(("last-modified", v)) => request = request.header("If-Modified-Since", v),
+                _ => {}
+            }
+        }
+    }
+
+    let mut resp = request.call().map_err(|e| e.to_string())?;
+    if resp.status().as_u16() == 304 {
+        return Ok(false);
+    }
+
+    let mut validators = String::new();
+    for name in ["etag", "last-modified"] {
+        if let Some(value) = resp.headers().get(name).and_then(|v| v.to_str().ok()) {
+            validators.push_str(&format!("{}: {}\n", name, value));
+        }
+    }
+    let body = resp.body_mut().read_to_string().map_err(|e| e.to_string())?;
+
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = dest.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(dest, body)?;
+        std::fs::write(&validators_path, &validators)
+    };
+    write().map_err(|e| e.to_string())?;
+    debug!("Storage: downloaded {} -> {}", url, dest.display());
+    Ok(true)
+}
+
+/// Accept `https://`, and `http://` only with `allow_http`: downloaded
+/// package.py files are executed.
+fn check_scheme(url: &str, allow_http: bool) -> Result<(), String> {
+    let scheme = url.split_once("://").map_or("", |(scheme, _)| scheme).to_ascii_lowercase();
+    match scheme.as_str() {
+        "https" => Ok(()),
+        "http" if allow_http => Ok(()),
+        "http" => Err(format!("refusing plain http (set {}=1 to allow)", ALLOW_HTTP_VAR)),
+        _ => Err(format!("unsupported URL scheme: {}", url)),
+    }
+}
+
+/// Resolve a manifest entry's `url` against the manifest URL.
+fn resolve_url(index: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_string();
+    }
+    if let Some(path) = url.strip_prefix('/') {
+        let host_start = index.find("://").map(|i| i + 3).unwrap_or(0);
+        let origin_end = index[host_start..].find('/').map(|i| host_start + i).unwrap_or(index.len());
+        return format!("{}/{}", &index[..origin_end], path);
+    }
+    match index.rfind('/') {
+        Some(i) => format!("{}{}", &index[..=i], url),
+        None => url.to_string(),
+    }
+}
+
+/// Name/version usable as a single directory component.
+fn is_safe_component(s: &str) -> bool {
+    !s.is_empty() && s != "." && s != ".." && !s.contains(['/', '\\'])
+}
+
+/// Per-repository download dir under the user cache dir.
+fn default_cache_dir(url: &str) -> PathBuf {
+    let key: String = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("pkg-rs")
+        .join("remote")
+        .join(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Serve a two-package repo with ETags, logging "<path> <status>".
+    fn serve(log: Arc<Mutex<Vec<String>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split_whitespace().nth(1).unwrap_or("").to_string();
+                let mut if_none_match = None;
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap() == 0 || header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("if-none-match") {
+                            if_none_match = Some(value.trim().to_string());
+                        }
+                    }
+                }
+
+                let body = match path.as_str() {
+                    "/repo/index.json" => r#"{"packages": [
+                        {"name": "ocio", "version": "2.1.0", "url": "ocio/2.1.0/package.py"},
+                        {"name": "maya", "version": "2026.0.0", "url": "/pkgs/maya.py"}
+                    ]}"#
+                    .to_string(),
+                    "/repo/ocio/2.1.0/package.py" => {
+                        "def get_package():\n    return pkg.Package(\"ocio\", \"2.1.0\")\n".to_string()
+                    }
+                    "/bad/index.json" => r#"{"packages": [
+                        {"name": "ocio", "version": "9.9.9", "url": "/repo/ocio/2.1.0/package.py"}
+                    ]}"#
+                    .to_string(),
+                    "/pkgs/maya.py" => "def get_package():\n    \
+                                        p = pkg.Package(\"maya\", \"2026.0.0\")\n    \
+                                        p.add_req(\"ocio@2\")\n    \
+                                        return p\n"
+                        .to_string(),
+                    _ => String::new(),
+                };
+                let etag = format!("\"{}\"", body.len());
+                let status = if body.is_empty() {
+                    404
+                } else if if_none_match.as_deref() == Some(etag.as_str()) {
+                    304
+                } else {
+                    200
+                };
+                log.lock().unwrap().push(format!("{} {}", path, status));
+
+                let body = if status == 200 { body } else { String::new() };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nETag: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    etag,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn remote_scan_url() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let url = format!("{}/repo/index.json", serve(log.clone()));
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let storage = Storage::scan_url_into(&url, cache_dir.path(), true).unwrap();
+        assert!(storage.warnings.is_empty(), "{:?}", storage.warnings);
+        assert_eq!(storage.count(), 2);
+        assert!(storage.has("ocio-2.1.0"));
+        assert!(storage.has("maya-2026.0.0"));
+        assert!(cache_dir.path().join("maya/2026.0.0/package.py").exists());
+
+        // Second scan revalidates with the stored ETags
+        log.lock().unwrap().clear();
+        let storage = Storage::scan_url_into(&url, cache_dir.path(), true).unwrap();
+        assert_eq!(storage.count(), 2);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["/repo/index.json 200", "/repo/ocio/2.1.0/package.py 304", "/pkgs/maya.py 304"]
+        );
+    }
+
+    #[test]
+    fn remote_checks_scheme_and_manifest() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let base = serve(log.clone());
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        // Plain http needs an explicit opt-in; nothing is fetched without it
+        let url = format!("{}/repo/index.json", base);
+        let err = Storage::scan_url_into(&url, cache_dir.path(), false).unwrap_err();
+        assert!(matches!(err, StorageError::RemoteFailed { .. }));
+        assert!(log.lock().unwrap().is_empty());
+        assert!(check_scheme("https://host/index.json", false).is_ok());
+        assert!(check_scheme("ftp://host/index.json", true).is_err());
+
+        // A file defining another version than its entry is dropped
+        let url = format!("{}/bad/index.json", base);
+        let storage = Storage::scan_url_into(&url, cache_dir.path(), true).unwrap();
+        assert_eq!(storage.count(), 0);
+        assert!(storage.warnings.iter().any(|w| w.contains("ocio-2.1.0") && w.contains("ocio-9.9.9")), "{:?}", storage.warnings);
+    }
+
+    #[test]
+    fn remote_resolve_url() {
+        let index = "http://host:8080/repo/index.json";
+        assert_eq!(resolve_url(index, "ocio/package.py"), "http://host:8080/repo/ocio/package.py");
+        assert_eq!(resolve_url(index, "/pkgs/maya.py"), "http://host:8080/pkgs/maya.py");
+        assert_eq!(resolve_url(index, "https://cdn/x.py"), "https://cdn/x.py");
+        assert!(!is_safe_component(".."));
+        assert!(!is_safe_component("a/b"));
+        assert!(is_safe_component("2.1.0"));
+    }
+}
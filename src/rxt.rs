@@ -0,0 +1,169 @@
+//! Rez-compatible resolved context (`.rxt`) export.
+//!
+//! A `.rxt` file is rez's serialized `ResolvedContext`: the request, the
+//! resolved package variants and where they live. [`build_snapshot`]
+//! produces the same JSON layout from a pkg resolve so existing rez
+//! tooling (`rez-context`, `rez-env --input`) can read it.
+//!
+//! Rez recomputes the environment from package definitions when it loads
+//! a context; the solved pkg environment is stored alongside under
+//! [`ENV_KEY`], which rez ignores. Secret values are masked there.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! let snapshot = rxt::build_snapshot(&storage, &requested, &resolved, &env);
+//! rxt::write(Path::new("maya.rxt"), &snapshot)?;
+//! ```
+
+use crate::env::Env;
+use crate::package::Package;
+use crate::storage::Storage;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Context format version written to `serialize_version`.
+pub const SERIALIZE_VERSION: &str = "4.3";
+
+/// Key holding the solved pkg environment (not part of rez's schema).
+pub const ENV_KEY: &str = "pkg_env";
+
+/// Build a rez `ResolvedContext` dict for a resolve.
+///
+/// # Arguments
+/// * `storage` - Storage the packages were scanned from (for locations)
+/// * `requested` - Original requests (e.g. `["maya", "ocio@<3"]`)
+/// * `resolved` - Resolved packages, requested ones first
+/// * `env` - Solved environment (secret values are written masked)
+pub fn build_snapshot(storage: &Storage, requested: &[String], resolved: &[Package], env: &Env) -> Value {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let variants: Vec<Value> = resolved
+        .iter()
+        .map(|pkg| {
+            let location = storage
+                .version_origins(&pkg.base)
+                .into_iter()
+                .find(|(name, _)| name == &pkg.name)
+                .map(|(_, loc)| loc.to_string_lossy().to_string());
+            json!({
+                "handle": {
+                    "key": "filesystem.variant",
+                    "variables": {
+                        "location": location,
+                        "name": pkg.base,
+                        "version": pkg.version,
+                        "repository_type": "filesystem",
+                        "index": null,
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let env_vars: BTreeMap<&str, &str> = env
+        .evars
+        .iter()
+        .map(|e| (e.name.as_str(), e.display_value()))
+        .collect();
+
+    json!({
+        "serialize_version": SERIALIZE_VERSION,
+        "timestamp": now,
+        "requested_timestamp": 0,
+        "building": false,
+        "caching": false,
+        "implicit_packages": [],
+        "package_requests": requested,
+        "package_paths": storage.locations(),
+        "default_patch_lock": "no_lock",
+        "patch_locks": {},
+        "package_filter": [],
+        "package_orderers": null,
+        "rez_version": format!("pkg-rs {}", crate::VERSION),
+        "rez_path": null,
+        "user": std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default(),
+        "host": std::env::var("HOSTNAME").or_else(|_| std::env::var("COMPUTERNAME")).unwrap_or_default(),
+        "platform": rez_platform(),
+        "arch": std::env::consts::ARCH,
+        "os": rez_platform(),
+        "created": now,
+        "parent_suite_path": null,
+        "suite_context_name": null,
+        "status": "solved",
+        "resolved_packages": variants,
+        "failure_description": null,
+        "graph": null,
+        "from_cache": false,
+        "solve_time": 0.0,
+        "load_time": 0.0,
+        "num_loaded_packages": storage.count(),
+        ENV_KEY: env_vars,
+    })
+}
+
+/// Write a snapshot as pretty JSON.
+pub fn write(path: &Path, snapshot: &Value) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(snapshot).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+/// Rez platform name for the current OS.
+fn rez_platform() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "osx",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Evar;
+
+    #[test]
+    fn rxt_snapshot() {
+        let mut maya = Package::new("maya".to_string(), "2026.1.0".to_string());
+        maya.add_req("ocio".to_string());
+        let ocio = Package::new("ocio".to_string(), "2.3.0".to_string());
+        let storage = Storage::from_packages(vec![maya.clone(), ocio.clone()]);
+
+        let mut env = Env::new("default".to_string());
+        env.add(Evar::set("MAYA_ROOT", "/opt/maya"));
+        env.add(Evar::set("LICENSE_TOKEN", "s3cr3t").with_secret(true));
+
+        let snapshot = build_snapshot(&storage, &["maya".to_string()], &[maya, ocio], &env);
+        assert_eq!(snapshot["package_requests"], json!(["maya"]));
+        assert_eq!(snapshot["status"], "solved");
+
+        let resolved: Vec<(String, String)> = snapshot["resolved_packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| {
+                let vars = &v["handle"]["variables"];
+                (vars["name"].as_str().unwrap().to_string(), vars["version"].as_str().unwrap().to_string())
+            })
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                ("maya".to_string(), "2026.1.0".to_string()),
+                ("ocio".to_string(), "2.3.0".to_string()),
+            ]
+        );
+        assert_eq!(snapshot[ENV_KEY]["MAYA_ROOT"], "/opt/maya");
+        assert_eq!(snapshot[ENV_KEY]["LICENSE_TOKEN"], crate::evar::SECRET_MASK);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("maya.rxt");
+        write(&path, &snapshot).unwrap();
+        let read: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read, snapshot);
+    }
+}
@@ -0,0 +1,342 @@
+//! Minimal read-only HTTP server for querying packages.
+//!
+//! Exposes a scanned [`Storage`] and a [`Solver`] built from it over a tiny
+//! JSON API, intended for dashboards and other tooling that can't link the
+//! library directly. Only available with the `serve` feature.
+//!
+//! # Endpoints
+//!
+//! | Method | Path                | Response                                   |
+//! |--------|---------------------|--------------------------------------------|
+//! | GET    | `/packages`         | Sorted list of all package names           |
+//! | GET    | `/packages/{base}`  | Versions of `base` plus the latest package |
+//! | GET    | `/resolve?reqs=...` | Resolved solution for the requirements     |
+//!
+//! Requirements for `/resolve` are space-separated (`+` or `%20` in the URL)
+//! or passed as repeated `reqs=` parameters, so constraints containing commas
+//! (`redshift@>=3.5,<4.0`) survive intact.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use pkg_lib::server::Server;
+//! use std::net::TcpListener;
+//!
+//! let server = Server::new(Storage::scan_impl(None)?)?;
+//! server.serve(TcpListener::bind("127.0.0.1:8080")?)?;
+//! ```
+//!
+//! The server is single-threaded and handles one connection at a time,
+//! which is plenty for a read-only query API. Reads and writes time out
+//! after [`IO_TIMEOUT`], so a stalled client can't hold the server.
+
+use crate::error::SolverError;
+use crate::solver::Solver;
+use crate::storage::Storage;
+use log::{debug, info, warn};
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// Read/write timeout for each accepted connection.
+pub const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HTTP response produced by [`Server::handle`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// HTTP status code.
+    pub status: u16,
+    /// JSON body.
+    pub body: String,
+}
+
+impl Response {
+    fn ok(body: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }).to_string(),
+        }
+    }
+
+    /// Reason phrase for the status code.
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            422 => "Unprocessable Entity",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Read-only package query server.
+pub struct Server {
+    storage: Storage,
+    solver: Solver,
+}
+
+impl Server {
+    /// Create server from scanned storage.
+    ///
+    /// # Errors
+    /// Returns `SolverError` if the solver index can't be built
+    /// (e.g. a package has an unparseable version).
+    pub fn new(storage: Storage) -> Result<Self, SolverError> {
+        let solver = Solver::from_packages(&storage.all_packages())?;
+        Ok(Self { storage, solver })
+    }
+
+    /// Accept and serve connections until the listener fails.
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        info!("Server: listening on {}", listener.local_addr()?);
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = self.handle_connection(stream) {
+                        warn!("Server: connection error: {}", e);
+                    }
+                }
+                Err(e) => warn!("Server: accept failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read one request from the stream and write the response.
+    pub fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // Drain headers; requests have no body we care about
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => self.handle(method, target),
+            _ => Response::error(400, "Malformed request line"),
+        };
+        debug!("Server: {} -> {}", request_line.trim(), response.status);
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.reason(),
+            response.body.len(),
+            response.body
+        )?;
+        stream.flush()
+    }
+
+    /// Route a request to its endpoint.
+    ///
+    /// # Arguments
+    /// * `method` - HTTP method (only `GET` is supported)
+    /// * `target` - Request target: path with optional query string
+    pub fn handle(&self, method: &str, target: &str) -> Response {
+        if method != "GET" {
+            return Response::error(405, format!("Method not allowed: {}", method));
+        }
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let path = path.trim_end_matches('/');
+
+        match path {
+            "/packages" => self.packages(),
+            "/resolve" => self.resolve(query),
+            _ => match path.strip_prefix("/packages/") {
+                Some(base) => self.package(&percent_decode(base)),
+                None => Response::error(404, format!("Unknown endpoint: {}", path)),
+            },
+        }
+    }
+
+    /// `GET /packages`
+    fn packages(&self) -> Response {
+        let mut names: Vec<&str> = self
+            .storage
+            .packages_iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        names.sort();
+        Response::ok(json!(names))
+    }
+
+    /// `GET /packages/{base}`
+    fn package(&self, base: &str) -> Response {
+        let Some(latest) = self.storage.latest(base) else {
+            return Response::error(404, format!("Package not found: {}", base));
+        };
+        Response::ok(json!({
+            "base": base,
+            "versions": self.storage.versions(base),
+            "latest": latest,
+        }))
+    }
+
+    /// `GET /resolve?reqs=...`
+    fn resolve(&self, query: &str) -> Response {
+        let reqs: Vec<String> = query_values(query, "reqs")
+            .iter()
+            .flat_map(|v| v.split_whitespace())
+            .map(String::from)
+            .collect();
+
+        if reqs.is_empty() {
+            return Response::error(400, "Missing 'reqs' query parameter");
+        }
+
+        match self.solver.solve_requirements_impl(&reqs) {
+            Ok(solution) => Response::ok(json!({
+                "reqs": reqs,
+                "solution": solution,
+            })),
+            Err(e) => Response::error(422, e.to_string()),
+        }
+    }
+}
+
+/// Collect all decoded values for `key` from a query string.
+fn query_values(query: &str, key: &str) -> Vec<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(k, _)| percent_decode(k) == key)
+        .map(|(_, v)| percent_decode(v))
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` (space) in a URL component.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            // from_str_radix takes a sign, so `%+1` must be ruled out first
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                out.push(u8::from_str_radix(hex, 16).unwrap_or(b'%'));
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+    use std::io::Read;
+
+    fn make_pkg(name: &str, version: &str, reqs: Vec<&str>) -> Package {
+        let mut pkg = Package::new(name.to_string(), version.to_string());
+        for req in reqs {
+            pkg.add_req(req.to_string());
+        }
+        pkg
+    }
+
+    fn make_server() -> Server {
+        let storage = Storage::from_packages(vec![
+            make_pkg("maya", "2025.0.0", vec![]),
+            make_pkg("maya", "2026.0.0", vec!["redshift@>=3.0"]),
+            make_pkg("redshift", "3.5.0", vec![]),
+        ]);
+        Server::new(storage).unwrap()
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("maya%40%3E%3D2026"), "maya@>=2026");
+        assert_eq!(percent_decode("a+b"), "a b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%+1x"), "% 1x");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn handle_routes() {
+        let server = make_server();
+
+        let resp = server.handle("GET", "/packages");
+        assert_eq!(resp.status, 200);
+        let names: Vec<String> = serde_json::from_str(&resp.body).unwrap();
+        assert_eq!(names.len(), 3);
+
+        let resp = server.handle("GET", "/packages/maya");
+        assert_eq!(resp.status, 200);
+        let value: serde_json::Value = serde_json::from_str(&resp.body).unwrap();
+        assert_eq!(value["latest"]["version"], "2026.0.0");
+        assert_eq!(value["versions"].as_array().unwrap().len(), 2);
+
+        assert_eq!(server.handle("GET", "/packages/nuke").status, 404);
+        assert_eq!(server.handle("GET", "/nope").status, 404);
+        assert_eq!(server.handle("POST", "/packages").status, 405);
+        assert_eq!(server.handle("GET", "/resolve").status, 400);
+        assert_eq!(server.handle("GET", "/resolve?reqs=nuke").status, 422);
+    }
+
+    #[test]
+    fn resolve_over_tcp() {
+        let server = make_server();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            server.handle_connection(stream).unwrap();
+        });
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        write!(
+            client,
+            "GET /resolve?reqs=maya%40%3E%3D2026 HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+
+        let mut raw = String::new();
+        client.read_to_string(&mut raw).unwrap();
+        handle.join().unwrap();
+
+        let (head, body) = raw.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+
+        let value: serde_json::Value = serde_json::from_str(body).unwrap();
+        let solution: Vec<&str> = value["solution"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(solution, vec!["maya-2026.0.0", "redshift-3.5.0"]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Lockfiles: frozen resolves that can be re-checked later.
+//!
+//! A [`Lockfile`] records the requirements that were solved and the exact
+//! version picked for every base. [`Solver::solve_locked`](super::Solver::solve_locked)
+//! validates it against the current packages instead of solving again.
+//!
+//! ```json
+//! {
+//!   "requirements": ["maya@2026"],
+//!   "pins": { "maya": "2026.1.0", "ocio": "2.3.0" }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Frozen resolve: original requirements plus base → exact version pins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Requirements as given to the solver
+    pub requirements: Vec<String>,
+    /// Exact version per resolved base (sorted by base)
+    pub pins: BTreeMap<String, String>,
+}
+
+impl Lockfile {
+    /// Pins as `base-version` names, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.pins.iter().map(|(base, version)| format!("{}-{}", base, version)).collect()
+    }
+
+    /// Read a lockfile written by [`write`](Self::write).
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(std::io::Error::other)
+    }
+
+    /// Write as pretty JSON.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json + "\n")
+    }
+}
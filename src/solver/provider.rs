@@ -4,11 +4,43 @@
 
 use super::ranges::depspec_to_ranges;
 use super::PackageIndex;
+use crate::cancel::{self, CancelToken};
 use crate::dep::DepSpec;
-use crate::error::SolverError;
-use pubgrub::{Dependencies, DependencyProvider, Map, PackageResolutionStatistics, Ranges};
+use crate::error::{ConflictExplanation, ConflictSide, SolverError};
+use log::debug;
+use pubgrub::{
+    Dependencies, DependencyProvider, DerivationTree, External, Map, PackageResolutionStatistics, Ranges,
+};
 use semver::Version;
+use std::cell::{Cell, RefCell};
 use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Name of the virtual package that depends on all root requirements.
+const ROOT: &str = "__root__";
+
+/// Which end of a matching range the solver tries first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionStrategy {
+    /// Highest matching version (default)
+    #[default]
+    Newest,
+    /// Lowest matching version, e.g. to test the floor of declared ranges
+    Oldest,
+}
+
+impl FromStr for VersionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "newest" => Ok(Self::Newest),
+            "oldest" => Ok(Self::Oldest),
+            other => Err(format!("unknown version strategy '{}' (expected newest or oldest)", other)),
+        }
+    }
+}
 
 /// PubGrub dependency provider.
 ///
@@ -17,6 +49,22 @@ pub struct PubGrubProvider<'a> {
     index: &'a PackageIndex,
     /// Optional root dependencies for multi-requirement solving.
     root_deps: Option<Vec<DepSpec>>,
+    /// Checked between PubGrub steps (defaults to the global token).
+    cancel: CancelToken,
+    /// Optional allowlist of bases; others are treated as unavailable.
+    allowed: Option<&'a HashSet<String>>,
+    /// First disallowed base a candidate depended on (for error reporting).
+    forbidden: RefCell<Option<String>>,
+    /// Constraint-only bounds: narrow dependencies on a base, never add one.
+    caps: Vec<DepSpec>,
+    /// Weak requirements applied as hard ones, keyed by requirer version.
+    promoted: Vec<(String, Version, DepSpec)>,
+    /// Order in which matching versions are tried.
+    strategy: VersionStrategy,
+    /// Budget for `choose_version` + `get_dependencies` calls.
+    max_steps: Option<usize>,
+    /// Provider calls so far.
+    steps: Cell<usize>,
 }
 
 impl<'a> PubGrubProvider<'a> {
@@ -25,7 +73,148 @@ impl<'a> PubGrubProvider<'a> {
         Self {
             index,
             root_deps: None,
+            cancel: cancel::global().clone(),
+            allowed: None,
+            forbidden: RefCell::new(None),
+            caps: Vec::new(),
+            promoted: Vec::new(),
+            strategy: VersionStrategy::Newest,
+            max_steps: None,
+            steps: Cell::new(0),
+        }
+    }
+
+    /// Builder: use a specific cancellation token.
+    pub fn with_cancel(mut self, token: &CancelToken) -> Self {
+        self.cancel = token.clone();
+        self
+    }
+
+    /// Builder: only consider packages whose base is in `allowed`.
+    ///
+    /// Root requirements outside the allowlist fail immediately with
+    /// [`SolverError::ForbiddenDependency`]. Versions depending on a
+    /// disallowed base are unavailable, so PubGrub can still backtrack to
+    /// a version that doesn't need it.
+    pub fn with_allowed_bases(mut self, allowed: &'a HashSet<String>) -> Self {
+        self.allowed = Some(allowed);
+        self
+    }
+
+    /// First disallowed base encountered during resolution, if any.
+    pub fn forbidden_base(&self) -> Option<String> {
+        self.forbidden.borrow().clone()
+    }
+
+    /// Builder: bound versions of bases without requiring them.
+    ///
+    /// Every dependency on a capped base (root or transitive) is
+    /// intersected with the cap; a capped base nothing depends on stays
+    /// out of the solution.
+    pub fn with_caps(mut self, caps: &[DepSpec]) -> Self {
+        self.caps = caps.to_vec();
+        self
+    }
+
+    /// Builder: try matching versions oldest-first or newest-first.
+    pub fn with_strategy(mut self, strategy: VersionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Builder: fail with [`SolverError::Budget`] after `max_steps` calls to
+    /// `choose_version`/`get_dependencies`.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Count one provider call against the step budget.
+    fn step(&self) -> Result<(), SolverError> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        match self.max_steps {
+            Some(max) if steps > max => Err(SolverError::Budget { steps: max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Intersect a dependency range on `base` with any caps on it.
+    fn capped(&self, base: &str, range: Ranges<Version>) -> Result<Ranges<Version>, SolverError> {
+        let mut range = range;
+        for cap in self.caps.iter().filter(|c| c.base == base) {
+            range = range.intersection(&depspec_to_ranges(cap)?);
         }
+        Ok(range)
+    }
+
+    /// Weak requirements of the root and of every package in `solution`.
+    pub fn weak_deps<'s>(&'s self, solution: &'s Map<String, Version>) -> impl Iterator<Item = &'s DepSpec> + 's {
+        solution
+            .iter()
+            .filter_map(|(base, ver)| self.index.deps(base, ver))
+            .flatten()
+            .chain(self.root_deps.iter().flatten())
+            .filter(|d| d.weak)
+    }
+
+    /// Weak requirements broken by `solution` that aren't promoted yet,
+    /// with the package version that declares them.
+    ///
+    /// Only requirers in the solution count, so a weak requirement of a
+    /// version the solver didn't pick never constrains anything.
+    fn violated_weak(&self, solution: &Map<String, Version>) -> Vec<(String, Version, DepSpec)> {
+        let mut violated = Vec::new();
+        for (base, ver) in solution {
+            let deps = if base == ROOT {
+                self.root_deps.as_deref()
+            } else {
+                self.index.deps(base, ver).map(|d| d.as_slice())
+            };
+            for spec in deps.into_iter().flatten().filter(|d| d.weak) {
+                let Some(picked) = solution.get(&spec.base) else {
+                    continue;
+                };
+                let entry = (base.clone(), ver.clone(), spec.clone());
+                if self.index.ranges_for(spec).is_ok_and(|r| !r.contains(picked))
+                    && !self.promoted.contains(&entry)
+                    && !violated.contains(&entry)
+                {
+                    violated.push(entry);
+                }
+            }
+        }
+        violated
+    }
+
+    /// Whether `package`@`version` applies its weak requirement `spec`.
+    fn is_promoted(&self, package: &str, version: &Version, spec: &DepSpec) -> bool {
+        self.promoted
+            .iter()
+            .any(|(base, ver, promoted)| base == package && ver == version && promoted == spec)
+    }
+
+    /// Copy of this provider that also applies the weak requirements in
+    /// `promote`, continuing the step count.
+    fn promoting(&self, promote: &[(String, Version, DepSpec)]) -> Self {
+        let mut promoted = self.promoted.clone();
+        promoted.extend_from_slice(promote);
+        Self {
+            index: self.index,
+            root_deps: self.root_deps.clone(),
+            cancel: self.cancel.clone(),
+            allowed: self.allowed,
+            forbidden: RefCell::new(self.forbidden_base()),
+            caps: self.caps.clone(),
+            promoted,
+            strategy: self.strategy,
+            max_steps: self.max_steps,
+            steps: Cell::new(self.steps.get()),
+        }
+    }
+
+    fn is_allowed(&self, base: &str) -> bool {
+        self.allowed.is_none_or(|allowed| allowed.contains(base))
     }
 
     /// Create provider with virtual root dependencies.
@@ -36,6 +225,14 @@ impl<'a> PubGrubProvider<'a> {
         Self {
             index,
             root_deps: Some(deps.to_vec()),
+            cancel: cancel::global().clone(),
+            allowed: None,
+            forbidden: RefCell::new(None),
+            caps: Vec::new(),
+            promoted: Vec::new(),
+            strategy: VersionStrategy::Newest,
+            max_steps: None,
+            steps: Cell::new(0),
         }
     }
 }
@@ -51,8 +248,9 @@ impl DependencyProvider for PubGrubProvider<'_> {
     type VS = Ranges<Version>;
 
     /// Priority for package selection (higher = pick first).
-    /// We use Reverse<Version> to prefer newest versions.
-    type Priority = Reverse<Version>;
+    /// We use Reverse<Version> to prefer newest versions; ties are broken
+    /// by name so the decision order never depends on map iteration.
+    type Priority = (Reverse<Version>, Reverse<String>);
 
     /// Message for unavailable packages.
     type M = String;
@@ -60,6 +258,14 @@ impl DependencyProvider for PubGrubProvider<'_> {
     /// Error type.
     type Err = SolverError;
 
+    /// Abort resolution when cancellation was requested.
+    fn should_cancel(&self) -> Result<(), Self::Err> {
+        if self.cancel.is_cancelled() {
+            return Err(SolverError::Cancelled);
+        }
+        Ok(())
+    }
+
     /// Prioritize packages - prefer newest versions.
     fn prioritize(
         &self,
@@ -68,35 +274,46 @@ impl DependencyProvider for PubGrubProvider<'_> {
         _stats: &PackageResolutionStatistics,
     ) -> Self::Priority {
         // Return highest version as priority (Reverse makes higher = better)
-        if let Some(ver) = self.index.versions(package).first() {
-            Reverse((*ver).clone())
-        } else {
-            Reverse(Version::new(0, 0, 0))
-        }
+        let newest = match self.index.versions(package).first() {
+            Some(ver) => (*ver).clone(),
+            None => Version::new(0, 0, 0),
+        };
+        (Reverse(newest), Reverse(package.clone()))
     }
 
-    /// Choose best version matching the range.
+    /// Choose best version matching the range (non-deprecated first, newest
+    /// or oldest depending on the strategy).
     fn choose_version(
         &self,
         package: &Self::P,
         range: &Self::VS,
     ) -> Result<Option<Self::V>, Self::Err> {
+        self.step()?;
+
         // Virtual root package
-        if package == "__root__" {
+        if package == ROOT {
             return Ok(Some(Version::new(0, 0, 0)));
         }
 
         // Get all versions (already sorted newest first)
-        let versions = self.index.versions(package);
+        let mut versions = self.index.versions(package);
+        if self.strategy == VersionStrategy::Oldest {
+            versions.reverse();
+        }
 
-        // Find first matching version
+        // First matching non-deprecated version; deprecated ones only
+        // when nothing else fits (e.g. exact version requested)
+        let mut fallback = None;
         for ver in versions {
             if range.contains(ver) {
-                return Ok(Some(ver.clone()));
+                if !self.index.is_deprecated(package, ver) {
+                    return Ok(Some(ver.clone()));
+                }
+                fallback.get_or_insert(ver);
             }
         }
 
-        Ok(None)
+        Ok(fallback.cloned())
     }
 
     /// Get dependencies for a package version.
@@ -105,13 +322,26 @@ impl DependencyProvider for PubGrubProvider<'_> {
         package: &Self::P,
         version: &Self::V,
     ) -> Result<Dependencies<Self::P, Self::VS, Self::M>, Self::Err> {
+        self.step()?;
+
         // Virtual root: return root_deps
-        if package == "__root__" {
+        if package == ROOT {
             if let Some(ref deps) = self.root_deps {
                 let mut constraints: Map<String, Ranges<Version>> = Map::default();
 
-                for spec in deps {
-                    let range = depspec_to_ranges(spec)?;
+                // Weak requirements are checked on the result, see `resolve`
+                for spec in deps.iter().filter(|d| !d.weak || self.is_promoted(package, version, d)) {
+                    if !self.is_allowed(&spec.base) {
+                        return Err(SolverError::ForbiddenDependency {
+                            base: spec.base.clone(),
+                        });
+                    }
+                    let range = self.capped(&spec.base, self.index.ranges_for(spec)?)?;
+                    // Several requests on one base all apply (intersection)
+                    let range = match constraints.get(&spec.base) {
+                        Some(existing) => existing.intersection(&range),
+                        None => range,
+                    };
                     constraints.insert(spec.base.clone(), range);
                 }
 
@@ -132,7 +362,7 @@ impl DependencyProvider for PubGrubProvider<'_> {
         // Convert DepSpecs to PubGrub constraints
         let mut constraints: Map<String, Ranges<Version>> = Map::default();
 
-        for spec in deps {
+        for spec in deps.iter().filter(|d| !d.weak || self.is_promoted(package, version, d)) {
             // Check if dependency exists in index
             if !self.index.has(&spec.base) {
                 return Ok(Dependencies::Unavailable(format!(
@@ -141,7 +371,15 @@ impl DependencyProvider for PubGrubProvider<'_> {
                 )));
             }
 
-            let range = depspec_to_ranges(spec)?;
+            if !self.is_allowed(&spec.base) {
+                self.forbidden.borrow_mut().get_or_insert_with(|| spec.base.clone());
+                return Ok(Dependencies::Unavailable(format!(
+                    "Dependency {} not in allowed bases",
+                    spec.base
+                )));
+            }
+
+            let range = self.capped(&spec.base, self.index.ranges_for(spec)?)?;
 
             // Merge with existing constraint (intersection)
             if let Some(existing) = constraints.get(&spec.base) {
@@ -155,6 +393,45 @@ impl DependencyProvider for PubGrubProvider<'_> {
     }
 }
 
+/// Run PubGrub for `package`, honouring weak requirements, and drop
+/// `provides` aliases from the solution.
+///
+/// Weak deps don't enter PubGrub's dependencies up front. Instead each
+/// solution is checked against the weak deps of the packages it selected;
+/// one it breaks (its base was selected outside its range) becomes a hard
+/// dependency of that one requirer version, and PubGrub runs again. The
+/// solver can then narrow the base or pick another requirer version, e.g.
+/// one without the weak dep. A weak dep is only promoted once its base
+/// was selected, so it never pulls an absent base in. Every round
+/// promotes at least one, so this ends.
+pub fn resolve<'a>(
+    provider: &PubGrubProvider<'a>,
+    package: String,
+    version: Version,
+) -> Result<Map<String, Version>, pubgrub::PubGrubError<PubGrubProvider<'a>>> {
+    let mut solution = pubgrub::resolve(provider, package.clone(), version.clone())?;
+    let mut bounded: Option<PubGrubProvider<'a>> = None;
+    loop {
+        let current = bounded.as_ref().unwrap_or(provider);
+        let violated = current.violated_weak(&solution);
+        if violated.is_empty() {
+            break;
+        }
+        debug!("Solver: re-solving within weak requirements {:?}", violated);
+        let next = current.promoting(&violated);
+        let result = pubgrub::resolve(&next, package.clone(), version.clone());
+        provider.steps.set(next.steps.get());
+        if let Some(base) = next.forbidden_base() {
+            provider.forbidden.borrow_mut().get_or_insert(base);
+        }
+        solution = result?;
+        bounded = Some(next);
+    }
+    // Provided aliases only stand in for the real package, which is also selected
+    solution.retain(|base, version| !provider.index.is_provided(base, version));
+    Ok(solution)
+}
+
 /// Convert PubGrub error to SolverError.
 pub fn pubgrub_error_to_solver_error(
     error: pubgrub::PubGrubError<PubGrubProvider<'_>>,
@@ -165,21 +442,31 @@ pub fn pubgrub_error_to_solver_error(
         PubGrubError::NoSolution(tree) => {
             // Generate human-readable conflict explanation
             let report = DefaultStringReporter::report(&tree);
-            SolverError::Conflict {
-                message: report,
+            match explain_conflict(&tree, &report) {
+                Some(explanation) => SolverError::VersionConflict(Box::new(explanation)),
+                None => SolverError::Conflict { message: report },
             }
         }
+        PubGrubError::ErrorInShouldCancel(SolverError::Cancelled) => SolverError::Cancelled,
         PubGrubError::ErrorInShouldCancel(e) => {
             SolverError::NoSolution {
                 reason: format!("Cancelled: {}", e),
             }
         }
+        PubGrubError::ErrorChoosingVersion {
+            source: source @ SolverError::Budget { .. },
+            ..
+        } => source,
         PubGrubError::ErrorChoosingVersion { package, source } => {
             SolverError::NoMatchingVersion {
                 package,
                 constraint: source.to_string(),
             }
         }
+        PubGrubError::ErrorRetrievingDependencies {
+            source: source @ (SolverError::ForbiddenDependency { .. } | SolverError::Budget { .. }),
+            ..
+        } => source,
         PubGrubError::ErrorRetrievingDependencies { package, version, source } => {
             SolverError::InvalidDependency {
                 package: format!("{}-{}", package, version),
@@ -190,6 +477,105 @@ pub fn pubgrub_error_to_solver_error(
     }
 }
 
+/// Dependency edge from a derivation tree:
+/// (requirer, requirer versions, dependency, dependency range).
+type Edge = (String, Ranges<Version>, String, Ranges<Version>);
+
+/// Find two disjoint constraints on one package in a failed resolve.
+///
+/// Walks the derivation tree for "X depends on Y" incompatibilities and
+/// returns the first package with two requirers whose ranges don't
+/// overlap, tracing each requirer back towards the root. None if the
+/// failure has another shape (missing package, single unsatisfiable range).
+fn explain_conflict(tree: &DerivationTree<String, Ranges<Version>, String>, report: &str) -> Option<ConflictExplanation> {
+    let mut edges = Vec::new();
+    collect_edges(tree, &mut edges);
+
+    for (i, a) in edges.iter().enumerate() {
+        for b in &edges[i + 1..] {
+            if a.2 == b.2 && a.0 != b.0 && a.3.intersection(&b.3) == Ranges::empty() {
+                // The report only adds something if the failure involves more
+                // than the two chains
+                let covered: Vec<&Edge> = chain_edges(&edges, a).into_iter().chain(chain_edges(&edges, b)).collect();
+                let extra = has_other_causes(tree) || edges.iter().any(|e| !covered.contains(&e));
+                return Some(ConflictExplanation {
+                    package: a.2.clone(),
+                    left: conflict_side(&edges, a),
+                    right: conflict_side(&edges, b),
+                    report: if extra { report.to_string() } else { String::new() },
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Collect dependency edges in tree order, without duplicates.
+fn collect_edges(tree: &DerivationTree<String, Ranges<Version>, String>, out: &mut Vec<Edge>) {
+    match tree {
+        DerivationTree::External(External::FromDependencyOf(p, p_range, d, d_range)) => {
+            let edge = (p.clone(), p_range.clone(), d.clone(), d_range.clone());
+            if !out.contains(&edge) {
+                out.push(edge);
+            }
+        }
+        DerivationTree::External(_) => {}
+        DerivationTree::Derived(derived) => {
+            collect_edges(&derived.cause1, out);
+            collect_edges(&derived.cause2, out);
+        }
+    }
+}
+
+/// Whether the tree has causes besides dependency edges (missing
+/// versions, unavailable packages).
+fn has_other_causes(tree: &DerivationTree<String, Ranges<Version>, String>) -> bool {
+    match tree {
+        DerivationTree::External(External::FromDependencyOf(..) | External::NotRoot(..)) => false,
+        DerivationTree::External(_) => true,
+        DerivationTree::Derived(derived) => has_other_causes(&derived.cause1) || has_other_causes(&derived.cause2),
+    }
+}
+
+/// `edge` followed by the edges leading to its requirer, towards the root.
+fn chain_edges<'e>(edges: &'e [Edge], edge: &'e Edge) -> Vec<&'e Edge> {
+    let mut chain = vec![edge];
+    let mut seen = HashSet::from([edge.0.clone()]);
+    let mut current = edge;
+    while let Some(parent) = edges.iter().find(|e| e.2 == current.0 && !seen.contains(&e.0)) {
+        seen.insert(parent.0.clone());
+        chain.push(parent);
+        current = parent;
+    }
+    chain
+}
+
+/// Requirer chain (root first) and constraint for one conflicting edge.
+fn conflict_side(edges: &[Edge], edge: &Edge) -> ConflictSide {
+    let mut chain: Vec<String> = chain_edges(edges, edge)
+        .into_iter()
+        .filter(|e| e.0 != ROOT)
+        .map(|e| edge_label(&e.0, &e.1))
+        .collect();
+    chain.reverse();
+
+    ConflictSide {
+        chain,
+        constraint: format!("{}@{}", edge.2, edge.3),
+    }
+}
+
+/// `maya-2026.0.0` for a single version, `maya` for any, else `maya@<range>`.
+fn edge_label(package: &str, versions: &Ranges<Version>) -> String {
+    if let Some(version) = versions.as_singleton() {
+        format!("{}-{}", package, version)
+    } else if *versions == Ranges::full() {
+        package.to_string()
+    } else {
+        format!("{}@{}", package, versions)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,4 +685,26 @@ mod tests {
             panic!("Expected Available dependencies");
         }
     }
+
+    #[test]
+    fn provider_skips_deprecated() {
+        let mut deprecated = make_pkg("ocio", "2.3.0", vec![]);
+        deprecated.deprecated = true;
+        let index = build_index(vec![
+            make_pkg("ocio", "2.1.0", vec![]),
+            deprecated,
+        ]);
+        let provider = PubGrubProvider::new(&index);
+
+        // Newest is deprecated: pick older one
+        let ver = provider
+            .choose_version(&"ocio".to_string(), &Ranges::full())
+            .unwrap();
+        assert_eq!(ver, Some(Version::parse("2.1.0").unwrap()));
+
+        // Explicitly requested: deprecated is still allowed
+        let exact = Ranges::singleton(Version::parse("2.3.0").unwrap());
+        let ver = provider.choose_version(&"ocio".to_string(), &exact).unwrap();
+        assert_eq!(ver, Some(Version::parse("2.3.0").unwrap()));
+    }
 }
@@ -2,7 +2,7 @@
 //!
 //! Converts semver-style constraints into PubGrub Ranges<Version>.
 
-use crate::dep::DepSpec;
+use crate::dep::{desugar_shorthand, is_prerelease, variant_release, DepSpec};
 use crate::error::SolverError;
 use pubgrub::Ranges;
 use semver::Version;
@@ -17,11 +17,63 @@ use semver::Version;
 /// - `>=1.0.0` → higher_than
 /// - `<1.0.0` → strictly_lower_than
 /// - `<=1.0.0` → lower_than
-/// - `^1.2.3` → [1.2.3, 2.0.0) (caret)
+/// - `^1.2.3` → [1.2.3, 2.0.0) (caret, desugared by the dep module)
 /// - `~1.2.3` → [1.2.3, 1.3.0) (tilde)
 /// - `>=1.0,<2.0` → intersection of constraints
+/// - `!=1.2.3` → everything but 1.2.3 (complement of the singleton)
+/// - `>=1.0,<2.0|>=3.0` → union of the `|`-separated branches
 pub fn depspec_to_ranges(spec: &DepSpec) -> Result<Ranges<Version>, SolverError> {
-    let constraint = spec.constraint.trim();
+    let mut result = Ranges::empty();
+    for branch in spec.branches() {
+        let branch = desugar_shorthand(branch).map_err(|e| SolverError::InvalidDependency {
+            package: "".to_string(),
+            dependency: branch.to_string(),
+            reason: e.to_string(),
+        })?;
+        result = result.union(&branch_to_ranges(&branch)?);
+    }
+    Ok(result)
+}
+
+/// Cut prerelease `candidates` out of `range` unless `spec` allows them.
+///
+/// Prereleases sit between releases, so "no prereleases" isn't a range of
+/// its own; the known prerelease versions of the base are removed one by
+/// one instead. See [`DepSpec::allows_prerelease`].
+///
+/// Variant candidates (`2026.0.0-win64`) are placed by their release the
+/// same way, unless the spec names a tagged version itself: semver sorts
+/// them below the release, so `>=2026` alone would skip them.
+pub fn without_prereleases<'a>(
+    spec: &DepSpec,
+    range: Ranges<Version>,
+    candidates: impl IntoIterator<Item = &'a Version>,
+) -> Ranges<Version> {
+    let cut_pre = !spec.allows_prerelease();
+    let place_variants = !spec.names_tagged();
+    candidates.into_iter().fold(range, |range, v| {
+        let keep = if is_prerelease(v) {
+            if !cut_pre {
+                return range;
+            }
+            false
+        } else {
+            match variant_release(v) {
+                Some(release) if place_variants => range.contains(&release),
+                _ => return range,
+            }
+        };
+        let single = Ranges::singleton(v.clone());
+        if keep {
+            range.union(&single)
+        } else {
+            range.intersection(&single.complement())
+        }
+    })
+}
+
+/// Convert one constraint branch (no `|`).
+fn branch_to_ranges(constraint: &str) -> Result<Ranges<Version>, SolverError> {
 
     // Any version
     if constraint == "*" {
@@ -70,14 +122,10 @@ fn parse_intersection(constraint: &str) -> Result<Ranges<Version>, SolverError>
 fn parse_single_constraint(constraint: &str) -> Result<Ranges<Version>, SolverError> {
     let constraint = constraint.trim();
 
-    // Caret: ^1.2.3 → [1.2.3, 2.0.0)
-    if let Some(rest) = constraint.strip_prefix('^') {
-        return parse_caret(rest);
-    }
-
-    // Tilde: ~1.2.3 → [1.2.3, 1.3.0)
-    if let Some(rest) = constraint.strip_prefix('~') {
-        return parse_tilde(rest);
+    // Exclusion: !=1.2.3 → (-inf, 1.2.3) ∪ (1.2.3, +inf)
+    if let Some(rest) = constraint.strip_prefix("!=") {
+        let ver = parse_version(rest.trim())?;
+        return Ok(Ranges::singleton(ver).complement());
     }
 
     // Comparison operators (order matters: >= before >)
@@ -118,35 +166,6 @@ fn parse_single_constraint(constraint: &str) -> Result<Ranges<Version>, SolverEr
     })
 }
 
-/// Parse caret constraint: ^1.2.3 → [1.2.3, 2.0.0)
-fn parse_caret(version_str: &str) -> Result<Ranges<Version>, SolverError> {
-    let ver = parse_version(version_str)?;
-
-    // Caret rules:
-    // ^1.2.3 → >=1.2.3, <2.0.0 (major bump)
-    // ^0.2.3 → >=0.2.3, <0.3.0 (minor bump for 0.x)
-    // ^0.0.3 → >=0.0.3, <0.0.4 (patch bump for 0.0.x)
-    let upper = if ver.major > 0 {
-        Version::new(ver.major + 1, 0, 0)
-    } else if ver.minor > 0 {
-        Version::new(0, ver.minor + 1, 0)
-    } else {
-        Version::new(0, 0, ver.patch + 1)
-    };
-
-    Ok(Ranges::between(ver, upper))
-}
-
-/// Parse tilde constraint: ~1.2.3 → [1.2.3, 1.3.0)
-fn parse_tilde(version_str: &str) -> Result<Ranges<Version>, SolverError> {
-    let ver = parse_version(version_str)?;
-
-    // Tilde: same major.minor, any patch
-    let upper = Version::new(ver.major, ver.minor + 1, 0);
-
-    Ok(Ranges::between(ver, upper))
-}
-
 /// Parse version string to semver::Version.
 /// Handles partial versions: "1" -> "1.0.0", "1.2" -> "1.2.0"
 fn parse_version(s: &str) -> Result<Version, SolverError> {
@@ -255,6 +274,76 @@ mod tests {
         assert!(!range.contains(&v("1.2.2")));
     }
 
+    #[test]
+    fn ranges_exclusion() {
+        let range = depspec_to_ranges(&spec(">=2.0.0,!=2.1.3")).unwrap();
+        assert!(range.contains(&v("2.1.2")));
+        assert!(!range.contains(&v("2.1.3")));
+        assert!(range.contains(&v("2.1.4")));
+        assert!(!range.contains(&v("1.0.0")));
+    }
+
+    #[test]
+    fn ranges_union() {
+        let range = depspec_to_ranges(&spec(">=3.0.0,<4.0.0|>=5.0.0,<6.0.0")).unwrap();
+        assert!(range.contains(&v("3.5.0")));
+        assert!(range.contains(&v("5.0.0")));
+        assert!(!range.contains(&v("4.0.0")));
+        assert!(!range.contains(&v("6.0.0")));
+
+        let exact = depspec_to_ranges(&spec("1.0.0|2.0.0")).unwrap();
+        assert!(exact.contains(&v("1.0.0")));
+        assert!(exact.contains(&v("2.0.0")));
+        assert!(!exact.contains(&v("1.5.0")));
+    }
+
+    #[test]
+    fn ranges_caret_tilde_partial() {
+        // Same semantics as DepSpec matching: ^0 → <1.0.0, ~1 → <2.0.0
+        let caret = depspec_to_ranges(&spec("^0")).unwrap();
+        assert!(caret.contains(&v("0.9.0")));
+        assert!(!caret.contains(&v("1.0.0")));
+
+        let tilde = depspec_to_ranges(&spec("~1")).unwrap();
+        assert!(tilde.contains(&v("1.5.0")));
+        assert!(!tilde.contains(&v("2.0.0")));
+    }
+
+    #[test]
+    fn ranges_prerelease() {
+        let candidates = [v("2026.0.0"), v("2026.1.0-rc.1")];
+        let range_for = |req: &str| {
+            let spec = DepSpec::parse_impl(req).unwrap();
+            without_prereleases(&spec, depspec_to_ranges(&spec).unwrap(), &candidates)
+        };
+
+        let plain = range_for("maya@>=2026.0.0");
+        assert!(plain.contains(&v("2026.0.0")));
+        assert!(!plain.contains(&v("2026.1.0-rc.1")));
+
+        assert!(range_for("maya@>=2026.1.0-0").contains(&v("2026.1.0-rc.1")));
+        assert!(range_for("maya@>=2026.0.0,pre").contains(&v("2026.1.0-rc.1")));
+    }
+
+    #[test]
+    fn ranges_variants_follow_release() {
+        let candidates = [v("2026.0.0-win64"), v("2027.0.0-py310")];
+        let range_for = |req: &str| {
+            let spec = DepSpec::parse_impl(req).unwrap();
+            without_prereleases(&spec, depspec_to_ranges(&spec).unwrap(), &candidates)
+        };
+
+        let from = range_for("maya@>=2026");
+        assert!(from.contains(&v("2026.0.0-win64")));
+        assert!(from.contains(&v("2027.0.0-py310")));
+
+        let below = range_for("maya@<2027");
+        assert!(below.contains(&v("2026.0.0-win64")));
+        assert!(!below.contains(&v("2027.0.0-py310")));
+
+        assert!(range_for("maya@2026.0.0-win64").contains(&v("2026.0.0-win64")));
+    }
+
     #[test]
     fn ranges_intersection() {
         // >=1.0.0,<2.0.0
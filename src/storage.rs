@@ -11,7 +11,17 @@
 //! 2. Paths from `PKG_LOCATIONS` environment variable
 //! 3. Explicitly added paths
 //!
+//! Location entries may be glob patterns (`/studio/*/packages`); each
+//! expands to the matching existing directories.
+//!
 //! Each location is scanned recursively for `package.py` files.
+//! Gzipped `package.py.gz` files are loaded too; if both forms exist in
+//! one directory the plain file wins (with a warning).
+//!
+//! A `.pkgignore` file anywhere in a location excludes paths below it,
+//! gitignore-style: one glob per line, `#` comments, a trailing `/` for
+//! directories only, and patterns containing `/` anchored to the file's
+//! directory (`vendor/**`). Negation (`!`) is not supported.
 //! Found packages are validated and indexed by name and version.
 //!
 //! # Directory Structure
@@ -81,13 +91,15 @@
 
 use crate::cache::Cache;
 use crate::dep::DepSpec;
-use crate::error::StorageError;
+use crate::error::{LoaderError, StorageError};
+use crate::loader::toml::PACKAGE_TOML;
 use crate::package::Package;
-use jwalk::WalkDir;
+use crate::solver::PackageIndex;
+use jwalk::WalkDirGeneric;
 use log::{debug, info, trace, warn};
 use pyo3::prelude::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -98,6 +110,15 @@ const PKG_LOCATIONS_VAR: &str = "PKG_LOCATIONS";
 /// Default package file name.
 const PACKAGE_FILE: &str = "package.py";
 
+/// Gzipped package file name.
+const PACKAGE_FILE_GZ: &str = "package.py.gz";
+
+/// Marker file flagging a location as read-only (e.g. release repos).
+pub const READONLY_MARKER: &str = ".readonly";
+
+/// Per-directory ignore file (gitignore-style globs).
+pub const PKGIGNORE_FILE: &str = ".pkgignore";
+
 /// Package storage and discovery.
 ///
 /// Holds all discovered packages and provides lookup functionality.
@@ -119,6 +140,19 @@ pub struct Storage {
     /// Scanned locations.
     locations: Vec<PathBuf>,
 
+    /// Layers as given by the caller, before glob expansion.
+    pub(crate) layers: Vec<PathBuf>,
+
+    /// Index into `layers` for each entry of `locations`.
+    location_layers: Vec<usize>,
+
+    /// Location each scanned package came from, by full name.
+    origins: HashMap<String, PathBuf>,
+
+    /// Platform filter (os, arch) from [`for_platform`](Self::for_platform),
+    /// re-applied by every rescan.
+    pub(crate) platform: Option<(String, String)>,
+
     /// Errors encountered during scanning (non-fatal).
     #[pyo3(get)]
     pub warnings: Vec<String>,
@@ -133,6 +167,10 @@ impl Storage {
             packages: HashMap::new(),
             by_base: HashMap::new(),
             locations: Vec::new(),
+            layers: Vec::new(),
+            location_layers: Vec::new(),
+            origins: HashMap::new(),
+            platform: None,
             warnings: Vec::new(),
         }
     }
@@ -162,6 +200,19 @@ impl Storage {
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
 
+    /// Scan layers in priority order: a `base-version` in an earlier layer
+    /// overrides the same one in later layers.
+    ///
+    /// # Arguments
+    /// * `layers` - Directories, highest priority first (e.g. user, release)
+    #[staticmethod]
+    #[pyo3(name = "scan_layered")]
+    pub fn py_scan_layered(layers: Vec<String>) -> PyResult<Self> {
+        let layers: Vec<PathBuf> = layers.into_iter().map(PathBuf::from).collect();
+        Self::scan_layered(&layers)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+
     /// Get package by full name.
     ///
     /// # Arguments
@@ -210,27 +261,38 @@ impl Storage {
         self.packages.values().cloned().collect()
     }
 
+    /// Get names of library packages (no apps), sorted.
+    pub fn libraries(&self) -> Vec<String> {
+        self.names_where(|pkg| pkg.apps.is_empty())
+    }
+
+    /// Get names of application packages (at least one app), sorted.
+    pub fn applications(&self) -> Vec<String> {
+        self.names_where(|pkg| !pkg.apps.is_empty())
+    }
+
     /// List packages with optional tag filter.
     ///
+    /// Hidden packages are skipped unless `include_hidden` is set.
+    ///
     /// # Arguments
     /// * `tags` - Filter by tags (package must have ALL specified tags)
+    /// * `include_hidden` - Also list packages marked `hidden`
     ///
     /// # Example
     /// ```python
     /// all_pkgs = storage.list()
     /// dcc_pkgs = storage.list(tags=["dcc"])
     /// adobe_render = storage.list(tags=["adobe", "render"])
+    /// everything = storage.list(include_hidden=True)
     /// ```
-    #[pyo3(signature = (tags = None))]
-    pub fn list(&self, tags: Option<Vec<String>>) -> Vec<Package> {
+    #[pyo3(signature = (tags = None, include_hidden = false))]
+    pub fn list(&self, tags: Option<Vec<String>>, include_hidden: bool) -> Vec<Package> {
         let tags = tags.unwrap_or_default();
-        
-        if tags.is_empty() {
-            return self.packages.values().cloned().collect();
-        }
 
         self.packages
             .values()
+            .filter(|pkg| include_hidden || !pkg.hidden)
             .filter(|pkg| tags.iter().all(|t| pkg.tags.contains(t)))
             .cloned()
             .collect()
@@ -245,6 +307,28 @@ impl Storage {
             .collect()
     }
 
+    /// Get locations marked read-only with a `.readonly` file.
+    #[getter]
+    pub fn readonly_locations(&self) -> Vec<String> {
+        self.locations
+            .iter()
+            .filter(|p| p.join(READONLY_MARKER).is_file())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// Source location of each version of a base, newest first.
+    ///
+    /// # Returns
+    /// List of (full name, location) tuples.
+    #[pyo3(name = "version_origins")]
+    fn py_version_origins(&self, base: &str) -> Vec<(String, String)> {
+        self.version_origins(base)
+            .into_iter()
+            .map(|(name, loc)| (name, loc.to_string_lossy().to_string()))
+            .collect()
+    }
+
     /// Get raw location paths (Rust only).
     pub fn location_paths(&self) -> &[PathBuf] {
         &self.locations
@@ -345,7 +429,7 @@ impl Storage {
     /// # Returns
     /// New Storage with refreshed packages.
     pub fn refresh(&self) -> PyResult<Self> {
-        Self::scan_impl(Some(&self.locations))
+        self.rescan()
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
 
@@ -368,8 +452,41 @@ impl Storage {
 
 // Pure Rust API
 impl Storage {
+    /// Scan `layers` in priority order.
+    ///
+    /// Each layer is a location or a glob matching several; when layers
+    /// define the same `base-version`, the earliest layer's package is kept,
+    /// the others are reported in `warnings` as overridden, and
+    /// [`Package::origin_layer`] records the winning layer's index in
+    /// `layers` (all directories a glob matches share its index).
+    ///
+    /// # Errors
+    /// Same as [`scan_cancellable`](Self::scan_cancellable).
+    pub fn scan_layered(layers: &[PathBuf]) -> Result<Self, StorageError> {
+        Self::scan_cancellable(Some(layers), crate::cancel::global())
+    }
+
     /// Internal scan implementation with caching and parallel scanning.
+    ///
+    /// Explicit paths are layers, see [`scan_layered`](Self::scan_layered);
+    /// None scans the default locations.
     pub fn scan_impl(paths: Option<&[PathBuf]>) -> Result<Self, StorageError> {
+        match paths {
+            Some(layers) => Self::scan_layered(layers),
+            None => Self::scan_cancellable(None, crate::cancel::global()),
+        }
+    }
+
+    /// Scan like [`scan_impl`](Self::scan_impl), checking `cancel` before each
+    /// package load.
+    ///
+    /// # Errors
+    /// [`StorageError::Cancelled`] if cancellation is requested mid-scan; the
+    /// partially loaded storage is discarded and the cache is not saved.
+    pub fn scan_cancellable(
+        paths: Option<&[PathBuf]>,
+        cancel: &crate::cancel::CancelToken,
+    ) -> Result<Self, StorageError> {
         info!("Storage: scanning for packages");
         
         // Initialize Python interpreter for Loader
@@ -379,8 +496,6 @@ impl Storage {
 
         // Load cache
         let mut cache = Cache::load();
-        let cache_hits = Arc::new(Mutex::new(0usize));
-        let cache_misses = Arc::new(Mutex::new(0usize));
 
         let mut storage = Self::empty();
 
@@ -397,61 +512,52 @@ impl Storage {
             }
         };
 
-        storage.locations = locations.clone();
+        let expanded = expand_locations(&locations);
+        storage.layers = locations;
+        storage.locations = expanded.iter().map(|(path, _)| path.clone()).collect();
+        storage.location_layers = expanded.into_iter().map(|(_, layer)| layer).collect();
+        let locations = storage.locations.clone();
 
-        // Collect all package.py files in parallel using jwalk
-        let package_files: Vec<PathBuf> = locations
+        // Collect all package.py files (with their location) in parallel using jwalk,
+        // pruning whatever .pkgignore files along the way exclude
+        let ignored = Arc::new(Mutex::new(Vec::new()));
+        let package_files: Vec<(PathBuf, PathBuf)> = locations
             .iter()
             .filter(|loc| loc.exists())
             .flat_map(|location| {
                 debug!("Storage: walking {}", location.display());
-                WalkDir::new(location)
+                let ignored = ignored.clone();
+                WalkDirGeneric::<(Vec<IgnoreRule>, ())>::new(location)
+                    .process_read_dir(move |_, dir, rules, children| {
+                        let skipped = prune_ignored(dir, rules, children);
+                        if !skipped.is_empty() {
+                            ignored.lock().unwrap().extend(skipped);
+                        }
+                    })
                     .into_iter()
                     .filter_map(|e| e.ok())
                     .filter(|e| e.file_type().is_file())
-                    .filter(|e| e.file_name().to_string_lossy() == PACKAGE_FILE)
-                    .map(|e| e.path())
+                    .filter(|e| is_package_file(&e.file_name().to_string_lossy()))
+                    .map(|e| (location.clone(), e.path()))
                     .collect::<Vec<_>>()
             })
             .collect();
+        let package_files = prefer_plain_sources(package_files, &mut storage.warnings);
+
+        let mut ignored = std::mem::take(&mut *ignored.lock().unwrap());
+        ignored.sort();
+        for path in ignored {
+            debug!("Storage: ignoring {} ({})", path.display(), PKGIGNORE_FILE);
+            // Only surfaced at -vv: large vendored trees would drown real warnings
+            if log::log_enabled!(log::Level::Debug) {
+                storage.warnings.push(format!("Ignored by {}: {}", PKGIGNORE_FILE, path.display()));
+            }
+        }
 
         debug!("Storage: found {} package.py files", package_files.len());
 
         // Load packages (with cache)
-        for path in &package_files {
-            // Try cache first
-            if let Some(pkg) = cache.get(path) {
-                *cache_hits.lock().unwrap() += 1;
-                
-                // Check for duplicates
-                if storage.packages.contains_key(&pkg.name) {
-                    storage.warnings.push(format!(
-                        "Duplicate package '{}': ignoring {} (first location wins)",
-                        pkg.name, path.display()
-                    ));
-                    continue;
-                }
-                
-                let name = pkg.name.clone();
-                let base = pkg.base.clone();
-                storage.packages.insert(name.clone(), pkg.clone());
-                storage.by_base.entry(base).or_default().push(name);
-                continue;
-            }
-
-            // Cache miss - load from disk
-            *cache_misses.lock().unwrap() += 1;
-            
-            match storage.load_package_cached(path, &mut cache) {
-                Ok(()) => {},
-                Err(e) => {
-                    storage.warnings.push(format!(
-                        "Failed to load {}: {}",
-                        path.display(), e
-                    ));
-                }
-            }
-        }
+        let (hits, misses) = storage.load_files(&package_files, &mut cache, cancel)?;
 
         // Scan toolsets for each location
         for location in &locations {
@@ -464,13 +570,13 @@ impl Storage {
         for versions in storage.by_base.values_mut() {
             sort_versions_vec(versions);
         }
+        let provides = storage.provides_warnings();
+        storage.warnings.extend(provides);
 
         // Prune and save cache
         cache.prune();
         cache.save();
 
-        let hits = *cache_hits.lock().unwrap();
-        let misses = *cache_misses.lock().unwrap();
         info!("Storage: found {} packages (cache: {} hits, {} misses)", 
               storage.packages.len(), hits, misses);
         
@@ -520,68 +626,246 @@ impl Storage {
         
         let toolset_packages = scan_toolsets_dir(location);
         
-        for pkg in toolset_packages {
-            // Check for duplicates (first wins with warning)
-            if self.packages.contains_key(&pkg.name) {
-                self.warnings.push(format!(
-                    "Duplicate package '{}': ignoring toolset (first location wins)",
-                    pkg.name
-                ));
-                warn!(
-                    "Duplicate package '{}': ignoring toolset (first location wins)",
-                    pkg.name
-                );
+        for mut pkg in toolset_packages {
+            // Check for duplicates (earlier layer wins with warning)
+            if self.is_shadowed(&pkg.name, location, "toolset") {
                 continue;
             }
             
             // Add to storage
+            pkg.origin_layer = self.layer_of(location);
             let name = pkg.name.clone();
             let base = pkg.base.clone();
             
+            self.origins.insert(name.clone(), location.to_path_buf());
             self.packages.insert(name.clone(), pkg);
             self.by_base.entry(base).or_default().push(name);
         }
     }
 
+    /// Load and index `(location, package file)` pairs, from `cache` where
+    /// still fresh. Failed loads become warnings.
+    ///
+    /// # Returns
+    /// Cache `(hits, misses)`.
+    ///
+    /// # Errors
+    /// [`StorageError::Cancelled`] if `cancel` fires before a file is loaded.
+    pub(crate) fn load_files(
+        &mut self,
+        files: &[(PathBuf, PathBuf)],
+        cache: &mut Cache,
+        cancel: &crate::cancel::CancelToken,
+    ) -> Result<(usize, usize), StorageError> {
+        let (mut hits, mut misses) = (0, 0);
+        for (location, path) in files {
+            if cancel.is_cancelled() {
+                info!("Storage: scan cancelled");
+                return Err(StorageError::Cancelled);
+            }
+
+            // Try cache first
+            if let Some(pkgs) = cache.get(path) {
+                hits += 1;
+                for pkg in pkgs {
+                    self.index_loaded(pkg.clone(), location, &path.display().to_string());
+                }
+                continue;
+            }
+
+            // Cache miss - load from disk
+            misses += 1;
+            match self.load_package_cached(path, location, cache) {
+                Ok(()) => {}
+                // Already `file:line: message`
+                Err(e @ LoaderError::Execution { .. }) => self.warnings.push(e.to_string()),
+                Err(e) => self.warnings.push(format!("Failed to load {}: {}", path.display(), e)),
+            }
+        }
+        Ok((hits, misses))
+    }
+
+    /// Storage over package files located by other means (e.g. a remote
+    /// mirror), instead of walking `locations`.
+    ///
+    /// # Errors
+    /// [`StorageError::Cancelled`] on Ctrl-C.
+    pub(crate) fn from_files(
+        locations: Vec<PathBuf>,
+        files: &[(PathBuf, PathBuf)],
+        cache: &mut Cache,
+    ) -> Result<Self, StorageError> {
+        let _ = pyo3::Python::initialize();
+        let mut storage = Self::empty();
+        storage.location_layers = (0..locations.len()).collect();
+        storage.layers = locations.clone();
+        storage.locations = locations;
+        let (hits, misses) = storage.load_files(files, cache, crate::cancel::global())?;
+        for versions in storage.by_base.values_mut() {
+            sort_versions_vec(versions);
+        }
+        debug!("Storage: loaded {} files (cache: {} hits, {} misses)", files.len(), hits, misses);
+        Ok(storage)
+    }
+
     /// Load a single package.py file and update cache.
-    fn load_package_cached(&mut self, path: &Path, cache: &mut Cache) -> Result<(), StorageError> {
+    fn load_package_cached(
+        &mut self,
+        path: &Path,
+        location: &Path,
+        cache: &mut Cache,
+    ) -> Result<(), LoaderError> {
         use crate::loader::Loader;
 
         trace!("Storage: loading package from {}", path.display());
 
-        // Use Loader to execute package.py and get Package
+        // Use Loader to execute package.py (one or more packages)
         let mut loader = Loader::new(Some(false));
-        let mut pkg = loader.load_path(path).map_err(|e| {
+        let mut pkgs = loader.load_path_all(path).inspect_err(|e| {
             debug!("Storage: failed to load {}: {}", path.display(), e);
-            StorageError::InvalidPackage {
-                path: path.to_path_buf(),
-                reason: e.to_string(),
-            }
         })?;
 
         // Set source path
-        pkg.package_source = Some(path.to_string_lossy().to_string());
+        for pkg in &mut pkgs {
+            pkg.package_source = Some(path.to_string_lossy().to_string());
+        }
 
         // Update cache
-        cache.insert(path.to_path_buf(), pkg.clone());
+        cache.insert(path.to_path_buf(), pkgs.clone());
 
-        // Check for duplicates (first wins with warning)
-        let name = pkg.name.clone();
-        if self.packages.contains_key(&name) {
-            self.warnings.push(format!(
-                "Duplicate package '{}': ignoring {} (first location wins)",
-                name, path.display()
-            ));
-            return Ok(());
+        for pkg in pkgs {
+            info!("Storage: loaded package {} ({})", pkg.name, pkg.base);
+            self.index_loaded(pkg, location, &path.display().to_string());
         }
-        
-        // Index it
+
+        Ok(())
+    }
+
+    /// Index one loaded package, earlier layers win on duplicate names
+    /// (with warning).
+    fn index_loaded(&mut self, mut pkg: Package, location: &Path, source: &str) {
+        if self.is_shadowed(&pkg.name, location, source) {
+            return;
+        }
+
+        self.warnings.extend(duplicate_set_warnings(&pkg));
+        pkg.origin_layer = self.layer_of(location);
+        let name = pkg.name.clone();
         let base = pkg.base.clone();
-        info!("Storage: loaded package {} ({})", name, base);
+        self.origins.insert(name.clone(), location.to_path_buf());
         self.packages.insert(name.clone(), pkg);
         self.by_base.entry(base).or_default().push(name);
+    }
 
-        Ok(())
+    /// Whether `name` is already indexed; warns that `source` in `location`
+    /// is overridden (other layer) or a duplicate (same layer).
+    fn is_shadowed(&mut self, name: &str, location: &Path, source: &str) -> bool {
+        if !self.packages.contains_key(name) {
+            return false;
+        }
+        let msg = match self.origins.get(name) {
+            Some(winner) if winner != location => format!(
+                "Package '{}' in {} overridden by {} (layer {})",
+                name,
+                source,
+                winner.display(),
+                self.layer_of(winner).map_or("?".to_string(), |i| i.to_string())
+            ),
+            _ => format!("Duplicate package '{}': ignoring {} (first one wins)", name, source),
+        };
+        warn!("{}", msg);
+        self.warnings.push(msg);
+        true
+    }
+
+    /// Caller's layer index of a scanned location.
+    fn layer_of(&self, location: &Path) -> Option<usize> {
+        let i = self.locations.iter().position(|l| l == location)?;
+        self.location_layers.get(i).copied()
+    }
+
+    /// Scan package definitions inside a zip archive.
+    ///
+    /// Matches the same files as a directory scan (package.py, package.py.gz,
+    /// package.toml, one per directory). Files are read into memory and
+    /// loaded via
+    /// [`Loader::load_all_from_string`](crate::loader::Loader::load_all_from_string),
+    /// nothing is extracted to disk. Each package's `package_source` is
+    /// recorded as `zip://<archive>!<entry>`. Packages that fail to load
+    /// are reported in `warnings`, like a regular scan.
+    ///
+    /// # Errors
+    /// Returns `StorageError` if the archive can't be opened or read.
+    pub fn scan_zip(path: &Path) -> Result<Self, StorageError> {
+        use crate::loader::Loader;
+        use std::io::Read;
+
+        info!("Storage: scanning zip {}", path.display());
+        let _ = pyo3::Python::initialize();
+
+        let zip_err = |e: zip::result::ZipError| StorageError::ScanFailed {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        };
+        let file = std::fs::File::open(path)?;
+        let mut archive = zip::ZipArchive::new(file).map_err(zip_err)?;
+
+        let mut storage = Self::empty();
+        storage.locations = vec![path.to_path_buf()];
+        storage.layers = storage.locations.clone();
+        storage.location_layers = vec![0];
+        let mut loader = Loader::new(Some(false));
+
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i).map_err(zip_err)?;
+            let entry_name = entry.name().to_string();
+            if entry.is_file() && is_package_file(entry_name.rsplit('/').next().unwrap_or_default()) {
+                entries.push((path.to_path_buf(), PathBuf::from(entry_name)));
+            }
+        }
+        let mut skipped = Vec::new();
+        let entries = prefer_plain_sources(entries, &mut skipped);
+        storage
+            .warnings
+            .extend(skipped.into_iter().map(|w| format!("{}: {}", path.display(), w)));
+
+        for (_, entry_path) in entries {
+            let entry_name = entry_path.to_string_lossy().into_owned();
+            let mut entry = archive.by_name(&entry_name).map_err(zip_err)?;
+            let source = format!("zip://{}!{}", path.display(), entry_name);
+            let mut code = String::new();
+            let read = if entry_name.ends_with(PACKAGE_FILE_GZ) {
+                flate2::read::GzDecoder::new(&mut entry).read_to_string(&mut code)
+            } else {
+                entry.read_to_string(&mut code)
+            };
+            if let Err(e) = read {
+                storage.warnings.push(format!("Failed to read {}: {}", source, e));
+                continue;
+            }
+
+            let pkgs = match loader.load_all_from_string(&code, &source) {
+                Ok(pkgs) => pkgs,
+                Err(e) => {
+                    storage.warnings.push(format!("Failed to load {}: {}", source, e));
+                    continue;
+                }
+            };
+
+            for mut pkg in pkgs {
+                pkg.package_source = Some(source.clone());
+                debug!("Storage: loaded package {} from {}", pkg.name, source);
+                storage.index_loaded(pkg, path, &source);
+            }
+        }
+
+        for versions in storage.by_base.values_mut() {
+            sort_versions_vec(versions);
+        }
+
+        info!("Storage: found {} packages in zip", storage.packages.len());
+        Ok(storage)
     }
 
     /// Get all packages as a vector (for Solver).
@@ -597,6 +881,56 @@ impl Storage {
     }
 
 
+    /// Source location of each version of `base`, newest first.
+    ///
+    /// Packages added manually (not scanned) have no origin and are skipped.
+    pub fn version_origins(&self, base: &str) -> Vec<(String, PathBuf)> {
+        self.by_base
+            .get(base)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| Some((name.clone(), self.origins.get(name)?.clone())))
+            .collect()
+    }
+
+    /// Overlapping `provides` aliases the solver will ignore.
+    fn provides_warnings(&self) -> Vec<String> {
+        if self.packages.values().all(|pkg| pkg.provides.is_empty()) {
+            return Vec::new();
+        }
+        let mut names: Vec<&String> = self.packages.keys().collect();
+        names.sort();
+        let mut index = PackageIndex::new();
+        for name in names {
+            // Unparsable versions are the solver's error to report
+            let _ = index.add(&self.packages[name]);
+        }
+        index.warnings().to_vec()
+    }
+
+    /// Sorted names of packages matching a predicate.
+    pub(crate) fn names_where(&self, pred: impl Fn(&Package) -> bool) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .packages
+            .values()
+            .filter(|pkg| pred(pkg))
+            .map(|pkg| pkg.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Check whether `path` lies inside a read-only location.
+    ///
+    /// Writers (installers, toolset editors) should refuse such targets.
+    /// Paths outside all scanned locations are considered writable.
+    pub fn is_readonly(&self, path: &Path) -> bool {
+        self.locations
+            .iter()
+            .filter(|loc| path.starts_with(loc))
+            .any(|loc| loc.join(READONLY_MARKER).is_file())
+    }
+
     /// Create storage from a list of packages (for testing).
     pub fn from_packages(packages: Vec<Package>) -> Self {
         let mut storage = Self::empty();
@@ -606,11 +940,12 @@ impl Storage {
         storage
     }
     
-    /// Exclude packages matching patterns (glob-style: * matches anything).
-    pub fn exclude_packages(&mut self, patterns: &[String]) {
-        use log::debug;
-        
-        let to_remove: Vec<String> = self.packages.keys()
+    /// Names of packages that `exclude_packages` would remove (sorted).
+    ///
+    /// Same matching as [`exclude_packages`](Self::exclude_packages) but
+    /// doesn't mutate storage, for dry runs.
+    pub fn exclude_preview(&self, patterns: &[String]) -> Vec<String> {
+        let mut names: Vec<String> = self.packages.keys()
             .filter(|name| {
                 patterns.iter().any(|pat| {
                     if pat.contains('*') {
@@ -632,17 +967,97 @@ impl Storage {
             })
             .cloned()
             .collect();
+        names.sort();
+        names
+    }
+
+    /// Exclude packages matching patterns (glob-style: * matches anything).
+    pub fn exclude_packages(&mut self, patterns: &[String]) {
+        use log::debug;
+        
+        let to_remove = self.exclude_preview(patterns);
         
         for name in &to_remove {
-            if let Some(pkg) = self.packages.remove(name) {
+            if self.remove(name) {
                 debug!("Excluded package: {}", name);
-                // Remove from by_base
-                if let Some(versions) = self.by_base.get_mut(&pkg.base) {
-                    versions.retain(|v| v != name);
-                }
             }
         }
     }
+
+    /// Copy of storage without packages released after `cutoff` (unix
+    /// seconds). Packages without a release time are kept.
+    pub fn released_before(&self, cutoff: i64) -> Storage {
+        let mut storage = self.clone();
+        for name in self.names_where(|pkg| pkg.released.is_some_and(|t| t > cutoff)) {
+            storage.remove(&name);
+        }
+        storage
+    }
+
+    /// Copy of storage without packages built for another OS or CPU.
+    ///
+    /// See [`for_platform`](Self::for_platform); matches against
+    /// `std::env::consts::OS` and `ARCH`.
+    pub fn for_current_platform(&self) -> Storage {
+        self.for_platform(std::env::consts::OS, std::env::consts::ARCH)
+    }
+
+    /// Copy of storage keeping packages that run on `os` / `arch`.
+    ///
+    /// Packages declare targets with `platform-<os>` and `arch-<arch>` tags
+    /// (`platform-linux`, `arch-x86_64`, names as in `std::env::consts`).
+    /// A package without tags of a kind runs everywhere for that kind;
+    /// with several, any one of them has to match.
+    pub fn for_platform(&self, os: &str, arch: &str) -> Storage {
+        let runs_on = |pkg: &Package, prefix: &str, value: &str| {
+            let mut targets = pkg.tags.iter().filter_map(|t| t.strip_prefix(prefix)).peekable();
+            targets.peek().is_none() || targets.any(|t| t == value)
+        };
+        let mut storage = self.clone();
+        for name in self.names_where(|pkg| !runs_on(pkg, "platform-", os) || !runs_on(pkg, "arch-", arch)) {
+            debug!("Storage: {} doesn't run on {}/{}", name, os, arch);
+            storage.remove(&name);
+        }
+        storage.platform = Some((os.to_string(), arch.to_string()));
+        storage
+    }
+
+    /// Rescan the same layers, keeping the platform filter.
+    ///
+    /// Globs are expanded again, so newly matching directories are picked up.
+    ///
+    /// # Errors
+    /// Same as [`scan_impl`](Self::scan_impl).
+    pub fn rescan(&self) -> Result<Self, StorageError> {
+        Self::rescan_with(&self.layers, self.platform.as_ref())
+    }
+
+    /// Scan `layers` and apply an optional (os, arch) filter.
+    pub(crate) fn rescan_with(
+        layers: &[PathBuf],
+        platform: Option<&(String, String)>,
+    ) -> Result<Self, StorageError> {
+        let storage = Self::scan_layered(layers)?;
+        Ok(match platform {
+            Some((os, arch)) => storage.for_platform(os, arch),
+            None => storage,
+        })
+    }
+
+    /// Drop a package from all indexes; false if it wasn't present.
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let Some(pkg) = self.packages.remove(name) else {
+            return false;
+        };
+        self.origins.remove(name);
+        if let Some(versions) = self.by_base.get_mut(&pkg.base) {
+            versions.retain(|v| v != name);
+            if versions.is_empty() {
+                self.by_base.remove(&pkg.base);
+            }
+        }
+        true
+    }
     
     /// Get user packages directory (~/.pkg-rs/packages).
     ///
@@ -679,6 +1094,183 @@ fn sort_versions_vec(versions: &mut Vec<String>) {
     });
 }
 
+/// Expand glob patterns in location entries.
+///
+/// Entries containing `*`, `?` or `[` are replaced by the existing
+/// directories they match (sorted); literal entries pass through as-is.
+/// The result is deduped, keeping first occurrence order.
+///
+/// # Returns
+/// Each location with the index of the entry that produced it.
+fn expand_locations(locations: &[PathBuf]) -> Vec<(PathBuf, usize)> {
+    let mut expanded: Vec<(PathBuf, usize)> = Vec::new();
+    for (layer, location) in locations.iter().enumerate() {
+        let pattern = location.to_string_lossy();
+        let matches = if pattern.contains(['*', '?', '[']) {
+            match glob::glob(&pattern) {
+                Ok(paths) => {
+                    let mut dirs: Vec<PathBuf> = paths.filter_map(|p| p.ok()).filter(|p| p.is_dir()).collect();
+                    dirs.sort();
+                    debug!("Storage: {} matched {} locations", pattern, dirs.len());
+                    dirs
+                }
+                Err(e) => {
+                    warn!("Storage: invalid location pattern {}: {}", pattern, e);
+                    Vec::new()
+                }
+            }
+        } else {
+            vec![location.clone()]
+        };
+        for path in matches {
+            if !expanded.iter().any(|(p, _)| *p == path) {
+                expanded.push((path, layer));
+            }
+        }
+    }
+    expanded
+}
+
+/// One `.pkgignore` pattern.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory holding the `.pkgignore`
+    base: PathBuf,
+    pattern: glob::Pattern,
+    /// Contains `/`: matched against the path relative to `base`,
+    /// otherwise against the file name at any depth
+    anchored: bool,
+    /// Trailing `/` (or `/**`): directories only
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    /// Parse one line; None for blanks, comments and bad globs.
+    fn parse(base: &Path, line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        if line.starts_with('!') {
+            warn!("{}: negated pattern '{}' is not supported", base.join(PKGIGNORE_FILE).display(), line);
+            return None;
+        }
+        // `dir/**` ignores everything inside, same as pruning `dir` itself
+        let dir_only = line.ends_with('/') || line.ends_with("/**");
+        let anchored = line.trim_end_matches('/').contains('/');
+        let glob = line.trim_end_matches("/**").trim_end_matches('/').trim_start_matches('/');
+        let pattern = glob::Pattern::new(glob)
+            .inspect_err(|e| warn!("{}: bad pattern '{}': {}", base.join(PKGIGNORE_FILE).display(), line, e))
+            .ok()?;
+        Some(Self {
+            base: base.to_path_buf(),
+            pattern,
+            anchored,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+        if self.anchored {
+            path.strip_prefix(&self.base)
+                .is_ok_and(|rel| self.pattern.matches_path_with(rel, options))
+        } else {
+            path.file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), options))
+        }
+    }
+}
+
+/// Walk callback: pick up `dir`'s `.pkgignore` (its rules pass down to
+/// subdirectories via `rules`) and drop ignored children.
+///
+/// Returns the dropped paths.
+fn prune_ignored(
+    dir: &Path,
+    rules: &mut Vec<IgnoreRule>,
+    children: &mut Vec<jwalk::Result<jwalk::DirEntry<(Vec<IgnoreRule>, ())>>>,
+) -> Vec<PathBuf> {
+    let has_ignore_file = children.iter().flatten().any(|e| e.file_name() == PKGIGNORE_FILE);
+    if has_ignore_file {
+        match std::fs::read_to_string(dir.join(PKGIGNORE_FILE)) {
+            Ok(text) => rules.extend(text.lines().filter_map(|line| IgnoreRule::parse(dir, line))),
+            Err(e) => warn!("Failed to read {}: {}", dir.join(PKGIGNORE_FILE).display(), e),
+        }
+    }
+    if rules.is_empty() {
+        return Vec::new();
+    }
+
+    let mut skipped = Vec::new();
+    children.retain(|entry| {
+        let Ok(entry) = entry else {
+            return true;
+        };
+        let path = entry.path();
+        if rules.iter().any(|r| r.matches(&path, entry.file_type().is_dir())) {
+            skipped.push(path);
+            return false;
+        }
+        true
+    });
+    skipped
+}
+
+/// Whether a file name is a package definition picked up by scans.
+pub fn is_package_file(name: &str) -> bool {
+    name == PACKAGE_FILE || name == PACKAGE_FILE_GZ || name == PACKAGE_TOML
+}
+
+/// Keep one definition per directory: `package.py` over `package.py.gz`
+/// over `package.toml`.
+fn prefer_plain_sources(
+    files: Vec<(PathBuf, PathBuf)>,
+    warnings: &mut Vec<String>,
+) -> Vec<(PathBuf, PathBuf)> {
+    const PREFERENCE: [&str; 3] = [PACKAGE_FILE, PACKAGE_FILE_GZ, PACKAGE_TOML];
+    let present: HashSet<PathBuf> = files.iter().map(|(_, path)| path.clone()).collect();
+
+    files
+        .into_iter()
+        .filter(|(_, path)| {
+            let Some(rank) = PREFERENCE.iter().position(|n| path.file_name().is_some_and(|f| f == *n)) else {
+                return true;
+            };
+            let winner = PREFERENCE[..rank]
+                .iter()
+                .find(|n| present.contains(&path.with_file_name(n)));
+            if let Some(winner) = winner {
+                let msg = format!("Ignoring {}: {} exists next to it", path.display(), winner);
+                warn!("{}", msg);
+                warnings.push(msg);
+            }
+            winner.is_none()
+        })
+        .collect()
+}
+
+/// Warnings for envs that `set` the same variable more than once.
+fn duplicate_set_warnings(pkg: &Package) -> Vec<String> {
+    pkg.envs
+        .iter()
+        .flat_map(|env| {
+            env.duplicate_sets().into_iter().map(move |(var, count)| {
+                format!(
+                    "Package '{}' env '{}': {} is set {} times (last wins)",
+                    pkg.name, env.name, var, count
+                )
+            })
+        })
+        .collect()
+}
+
 impl Default for Storage {
     fn default() -> Self {
         Self::empty()
@@ -770,4 +1362,411 @@ mod tests {
         assert!(bases.contains(&"maya".to_string()));
         assert!(bases.contains(&"houdini".to_string()));
     }
+
+    #[test]
+    fn storage_libraries_applications() {
+        let mut maya = Package::new("maya".to_string(), "2026.0.0".to_string());
+        maya.add_app(crate::app::App::named("maya"));
+        let ocio = Package::new("ocio".to_string(), "2.1.0".to_string());
+        let storage = Storage::from_packages(vec![maya, ocio]);
+
+        assert_eq!(storage.applications(), vec!["maya-2026.0.0"]);
+        assert_eq!(storage.libraries(), vec!["ocio-2.1.0"]);
+    }
+
+    #[test]
+    fn storage_readonly_locations() {
+        let release = tempfile::TempDir::new().unwrap();
+        let dev = tempfile::TempDir::new().unwrap();
+        std::fs::write(release.path().join(READONLY_MARKER), "").unwrap();
+
+        let paths = vec![release.path().to_path_buf(), dev.path().to_path_buf()];
+        let storage = Storage::scan_impl(Some(&paths)).unwrap();
+
+        assert_eq!(
+            storage.readonly_locations(),
+            vec![release.path().to_string_lossy().to_string()]
+        );
+        assert!(storage.is_readonly(&release.path().join("maya").join("2026.0.0")));
+        assert!(!storage.is_readonly(&dev.path().join("maya").join("2026.0.0")));
+        assert!(!storage.is_readonly(Path::new("/somewhere/else")));
+    }
+
+    #[test]
+    fn storage_list_hidden() {
+        let mut maya = Package::new("maya".to_string(), "2026.0.0".to_string());
+        maya.add_req("maya-internal".to_string());
+        let mut internal = Package::new("maya-internal".to_string(), "1.0.0".to_string());
+        internal.hidden = true;
+        let storage = Storage::from_packages(vec![maya, internal]);
+
+        let names = |pkgs: Vec<Package>| {
+            let mut names: Vec<String> = pkgs.into_iter().map(|p| p.name).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(storage.list(None, false)), vec!["maya-2026.0.0"]);
+        assert_eq!(
+            names(storage.list(None, true)),
+            vec!["maya-2026.0.0", "maya-internal-1.0.0"]
+        );
+
+        // Hidden packages still resolve as dependencies
+        let solver = crate::Solver::from_packages(&storage.all_packages()).unwrap();
+        let solution = solver
+            .solve_requirements_impl(&["maya".to_string()])
+            .unwrap();
+        assert!(solution.contains(&"maya-internal-1.0.0".to_string()));
+    }
+
+    #[test]
+    fn storage_scan_cancelled() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let started = repo.path().join("started");
+        let release = repo.path().join("release");
+        let (started_py, release_py) = (
+            started.to_string_lossy().into_owned(),
+            release.to_string_lossy().into_owned(),
+        );
+        // Each package.py signals that loading began, then waits for the
+        // test, so the token is cancelled while the scan is running
+        for base in ["alpha", "beta"] {
+            let pkg_dir = repo.path().join(base).join("1.0.0");
+            std::fs::create_dir_all(&pkg_dir).unwrap();
+            let source = format!(
+                "import os, time\n\ndef get_package():\n    open({started_py:?}, 'w').close()\n    for _ in range(1000):\n        if os.path.exists({release_py:?}):\n            break\n        time.sleep(0.01)\n    return pkg.Package({base:?}, \"1.0.0\")\n"
+            );
+            std::fs::write(pkg_dir.join(PACKAGE_FILE), source).unwrap();
+        }
+        let paths = vec![repo.path().to_path_buf()];
+
+        let token = crate::cancel::CancelToken::new();
+        let scan = {
+            let token = token.clone();
+            std::thread::spawn(move || Storage::scan_cancellable(Some(&paths), &token))
+        };
+        for _ in 0..1000 {
+            if started.exists() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(started.exists(), "scan never started loading");
+        token.cancel();
+        std::fs::write(&release, "").unwrap();
+
+        // The first package finishes, the second is never loaded
+        let result = scan.join().unwrap();
+        assert!(matches!(result, Err(StorageError::Cancelled)));
+    }
+
+    #[test]
+    fn storage_load_error_line() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let pkg_dir = repo.path().join("tool").join("1.0.0");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        let path = pkg_dir.join(PACKAGE_FILE);
+        std::fs::write(&path, "def get_package():\n    return pkg.Package(\"tool\", ROOT)\n").unwrap();
+
+        let storage = Storage::scan_impl(Some(&[repo.path().to_path_buf()])).unwrap();
+        let expected = format!("{}:2: NameError", path.display());
+        assert!(storage.warnings.iter().any(|w| w.starts_with(&expected)), "{:?}", storage.warnings);
+    }
+
+    #[test]
+    fn storage_pkgignore() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let studio = repo.path().join("studio");
+        for (dir, base) in [("ocio", "ocio"), ("vendor/thirdparty/usd", "usd"), ("ocio/2.1.0/tests", "fixture")] {
+            let pkg_dir = studio.join(dir).join("1.0.0");
+            std::fs::create_dir_all(&pkg_dir).unwrap();
+            std::fs::write(
+                pkg_dir.join(PACKAGE_FILE),
+                format!("def get_package():\n    return pkg.Package(\"{base}\", \"1.0.0\")\n"),
+            )
+            .unwrap();
+        }
+        // Nested ignore file: anchored subtree plus a name matched at any depth
+        std::fs::write(studio.join(PKGIGNORE_FILE), "# vendored trees\nvendor/**\ntests/\n").unwrap();
+
+        let storage = Storage::scan_impl(Some(&[repo.path().to_path_buf()])).unwrap();
+        assert!(storage.has("ocio-1.0.0"));
+        assert!(!storage.has("usd-1.0.0"));
+        assert!(!storage.has("fixture-1.0.0"));
+
+        let rule = IgnoreRule::parse(Path::new("/repo"), "vendor/**").unwrap();
+        assert!(rule.matches(Path::new("/repo/vendor"), true));
+        assert!(!rule.matches(Path::new("/repo/sub/vendor"), true));
+        assert!(IgnoreRule::parse(Path::new("/repo"), "# comment").is_none());
+    }
+
+    #[test]
+    fn storage_layered_override() {
+        let user = tempfile::TempDir::new().unwrap();
+        let release = tempfile::TempDir::new().unwrap();
+        for (layer, base, tag) in [(&user, "ocio", "user"), (&release, "ocio", "release"), (&release, "usd", "release")] {
+            let pkg_dir = layer.path().join(base).join("2.1.0");
+            std::fs::create_dir_all(&pkg_dir).unwrap();
+            std::fs::write(
+                pkg_dir.join(PACKAGE_FILE),
+                format!(
+                    "def get_package():\n    p = pkg.Package(\"{base}\", \"2.1.0\")\n    \
+                     p.tags = [\"{tag}\"]\n    return p\n"
+                ),
+            )
+            .unwrap();
+        }
+
+        let layers = vec![user.path().to_path_buf(), release.path().to_path_buf()];
+        let storage = Storage::scan_layered(&layers).unwrap();
+        let ocio = storage.get("ocio-2.1.0").unwrap();
+        assert_eq!(ocio.tags, vec!["user"]);
+        assert_eq!(ocio.origin_layer, Some(0));
+        assert_eq!(storage.get("usd-2.1.0").unwrap().origin_layer, Some(1));
+        assert!(storage.warnings.iter().any(|w| w.contains("'ocio-2.1.0'") && w.contains("overridden by")));
+    }
+
+    #[test]
+    fn storage_version_origins() {
+        let studio = tempfile::TempDir::new().unwrap();
+        let site = tempfile::TempDir::new().unwrap();
+        for (location, version) in [(&studio, "2026.0.0"), (&site, "2025.0.0")] {
+            let pkg_dir = location.path().join("maya").join(version);
+            std::fs::create_dir_all(&pkg_dir).unwrap();
+            std::fs::write(
+                pkg_dir.join(PACKAGE_FILE),
+                format!("def get_package():\n    return pkg.Package(\"maya\", \"{version}\")\n"),
+            )
+            .unwrap();
+        }
+
+        let paths = vec![studio.path().to_path_buf(), site.path().to_path_buf()];
+        let storage = Storage::scan_impl(Some(&paths)).unwrap();
+        assert_eq!(
+            storage.version_origins("maya"),
+            vec![
+                ("maya-2026.0.0".to_string(), studio.path().to_path_buf()),
+                ("maya-2025.0.0".to_string(), site.path().to_path_buf()),
+            ]
+        );
+
+        // Manually added packages have no origin
+        let manual = Storage::from_packages(vec![Package::new("maya".to_string(), "1.0.0".to_string())]);
+        assert!(manual.version_origins("maya").is_empty());
+    }
+
+    #[test]
+    fn storage_scan_glob_locations() {
+        let root = tempfile::TempDir::new().unwrap();
+        for (studio, base) in [("a", "ocio"), ("b", "usd")] {
+            let dir = root.path().join(studio).join("packages").join(base).join("1.0.0");
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(
+                dir.join(PACKAGE_FILE),
+                format!("def get_package():\n    return pkg.Package(\"{}\", \"1.0.0\")\n", base),
+            )
+            .unwrap();
+        }
+        // Not a directory: must not become a location
+        std::fs::write(root.path().join("c"), "").unwrap();
+
+        let extra = tempfile::TempDir::new().unwrap();
+        let exr_dir = extra.path().join("openexr").join("3.2.0");
+        std::fs::create_dir_all(&exr_dir).unwrap();
+        std::fs::write(exr_dir.join(PACKAGE_TOML), "[package]\nbase = \"openexr\"\nversion = \"3.2.0\"\n").unwrap();
+
+        let pattern = root.path().join("*").join("packages");
+        let paths = vec![pattern.clone(), pattern, extra.path().to_path_buf()];
+        let storage = Storage::scan_impl(Some(&paths)).unwrap();
+        assert!(storage.has("ocio-1.0.0"));
+        assert!(storage.has("usd-1.0.0"));
+        assert_eq!(
+            storage.locations,
+            vec![
+                root.path().join("a").join("packages"),
+                root.path().join("b").join("packages"),
+                extra.path().to_path_buf(),
+            ]
+        );
+        // Layers are the caller's entries, not the expanded locations
+        assert_eq!(storage.get("ocio-1.0.0").unwrap().origin_layer, Some(0));
+        assert_eq!(storage.get("usd-1.0.0").unwrap().origin_layer, Some(0));
+        assert_eq!(storage.get("openexr-3.2.0").unwrap().origin_layer, Some(2));
+    }
+
+    #[test]
+    fn storage_scan_gzipped() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let source = "def get_package():\n    return pkg.Package(\"ocio\", \"2.1.0\")\n";
+        let gzip = |path: &Path| {
+            let mut encoder = GzEncoder::new(std::fs::File::create(path).unwrap(), flate2::Compression::default());
+            encoder.write_all(source.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+        };
+
+        let plain_repo = tempfile::TempDir::new().unwrap();
+        let gz_repo = tempfile::TempDir::new().unwrap();
+        let plain_dir = plain_repo.path().join("ocio").join("2.1.0");
+        let gz_dir = gz_repo.path().join("ocio").join("2.1.0");
+        std::fs::create_dir_all(&plain_dir).unwrap();
+        std::fs::create_dir_all(&gz_dir).unwrap();
+        std::fs::write(plain_dir.join(PACKAGE_FILE), source).unwrap();
+        gzip(&gz_dir.join(PACKAGE_FILE_GZ));
+
+        let plain_paths = vec![plain_repo.path().to_path_buf()];
+        let gz_paths = vec![gz_repo.path().to_path_buf()];
+        let plain = Storage::scan_impl(Some(&plain_paths)).unwrap();
+        let gz = Storage::scan_impl(Some(&gz_paths)).unwrap();
+        let mut plain_pkg = plain.get("ocio-2.1.0").unwrap();
+        let mut gz_pkg = gz.get("ocio-2.1.0").unwrap();
+        assert_eq!(
+            gz_pkg.package_source.as_deref(),
+            Some(gz_dir.join(PACKAGE_FILE_GZ).to_string_lossy().as_ref())
+        );
+        plain_pkg.package_source = None;
+        gz_pkg.package_source = None;
+        plain_pkg.released = None;
+        gz_pkg.released = None;
+        assert_eq!(plain_pkg, gz_pkg);
+
+        // Both forms in one directory: plain wins with a warning
+        gzip(&plain_dir.join(PACKAGE_FILE_GZ));
+        let both = Storage::scan_impl(Some(&plain_paths)).unwrap();
+        assert_eq!(both.count(), 1);
+        assert_eq!(
+            both.get("ocio-2.1.0").unwrap().package_source.as_deref(),
+            Some(plain_dir.join(PACKAGE_FILE).to_string_lossy().as_ref())
+        );
+        assert!(both.warnings.iter().any(|w| w.contains(PACKAGE_FILE_GZ)));
+    }
+
+    #[test]
+    fn storage_scan_toml() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let toml = "[package]\nbase = \"ocio\"\nversion = \"2.1.0\"\n";
+        for version in ["2.1.0", "2.2.0"] {
+            let dir = repo.path().join("ocio").join(version);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(PACKAGE_TOML), toml.replace("2.1.0", version)).unwrap();
+        }
+        // package.py next to a package.toml wins
+        let py_dir = repo.path().join("ocio").join("2.2.0");
+        std::fs::write(
+            py_dir.join(PACKAGE_FILE),
+            "def get_package():\n    p = pkg.Package(\"ocio\", \"2.2.0\")\n    p.add_tag(\"py\")\n    return p\n",
+        )
+        .unwrap();
+
+        let storage = Storage::scan_impl(Some(&[repo.path().to_path_buf()])).unwrap();
+        assert_eq!(storage.count(), 2);
+        assert!(storage.get("ocio-2.1.0").unwrap().tags.is_empty());
+        assert_eq!(storage.get("ocio-2.2.0").unwrap().tags, vec!["py"]);
+        let ignored = py_dir.join(PACKAGE_TOML).display().to_string();
+        assert!(storage.warnings.iter().any(|w| w.contains(&ignored)), "{:?}", storage.warnings);
+    }
+
+    #[test]
+    fn storage_released_before() {
+        let mut storage = Storage::empty();
+        for (version, released) in [("2.1.0", Some(100)), ("2.3.0", Some(200)), ("2.0.0", None)] {
+            let mut pkg = Package::new("ocio".to_string(), version.to_string());
+            pkg.released = released;
+            storage.add(pkg);
+        }
+
+        let dated = storage.released_before(150);
+        assert_eq!(dated.versions("ocio"), vec!["ocio-2.1.0", "ocio-2.0.0"]);
+        assert_eq!(dated.latest("ocio").unwrap().version, "2.1.0");
+        assert_eq!(storage.released_before(200).count(), 3);
+    }
+
+    #[test]
+    fn storage_for_platform() {
+        let mut storage = Storage::empty();
+        for (name, tags) in [
+            ("any", vec![]),
+            ("linux", vec!["platform-linux"]),
+            ("windows", vec!["platform-windows", "arch-x86_64"]),
+            ("linux_arm", vec!["platform-linux", "arch-aarch64"]),
+            ("unix", vec!["platform-linux", "platform-macos", "gpu"]),
+        ] {
+            let mut pkg = Package::new(name.to_string(), "1.0.0".to_string());
+            pkg.tags = tags.into_iter().map(String::from).collect();
+            storage.add(pkg);
+        }
+
+        let names = |s: &Storage| {
+            let mut bases = s.bases();
+            bases.sort();
+            bases
+        };
+        assert_eq!(names(&storage.for_platform("linux", "x86_64")), ["any", "linux", "unix"]);
+        assert_eq!(names(&storage.for_platform("linux", "aarch64")), ["any", "linux", "linux_arm", "unix"]);
+        assert_eq!(names(&storage.for_platform("windows", "x86_64")), ["any", "windows"]);
+        assert_eq!(storage.count(), 5);
+    }
+
+    #[test]
+    fn storage_rescan_keeps_platform() {
+        let repo = tempfile::TempDir::new().unwrap();
+        for (base, tag) in [("ocio", "platform-linux"), ("dx", "platform-windows")] {
+            let dir = repo.path().join(base).join("1.0.0");
+            std::fs::create_dir_all(&dir).unwrap();
+            let toml = format!("[package]\nbase = \"{base}\"\nversion = \"1.0.0\"\ntags = [\"{tag}\"]\n");
+            std::fs::write(dir.join(PACKAGE_TOML), toml).unwrap();
+        }
+
+        let storage = Storage::scan_impl(Some(&[repo.path().to_path_buf()])).unwrap();
+        assert_eq!(storage.count(), 2);
+        let linux = storage.for_platform("linux", "x86_64");
+        assert_eq!(linux.bases(), ["ocio"]);
+        assert_eq!(linux.rescan().unwrap().bases(), ["ocio"]);
+        assert_eq!(storage.rescan().unwrap().count(), 2);
+    }
+
+    #[test]
+    fn storage_exclude_preview() {
+        let make = || {
+            Storage::from_packages(vec![
+                Package::new("maya".to_string(), "2025.0.0".to_string()),
+                Package::new("maya".to_string(), "2026.0.0".to_string()),
+                Package::new("maya-usd".to_string(), "0.30.0".to_string()),
+                Package::new("houdini".to_string(), "20.0.0".to_string()),
+                Package::new("redshift".to_string(), "3.5.0".to_string()),
+            ])
+        };
+
+        for patterns in [
+            vec!["maya-2025.0.0".to_string()],
+            vec!["houdini".to_string()],
+            vec!["maya*".to_string()],
+            vec!["*-3.5.0".to_string(), "houdini".to_string()],
+            vec!["nuke".to_string()],
+        ] {
+            let mut storage = make();
+            let preview = storage.exclude_preview(&patterns);
+            assert_eq!(storage.count(), 5, "preview must not mutate");
+
+            storage.exclude_packages(&patterns);
+            let mut remaining: Vec<String> = storage.packages_iter().map(|p| p.name.clone()).collect();
+            remaining.sort();
+            let mut removed: Vec<String> = make()
+                .packages_iter()
+                .map(|p| p.name.clone())
+                .filter(|n| !remaining.contains(n))
+                .collect();
+            removed.sort();
+
+            assert_eq!(preview, removed, "patterns {:?}", patterns);
+        }
+
+        let storage = make();
+        assert_eq!(
+            storage.exclude_preview(&["maya*".to_string()]),
+            vec!["maya-2025.0.0", "maya-2026.0.0", "maya-usd-0.30.0"]
+        );
+        assert_eq!(storage.exclude_preview(&["houdini".to_string()]), vec!["houdini-20.0.0"]);
+    }
 }
@@ -3,6 +3,15 @@
 //! Provides unified `{TOKEN}` expansion logic used by both [`Evar`](crate::evar::Evar) and [`Env`](crate::env::Env).
 //! Supports recursive expansion with cycle detection and depth limiting.
 //!
+//! # Defaults
+//!
+//! `{VAR:-default}` expands to `VAR` when it resolves (including the OS
+//! fallback, where enabled) and to `default` otherwise. The default may
+//! itself contain tokens, e.g. `{A:-{B:-x}}`. A bare `{VAR}` that doesn't
+//! resolve is left as-is. Defaults apply to the recursive expanders
+//! ([`expand_recursive`], [`expand_with_fallback`], [`expand_strict`] and
+//! [`Expander`]), not to single-pass [`expand_tokens`].
+//!
 //! # Example
 //!
 //! ```ignore
@@ -63,17 +72,18 @@ pub fn extract(value: &str) -> HashSet<String> {
 
     while i < chars.len() {
         if chars[i] == '{' {
-            let start = i + 1;
-            let mut end = start;
+            if let Some((token, default, next)) = parse_token(&chars, i) {
+                tokens.insert(token);
+                if let Some(default) = default {
+                    tokens.extend(extract(&default));
+                }
+                i = next;
+                continue;
+            }
+            let mut end = i + 1;
             while end < chars.len() && chars[end] != '}' {
                 end += 1;
             }
-            if end < chars.len() {
-                let token: String = chars[start..end].iter().collect();
-                if is_valid_identifier(&token) {
-                    tokens.insert(token);
-                }
-            }
             i = end + 1;
         } else {
             i += 1;
@@ -83,6 +93,44 @@ pub fn extract(value: &str) -> HashSet<String> {
     tokens
 }
 
+/// Parse the token starting at `chars[i] == '{'`.
+///
+/// Accepts `{NAME}` and `{NAME:-default}`, where the default may contain
+/// nested braces.
+///
+/// # Returns
+/// (name, default text, index after the closing brace), or None if this
+/// isn't a token.
+fn parse_token(chars: &[char], i: usize) -> Option<(String, Option<String>, usize)> {
+    let start = i + 1;
+    let close = start + chars[start..].iter().position(|&c| c == '}')?;
+    let token: String = chars[start..close].iter().collect();
+    if is_valid_identifier(&token) {
+        return Some((token, None, close + 1));
+    }
+
+    let colon = start + chars[start..].iter().position(|&c| c == ':')?;
+    let name: String = chars[start..colon].iter().collect();
+    if !is_valid_identifier(&name) || chars.get(colon + 1) != Some(&'-') {
+        return None;
+    }
+    let mut depth = 1;
+    for (j, &c) in chars.iter().enumerate().skip(colon + 2) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let default = chars[colon + 2..j].iter().collect();
+                    return Some((name, Some(default), j + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Check if string contains any `{TOKEN}` patterns.
 #[inline]
 pub fn has_tokens(value: &str) -> bool {
@@ -149,6 +197,7 @@ where
 /// * `value` - String with `{TOKEN}` patterns
 /// * `lookup` - Token value provider
 /// * `max_depth` - Maximum recursion depth (10 is typical)
+/// * `dollar_syntax` - Also expand `${TOKEN}` (`$${` escapes to `${`)
 ///
 /// # Errors
 /// - [`TokenError::CircularReference`] if A references B which references A
@@ -157,9 +206,11 @@ pub fn expand_recursive(
     value: &str,
     lookup: &HashMap<String, String>,
     max_depth: usize,
+    dollar_syntax: bool,
 ) -> Result<String, TokenError> {
-    let mut visiting: HashSet<String> = HashSet::new();
-    expand_impl(value, lookup, &mut visiting, 0, max_depth)
+    Expander::new(lookup, max_depth)
+        .with_dollar_syntax(dollar_syntax)
+        .expand(value)
 }
 
 /// Expand with OS environment fallback.
@@ -170,106 +221,230 @@ pub fn expand_with_fallback(
     lookup: &HashMap<String, String>,
     max_depth: usize,
 ) -> Result<String, TokenError> {
-    let mut visiting: HashSet<String> = HashSet::new();
-    expand_impl_with_fallback(value, lookup, &mut visiting, 0, max_depth, true)
+    Expander::new(lookup, max_depth)
+        .with_os_fallback(true)
+        .expand(value)
 }
 
-/// Internal recursive expansion.
-fn expand_impl(
+/// Expand in strict mode.
+///
+/// Only names in `lookup` are substituted. Names in `private` (lowercase)
+/// are left as literal `{TOKEN}` even when `use_os_fallback` is set, so
+/// internal variables can't be picked up from the OS environment either.
+pub fn expand_strict(
     value: &str,
     lookup: &HashMap<String, String>,
-    visiting: &mut HashSet<String>,
-    depth: usize,
+    private: &HashSet<String>,
     max_depth: usize,
+    use_os_fallback: bool,
 ) -> Result<String, TokenError> {
-    expand_impl_with_fallback(value, lookup, visiting, depth, max_depth, false)
+    Expander::new(lookup, max_depth)
+        .with_os_fallback(use_os_fallback)
+        .with_private(private.clone())
+        .expand(value)
 }
 
-/// Internal recursive expansion with optional OS fallback.
-fn expand_impl_with_fallback(
-    value: &str,
-    lookup: &HashMap<String, String>,
-    visiting: &mut HashSet<String>,
-    depth: usize,
+/// `$${` at `i`: an escaped literal `${`.
+#[inline]
+fn is_dollar_escape(chars: &[char], i: usize) -> bool {
+    chars[i..].starts_with(&['$', '$', '{'])
+}
+
+/// The `{` at `i` follows a `$`, making it a `${TOKEN}`.
+///
+/// The `$` was copied to the output as a literal and is dropped once
+/// the token resolves.
+#[inline]
+fn is_dollar_prefixed(chars: &[char], i: usize) -> bool {
+    i > 0 && chars[i - 1] == '$'
+}
+
+/// Memoizing expander for a single solve.
+///
+/// Each lookup variable's value is expanded at most once and reused for
+/// every later reference. [`expand_recursive`], [`expand_with_fallback`]
+/// and [`expand_strict`] are one-shot expanders: a successfully expanded
+/// variable can't reach a cycle, and the depth limit is enforced by
+/// caching how deep each expansion nests.
+pub struct Expander<'a> {
+    lookup: &'a HashMap<String, String>,
+    private: HashSet<String>,
     max_depth: usize,
     use_os_fallback: bool,
-) -> Result<String, TokenError> {
-    trace!("token::expand depth={} value={}", depth, value);
-    
-    if depth > max_depth {
-        return Err(TokenError::DepthExceeded {
-            name: String::new(),
+    dollar_syntax: bool,
+    /// Lowercase name -> (expanded value, nesting height below the value)
+    cache: HashMap<String, (String, usize)>,
+    expansions: usize,
+}
+
+impl<'a> Expander<'a> {
+    /// Expander over `lookup` (lowercase keys) without OS fallback.
+    pub fn new(lookup: &'a HashMap<String, String>, max_depth: usize) -> Self {
+        Self {
+            lookup,
+            private: HashSet::new(),
             max_depth,
-        });
+            use_os_fallback: false,
+            dollar_syntax: false,
+            cache: HashMap::new(),
+            expansions: 0,
+        }
     }
 
-    // Quick exit if no tokens
-    if !has_tokens(value) {
-        return Ok(value.to_string());
+    /// Fall back to the OS environment for names missing from lookup.
+    pub fn with_os_fallback(mut self, use_os_fallback: bool) -> Self {
+        self.use_os_fallback = use_os_fallback;
+        self
     }
 
-    let mut result = String::with_capacity(value.len());
-    let chars: Vec<char> = value.chars().collect();
-    let mut i = 0;
+    /// Also expand `${TOKEN}`, with `$${` escaping to `${`
+    /// (see [`expand_recursive`]).
+    pub fn with_dollar_syntax(mut self, dollar_syntax: bool) -> Self {
+        self.dollar_syntax = dollar_syntax;
+        self
+    }
 
-    while i < chars.len() {
-        if chars[i] == '{' {
-            let start = i + 1;
-            let mut end = start;
-            while end < chars.len() && chars[end] != '}' {
-                end += 1;
-            }
+    /// Names (lowercase) that never fall back to the OS (see [`expand_strict`]).
+    pub fn with_private(mut self, private: HashSet<String>) -> Self {
+        self.private = private;
+        self
+    }
 
-            if end < chars.len() {
-                let token: String = chars[start..end].iter().collect();
+    /// Number of lookup values expanded so far (cache misses).
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
 
-                if is_valid_identifier(&token) {
+    /// Expand `value` recursively, reusing earlier variable expansions.
+    pub fn expand(&mut self, value: &str) -> Result<String, TokenError> {
+        let mut visiting = HashSet::new();
+        self.expand_at(value, &mut visiting, 0).map(|(expanded, _)| expanded)
+    }
+
+    /// Expand `value` at `depth`, returning it with its nesting height.
+    fn expand_at(
+        &mut self,
+        value: &str,
+        visiting: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<(String, usize), TokenError> {
+        trace!("token::expand depth={} value={}", depth, value);
+        if !has_tokens(value) {
+            return Ok((value.to_string(), 0));
+        }
+
+        let mut height = 0;
+        let mut result = String::with_capacity(value.len());
+        let chars: Vec<char> = value.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            if self.dollar_syntax && is_dollar_escape(&chars, i) {
+                result.push_str("${");
+                i += 3;
+            } else if chars[i] == '{' {
+                if let Some((token, default, next)) = parse_token(&chars, i) {
                     let token_lower = token.to_lowercase();
 
-                    // Cycle detection
                     if visiting.contains(&token_lower) {
                         return Err(TokenError::CircularReference { name: token });
                     }
 
-                    // Try lookup
-                    let replacement = if let Some(val) = lookup.get(&token_lower) {
-                        // Recursively expand the value
-                        visiting.insert(token_lower.clone());
-                        let expanded = expand_impl_with_fallback(
-                            val,
-                            lookup,
-                            visiting,
-                            depth + 1,
-                            max_depth,
-                            use_os_fallback,
-                        )?;
-                        visiting.remove(&token_lower);
+                    let replacement = if self.lookup.contains_key(&token_lower) {
+                        let (expanded, h) = self.expand_var(&token, visiting, depth + 1)?;
+                        height = height.max(h + 1);
                         Some(expanded)
-                    } else if use_os_fallback {
-                        // Try OS environment
+                    } else if self.use_os_fallback && !self.private.contains(&token_lower) {
                         std::env::var(&token).ok()
                     } else {
                         None
                     };
 
-                    if let Some(ref rep) = replacement {
-                        trace!("token::expand {{{}}} -> {}", token, rep);
-                        result.push_str(rep);
-                        i = end + 1;
+                    let replacement = match (replacement, default) {
+                        (None, Some(default)) => {
+                            self.check_depth(&token, depth + 1)?;
+                            let (expanded, h) = self.expand_at(&default, visiting, depth + 1)?;
+                            height = height.max(h + 1);
+                            Some(expanded)
+                        }
+                        (replacement, _) => replacement,
+                    };
+
+                    if let Some(rep) = replacement {
+                        if self.dollar_syntax && is_dollar_prefixed(&chars, i) {
+                            result.pop();
+                        }
+                        result.push_str(&rep);
+                        i = next;
                         continue;
                     }
                 }
+                result.push('{');
+                i += 1;
+            } else {
+                result.push(chars[i]);
+                i += 1;
             }
-            // Token not found - keep original
-            result.push('{');
-            i += 1;
-        } else {
-            result.push(chars[i]);
-            i += 1;
         }
+
+        Ok((result, height))
+    }
+
+    /// Expand lookup variable `token` whose value sits at `depth`.
+    fn expand_var(
+        &mut self,
+        token: &str,
+        visiting: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<(String, usize), TokenError> {
+        let name = token.to_lowercase();
+        if let Some((expanded, height)) = self.cache.get(&name) {
+            // Same check the uncached expansion would hit somewhere below
+            let (expanded, height) = (expanded.clone(), *height);
+            self.check_depth(token, depth + height)?;
+            return Ok((expanded, height));
+        }
+        self.check_depth(token, depth)?;
+
+        let lookup = self.lookup;
+        let value = &lookup[&name];
+        self.expansions += 1;
+        visiting.insert(name.clone());
+        let result = self.expand_at(value, visiting, depth);
+        visiting.remove(&name);
+
+        let (expanded, height) = result?;
+        trace!("token::expand {{{}}} -> {}", token, expanded);
+        self.cache.insert(name, (expanded.clone(), height));
+        Ok((expanded, height))
+    }
+
+    /// Fail if `token`'s value would be expanded deeper than `max_depth`.
+    fn check_depth(&self, token: &str, depth: usize) -> Result<(), TokenError> {
+        if depth > self.max_depth {
+            return Err(TokenError::DepthExceeded {
+                name: token.to_string(),
+                max_depth: self.max_depth,
+            });
+        }
+        Ok(())
     }
+}
+
+/// Built-in token for the directory containing a package's definition file.
+///
+/// Expanded per package (before envs are merged), so `{PKG_ROOT}/bin`
+/// always refers to the package that declared it.
+pub const PKG_ROOT: &str = "PKG_ROOT";
 
-    Ok(result)
+/// Replace `{PKG_ROOT}` with the given package root directory.
+///
+/// Matching is case-insensitive like all token lookups. Other tokens are
+/// left as-is for the regular solve pass.
+pub fn expand_pkg_root(value: &str, root: &str) -> String {
+    expand_tokens(value, |name| {
+        name.eq_ignore_ascii_case(PKG_ROOT).then(|| root.to_string())
+    })
 }
 
 /// Check if string is valid identifier (alphanumeric + underscore).
@@ -321,7 +496,7 @@ mod tests {
         .into_iter()
         .collect();
 
-        let result = expand_recursive("{C}", &lookup, 10).unwrap();
+        let result = expand_recursive("{C}", &lookup, 10, false).unwrap();
         assert_eq!(result, "base/level1/level2");
     }
 
@@ -334,7 +509,7 @@ mod tests {
         .into_iter()
         .collect();
 
-        let result = expand_recursive("{A}", &lookup, 10);
+        let result = expand_recursive("{A}", &lookup, 10, false);
         assert!(matches!(result, Err(TokenError::CircularReference { .. })));
     }
 
@@ -347,8 +522,69 @@ mod tests {
             lookup.insert(format!("v{}", i), format!("{{V{}}}", i - 1));
         }
 
-        let result = expand_recursive("{V15}", &lookup, 5);
-        assert!(matches!(result, Err(TokenError::DepthExceeded { .. })));
+        let result = expand_recursive("{V15}", &lookup, 5, false);
+        assert_eq!(
+            result,
+            Err(TokenError::DepthExceeded { name: "V10".to_string(), max_depth: 5 })
+        );
+    }
+
+    #[test]
+    fn expand_defaults() {
+        let empty: HashMap<String, String> = HashMap::new();
+        assert_eq!(expand_recursive("{A:-/opt}/bin", &empty, 10, false).unwrap(), "/opt/bin");
+        assert_eq!(expand_recursive("{A:-{B:-x}}", &empty, 10, false).unwrap(), "x");
+        // Bare unresolved tokens stay literal, also inside defaults
+        assert_eq!(expand_recursive("{A}", &empty, 10, false).unwrap(), "{A}");
+        assert_eq!(expand_recursive("{A:-{B}}", &empty, 10, false).unwrap(), "{B}");
+
+        let lookup: HashMap<String, String> = [
+            ("b".into(), "from_b".into()),
+            ("c".into(), "{C:-loop}".into()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(expand_recursive("{A:-{B:-x}}", &lookup, 10, false).unwrap(), "from_b");
+        assert_eq!(expand_recursive("{B:-x}", &lookup, 10, false).unwrap(), "from_b");
+        assert!(matches!(
+            expand_recursive("{C}", &lookup, 10, false),
+            Err(TokenError::CircularReference { .. })
+        ));
+
+        // OS fallback resolves before the default
+        std::env::set_var("PKG_TOKEN_DEFAULT_TEST", "os");
+        assert_eq!(expand_with_fallback("{PKG_TOKEN_DEFAULT_TEST:-x}", &empty, 10).unwrap(), "os");
+        assert_eq!(expand_recursive("{PKG_TOKEN_DEFAULT_TEST:-x}", &empty, 10, false).unwrap(), "x");
+
+        // Memoizing expander agrees
+        let mut expander = Expander::new(&lookup, 10);
+        assert_eq!(expander.expand("{A:-{B:-x}}/{D:-{E:-y}}").unwrap(), "from_b/y");
+
+        let tokens = extract("{A:-{B:-x}}");
+        assert!(tokens.contains("A") && tokens.contains("B"));
+    }
+
+    #[test]
+    fn expand_dollar_syntax() {
+        let lookup: HashMap<String, String> = [
+            ("root".into(), "/opt/maya".into()),
+            ("bin".into(), "${ROOT}/bin".into()),
+        ]
+        .into_iter()
+        .collect();
+
+        // Both syntaxes in one value, recursively
+        assert_eq!(
+            expand_recursive("${BIN}:{ROOT}/lib", &lookup, 10, true).unwrap(),
+            "/opt/maya/bin:/opt/maya/lib"
+        );
+        // `$${` escapes; unknown `${X}` stays literal
+        assert_eq!(expand_recursive("$${ROOT} ${X}", &lookup, 10, true).unwrap(), "${ROOT} ${X}");
+        // Off by default: `$` is kept as a literal prefix
+        assert_eq!(expand_recursive("${ROOT}", &lookup, 10, false).unwrap(), "$/opt/maya");
+
+        let mut expander = Expander::new(&lookup, 10).with_dollar_syntax(true);
+        assert_eq!(expander.expand("${BIN} $${BIN}").unwrap(), "/opt/maya/bin ${BIN}");
     }
 
     #[test]
@@ -359,4 +595,12 @@ mod tests {
         assert!(!has_tokens("just { brace"));
         assert!(!has_tokens("just } brace"));
     }
+
+    #[test]
+    fn pkg_root_expansion() {
+        assert_eq!(expand_pkg_root("{PKG_ROOT}/bin", "/repo/maya/1.0.0"), "/repo/maya/1.0.0/bin");
+        assert_eq!(expand_pkg_root("{pkg_root}/lib", "/r"), "/r/lib");
+        // Other tokens untouched
+        assert_eq!(expand_pkg_root("{PKG_ROOT}/{SUB}", "/r"), "/r/{SUB}");
+    }
 }
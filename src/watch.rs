@@ -0,0 +1,155 @@
+//! File watching for live [`Storage`] reloads.
+//!
+//! [`Storage::watch`] monitors every scanned location for created, modified
+//! or removed `package.py` files, `.toolsets/*.toml` definitions and
+//! `.pkgignore` files. Editors
+//! often write several times per save, so events are collected until the
+//! locations have been quiet for [`DEBOUNCE`], then the locations are
+//! rescanned once and the callback gets the fresh storage.
+//!
+//! ```ignore
+//! let watcher = storage.watch(|fresh| println!("{} packages", fresh.count()))?;
+//! // ... watching until `watcher` is dropped
+//! ```
+
+use crate::error::StorageError;
+use crate::storage::Storage;
+use log::{debug, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// Quiet period after the last relevant change before rescanning.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Active watch on a storage's locations. Dropping it stops watching.
+pub struct StorageWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl Storage {
+    /// Watch all locations and call `callback` with a rescanned storage
+    /// after package files change.
+    ///
+    /// The callback runs on a background thread. Locations that don't
+    /// exist are skipped.
+    ///
+    /// # Errors
+    /// [`StorageError::WatchFailed`] if the OS watcher can't be set up.
+    pub fn watch<F>(&self, callback: F) -> Result<StorageWatcher, StorageError>
+    where
+        F: FnMut(Storage) + Send + 'static,
+    {
+        let locations = self.location_paths().to_vec();
+        let layers = self.layers.clone();
+        let platform = self.platform.clone();
+        let (tx, rx) = mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event) if is_relevant(&event) => {
+                debug!("watch: {:?} {:?}", event.kind, event.paths);
+                let _ = tx.send(());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("watch: {}", e),
+        })
+        .map_err(|e| watch_failed(Path::new(""), e))?;
+
+        for location in locations.iter().filter(|l| l.is_dir()) {
+            watcher
+                .watch(location, RecursiveMode::Recursive)
+                .map_err(|e| watch_failed(location, e))?;
+        }
+
+        thread::spawn(move || rescan_loop(rx, layers, platform, callback));
+        Ok(StorageWatcher { _watcher: watcher })
+    }
+}
+
+/// Wait for changes, debounce them and rescan. Ends when the watcher is dropped.
+fn rescan_loop<F: FnMut(Storage)>(
+    rx: mpsc::Receiver<()>,
+    layers: Vec<PathBuf>,
+    platform: Option<(String, String)>,
+    mut callback: F,
+) {
+    while rx.recv().is_ok() {
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(()) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        match Storage::rescan_with(&layers, platform.as_ref()) {
+            Ok(storage) => callback(storage),
+            Err(e) => warn!("watch: rescan failed: {}", e),
+        }
+    }
+}
+
+/// Create/modify/remove of a package file or toolset definition.
+fn is_relevant(event: &Event) -> bool {
+    let kind = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_));
+    kind && event.paths.iter().any(|p| is_package_file(p))
+}
+
+fn is_package_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if matches!(name, "package.py" | "package.py.gz" | crate::loader::toml::PACKAGE_TOML)
+        || name == crate::storage::PKGIGNORE_FILE
+    {
+        return true;
+    }
+    let in_toolsets = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|n| n == ".toolsets");
+    in_toolsets && path.extension().is_some_and(|e| e == "toml")
+}
+
+fn watch_failed(path: &Path, e: notify::Error) -> StorageError {
+    StorageError::WatchFailed {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_package_files() {
+        assert!(is_package_file(Path::new("/repo/maya/2026.0.0/package.py")));
+        assert!(is_package_file(Path::new("/repo/.toolsets/studio.toml")));
+        assert!(!is_package_file(Path::new("/repo/maya/2026.0.0/notes.toml")));
+        assert!(!is_package_file(Path::new("/repo/maya/2026.0.0/build.py")));
+    }
+
+    #[test]
+    fn watch_reports_new_package() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let pkg_dir = repo.path().join("ocio").join("2.1.0");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        let storage = Storage::scan_impl(Some(&[repo.path().to_path_buf()])).unwrap();
+        assert!(!storage.has("ocio-2.1.0"));
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = storage.watch(move |fresh| {
+            let _ = tx.send(fresh);
+        })
+        .unwrap();
+
+        std::fs::write(
+            pkg_dir.join("package.py"),
+            "def get_package():\n    return pkg.Package(\"ocio\", \"2.1.0\")\n",
+        )
+        .unwrap();
+
+        let fresh = rx.recv_timeout(Duration::from_secs(10)).expect("callback did not fire");
+        assert!(fresh.has("ocio-2.1.0"));
+    }
+}
@@ -538,3 +538,146 @@ fn test_direct_class_access() {
     let storage = Storage::scan_impl(Some(&[dir.path().to_path_buf()])).unwrap();
     assert!(storage.has("direct-3.0.0"));
 }
+
+#[test]
+fn test_storage_scan_zip() {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let dir = TempDir::new().unwrap();
+    let archive = dir.path().join("repo.zip");
+
+    let mut writer = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+    for (name, version) in [("maya", "2026.1.0"), ("ocio", "2.3.0")] {
+        writer
+            .start_file(format!("{}/{}/package.py", name, version), SimpleFileOptions::default())
+            .unwrap();
+        write!(
+            writer,
+            "def get_package():\n    return pkg.Package(\"{}\", \"{}\")\n",
+            name, version
+        )
+        .unwrap();
+    }
+    writer.start_file("README.txt", SimpleFileOptions::default()).unwrap();
+    writer.write_all(b"not a package").unwrap();
+    writer
+        .start_file("usd/24.0.0/package.toml", SimpleFileOptions::default())
+        .unwrap();
+    writer
+        .write_all(b"[package]\nbase = \"usd\"\nversion = \"24.0.0\"\n")
+        .unwrap();
+    // Shadowed by the package.py next to it, as in a directory scan
+    writer
+        .start_file("ocio/2.3.0/package.toml", SimpleFileOptions::default())
+        .unwrap();
+    writer
+        .write_all(b"[package]\nbase = \"ocio\"\nversion = \"2.3.0\"\n")
+        .unwrap();
+    writer.finish().unwrap();
+
+    let storage = Storage::scan_zip(&archive).unwrap();
+    assert_eq!(storage.count(), 3);
+    assert_eq!(storage.warnings.len(), 1, "{:?}", storage.warnings);
+    assert!(storage.warnings[0].contains("ocio/2.3.0/package.toml"));
+    assert!(storage.has("usd-24.0.0"));
+
+    let maya = storage.get("maya-2026.1.0").unwrap();
+    assert_eq!(
+        maya.package_source.as_deref(),
+        Some(format!("zip://{}!maya/2026.1.0/package.py", archive.display()).as_str())
+    );
+    assert!(storage.has("ocio-2.3.0"));
+}
+
+#[test]
+fn test_cli_which() {
+    let repo = create_test_repo(&[("ocio", "2.1.0", &[]), ("ocio", "2.3.0", &[])]);
+    for version in ["2025.0.0", "2026.1.0"] {
+        create_package_custom(
+            repo.path(),
+            "maya",
+            version,
+            &format!(
+                r#"def get_package():
+    p = Package("maya", "{version}")
+    p.add_req("ocio@2")
+    env = Env("default")
+    env.add(Evar("MAYA_ROOT", "/opt/maya/{version}", "set"))
+    p.add_env(env)
+    p.add_app(App("maya").with_path("{{MAYA_ROOT}}/bin/maya"))
+    p.add_app(App("mayapy"))
+    return p
+"#
+            ),
+        );
+    }
+
+    let which = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_pkg"))
+            .arg("-r")
+            .arg(repo.path())
+            .arg("which")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let out = which(&["maya@2025"]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "maya-2025.0.0");
+    assert!(Path::new(lines[1]).is_absolute());
+    assert!(lines[1].replace('\\', "/").ends_with("/opt/maya/2025.0.0/bin/maya"));
+
+    let out = which(&["maya", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["package"], "maya-2026.1.0");
+    assert_eq!(json["app"], "maya");
+
+    // App without a path, and an unresolvable package
+    let out = which(&["maya", "mayapy"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no executable path"));
+    assert!(!which(&["nuke"]).status.success());
+}
+
+#[test]
+fn test_cli_diff_env() {
+    let repo = create_test_repo(&[("ocio", "2.3.0", &[])]);
+    for (name, config, extra) in [("lookdev", "/shows/a.ocio", "LOOKDEV_MODE"), ("comp", "/shows/b.ocio", "COMP_MODE")] {
+        create_package_custom(
+            repo.path(),
+            name,
+            "1.0.0",
+            &format!(
+                r#"def get_package():
+    p = Package("{name}", "1.0.0")
+    p.add_req("ocio")
+    env = Env("default")
+    env.add(Evar("OCIO", "{config}", "set"))
+    env.add(Evar("SHOW", "demo", "set"))
+    env.add(Evar("{extra}", "1", "set"))
+    p.add_env(env)
+    return p
+"#
+            ),
+        );
+    }
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_pkg"))
+        .arg("-r")
+        .arg(repo.path())
+        .args(["diff", "lookdev", "comp", "--env", "--json"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+
+    let delta: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(delta["changed"]["OCIO"]["old"], "/shows/a.ocio");
+    assert_eq!(delta["changed"]["OCIO"]["new"], "/shows/b.ocio");
+    assert!(delta["changed"].get("SHOW").is_none());
+    assert_eq!(delta["added"]["COMP_MODE"], "1");
+    assert_eq!(delta["removed"], serde_json::json!(["LOOKDEV_MODE"]));
+}